@@ -0,0 +1,136 @@
+use crate::book::Chapter;
+use crate::slug::Slugger;
+use crate::tags::{index_chapter, index_page, index_pages};
+use crate::title::Titles;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::process::Command;
+
+/// Directory of the generated author pages, relative to the notes dir
+pub const AUTHOR_DIR: &str = "authors";
+
+/// A contributor with the files they wrote most lines of
+#[derive(Debug, Clone, PartialEq)]
+pub struct Author {
+    /// The name as in the git history
+    pub name: String,
+    pub files: Vec<String>,
+}
+
+/// Authors keyed by their slug
+pub type Authors = BTreeMap<String, Author>;
+
+/// Attribute each markdown file of `book` to the author of most of its lines by
+/// `git blame`. Files git doesn't know, e.g. outside of a repository, are left out.
+pub fn collect(book: &Chapter, root: &Path, slugger: &Slugger) -> Authors {
+    let mut authors = Authors::new();
+    for file in book.all_files().into_iter().filter(|f| f.to_lowercase().ends_with(".md")) {
+        let name = match primary_author(root, file) {
+            Some(name) => name,
+            None => continue,
+        };
+        let slug = slugger.slug(&name);
+        if slug.is_empty() {
+            continue;
+        }
+        let author = authors.entry(slug).or_insert_with(|| Author { name, files: vec![] });
+        author.files.push(file.clone());
+    }
+    authors
+}
+
+// The author of most lines of `file`, the first one in the blame on a tie
+fn primary_author(root: &Path, file: &str) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["blame", "--line-porcelain", "--"])
+        .arg(file)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let blame = String::from_utf8_lossy(&output.stdout);
+    let mut lines: HashMap<&str, (usize, usize)> = HashMap::new();
+    for (i, name) in blame.lines().filter_map(|l| l.strip_prefix("author ")).enumerate() {
+        lines.entry(name).or_insert((0, i)).0 += 1;
+    }
+    lines
+        .into_iter()
+        .max_by(|(_, (a, first_a)), (_, (b, first_b))| a.cmp(b).then(first_b.cmp(first_a)))
+        .map(|(name, _)| name.to_string())
+}
+
+/// Path of the page of the author with `slug`
+pub fn page(slug: &str) -> String {
+    index_page(AUTHOR_DIR, slug)
+}
+
+/// The author index chapter: its README lists all authors, followed by a page per author
+pub fn chapter(authors: &Authors) -> Chapter {
+    index_chapter(AUTHOR_DIR, authors.keys())
+}
+
+/// The README titled `heading` and the page of each author as `(path, content)`,
+/// the pages link the notes with their resolved `titles`
+pub fn pages(authors: &Authors, heading: &str, titles: &Titles) -> Vec<(String, String)> {
+    let entries = authors
+        .iter()
+        .map(|(slug, author)| (slug.as_str(), author.name.as_str(), author.files.as_slice()));
+    index_pages(AUTHOR_DIR, heading, entries, titles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::fixture;
+
+    fn git(dir: &Path, author: &str, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", &format!("user.name={}", author), "-c", "user.email=a@example.com"])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success());
+    }
+
+    #[test]
+    fn authors_test() {
+        let dir = fixture(&[]);
+        std::fs::create_dir_all(dir.join("guide")).unwrap();
+        git(&dir, "Ada", &["init", "-q"]);
+        std::fs::write(dir.join("about.md"), "# About\n").unwrap();
+        std::fs::write(dir.join("guide/setup.md"), "# Setup\n").unwrap();
+        git(&dir, "Ada", &["add", "."]);
+        git(&dir, "Ada", &["commit", "-q", "-m", "notes"]);
+        std::fs::write(dir.join("guide/setup.md"), "# Setup\n\nInstall it.\nRun it.\n").unwrap();
+        git(&dir, "Grace Hopper", &["commit", "-q", "-am", "setup"]);
+        std::fs::write(dir.join("draft.md"), "# Draft\n").unwrap();
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &["about.md".to_string(), "draft.md".to_string(), "guide/setup.md".to_string()],
+        );
+
+        let authors = collect(&book, &dir, &Slugger::default());
+
+        assert_eq!(vec!["ada", "grace-hopper"], authors.keys().collect::<Vec<_>>());
+        assert_eq!(vec!["about.md"], authors["ada"].files);
+        assert_eq!(vec!["guide/setup.md"], authors["grace-hopper"].files);
+        let pages = pages(&authors, "Authors", &Titles::new());
+        assert_eq!(
+            (
+                "authors/README.md".to_string(),
+                "# Authors\n\n- [Ada](ada.md) (1)\n- [Grace Hopper](grace-hopper.md) (1)\n".to_string()
+            ),
+            pages[0]
+        );
+        assert_eq!(
+            ("authors/grace-hopper.md".to_string(), "# Grace Hopper\n\n- [Setup](../guide/setup.md)\n".to_string()),
+            pages[2]
+        );
+    }
+}
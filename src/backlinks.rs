@@ -0,0 +1,129 @@
+use crate::book::Chapter;
+use crate::links;
+use crate::render::target;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// First line of the generated backlinks section of a file
+pub const START: &str = "<!-- backlinks -->";
+/// Last line of the generated backlinks section of a file
+pub const END: &str = "<!-- /backlinks -->";
+
+/// Files linking to a file, keyed by the linked file
+pub type Backlinks = BTreeMap<String, BTreeSet<String>>;
+
+/// The markdown files of `book` linking to each other, from the inline links
+/// and the wiki links (`[[Note]]`, by file name) of their `contents`. Links of
+/// a file to itself and those of the backlinks sections are left out.
+pub fn collect(book: &Chapter, contents: &HashMap<String, String>) -> Backlinks {
+    let files = book
+        .all_files()
+        .into_iter()
+        .filter(|f| f.to_lowercase().ends_with(".md"))
+        .collect::<Vec<_>>();
+    // wiki links name the file without extension, a path only if ambiguous
+    let mut names = HashMap::new();
+    for &file in &files {
+        let stem = file.rsplit('/').next().unwrap_or(file);
+        let stem = stem.strip_suffix(".md").unwrap_or(stem).to_lowercase();
+        names.entry(stem).or_insert(file);
+        names.insert(file.trim_end_matches(".md").to_lowercase(), file);
+    }
+
+    let mut backlinks = Backlinks::new();
+    for &file in &files {
+        let content = match contents.get(file.as_str()) {
+            Some(content) => strip(content),
+            None => continue,
+        };
+        let linked = links::targets(content)
+            .iter()
+            .filter_map(|target| links::resolve(file, target))
+            .filter(|target| files.contains(&target))
+            .chain(
+                links::wikilinks(content)
+                    .iter()
+                    .filter_map(|name| names.get(&name.trim_end_matches(".md").to_lowercase()))
+                    .map(|target| target.to_string()),
+            )
+            .collect::<Vec<_>>();
+        for target in linked.into_iter().filter(|target| target != file) {
+            backlinks.entry(target).or_default().insert(file.clone());
+        }
+    }
+    backlinks
+}
+
+/// `content` without its backlinks section
+pub fn strip(content: &str) -> &str {
+    match content.find(START) {
+        Some(start) => match content[start..].find(END) {
+            Some(end) => {
+                let end = start + end + END.len();
+                // the section is always the end of the file
+                if content[end..].trim().is_empty() {
+                    return &content[..start];
+                }
+                content
+            }
+            None => content,
+        },
+        None => content,
+    }
+}
+
+/// `content` with its backlinks section titled `heading` listing `links` as
+/// `(title, relative link)`, the section is removed if there are none
+pub fn update(content: &str, heading: &str, links: &[(String, String)]) -> String {
+    let stripped = strip(content);
+    if links.is_empty() {
+        if stripped.len() == content.len() {
+            return content.to_string();
+        }
+        return format!("{}\n", stripped.trim_end());
+    }
+
+    let mut updated = format!("{}\n\n{}\n## {}\n\n", stripped.trim_end(), START, heading);
+    for (title, link) in links {
+        updated += &format!("- [{}]({})\n", title, target(link));
+    }
+    updated += END;
+    updated.push('\n');
+    updated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collect_test() {
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &["a.md".to_string(), "guide/b.md".to_string(), "guide/c.md".to_string()],
+        );
+        let mut contents = HashMap::new();
+        contents.insert("a.md".to_string(), "[B](guide/b.md) [self](a.md) [web](https://x.org)".to_string());
+        contents.insert("guide/b.md".to_string(), "[[C]]\n".to_string());
+        let stale = format!("{}\n## Backlinks\n\n- [B](../a.md)\n{}\n", START, END);
+        contents.insert("guide/c.md".to_string(), format!("[A](../a.md#top)\n\n{}", stale));
+
+        let backlinks = collect(&book, &contents);
+        assert_eq!(3, backlinks.len());
+        assert_eq!(vec!["guide/c.md"], backlinks["a.md"].iter().collect::<Vec<_>>());
+        assert_eq!(vec!["a.md"], backlinks["guide/b.md"].iter().collect::<Vec<_>>());
+        assert_eq!(vec!["guide/b.md"], backlinks["guide/c.md"].iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn update_test() {
+        let links = vec![("A".to_string(), "../a.md".to_string())];
+        let updated = update("# B\n\nText\n", "Backlinks", &links);
+        assert_eq!(
+            "# B\n\nText\n\n<!-- backlinks -->\n## Backlinks\n\n- [A](../a.md)\n<!-- /backlinks -->\n",
+            updated
+        );
+        assert_eq!(updated, update(&updated, "Backlinks", &links));
+        assert_eq!("# B\n\nText\n", update(&updated, "Backlinks", &[]));
+        assert_eq!("# B", update("# B", "Backlinks", &[]));
+    }
+}
@@ -1,31 +1,31 @@
-use std::path::Path;
-use std::str::FromStr;
-use std::string::ParseError;
+use crate::i18n::Labels;
+use crate::meta::ChapterMetas;
+use crate::normalize::nfc;
+use crate::render::{Format, RenderContext};
+use crate::slug::Slugger;
+use crate::title::Titles;
 use titlecase::titlecase;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 
-#[derive(Debug, PartialEq)]
-pub enum Format {
-    Md(char),
-    Git(char),
-}
-
-impl FromStr for Format {
-    type Err = ParseError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "md" => Ok(Format::Md('-')),
-            "git" => Ok(Format::Git('*')),
-            _ => panic!("Error: Invalid format {}", s),
-        }
-    }
-}
-
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Chapter {
     pub name: String,
     pub files: Vec<String>,
     pub chapter: Vec<Chapter>,
+    /// Kept order of the files and sub chapters mixed, empty for files first, then chapters
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub order: Vec<Item>,
+}
+
+/// Entry of a chapter, a file by its path or a sub chapter by its name
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Item {
+    File(String),
+    Chapter(String),
 }
 
 impl Chapter {
@@ -34,75 +34,101 @@ impl Chapter {
             name,
             files: vec![],
             chapter: vec![],
+            order: vec![],
         };
 
         for entry in entries {
-            chapter.add_entry(entry.split('/').collect::<Vec<_>>(), "");
+            chapter.add_entry(entry.split('/').collect::<Vec<_>>(), entry);
         }
 
         chapter
     }
 
-    // This is a recursive function to add new chapters and files to an existing chapter.
-    fn add_entry(&mut self, entry: Vec<&str>, root: &str) {
-        let new_root = match root {
-            "" => entry[0].to_string(),
-            _ => format!("{}/{}", root, entry[0]),
+    /// Chapter tree of `entries` with the entries below the source dirs of `merges` moved
+    /// into the chapter of their target path, e.g. `("archive/rust-old", "notes/rust")`.
+    /// The files keep their paths, the longest matching source dir wins.
+    pub fn merged(name: String, entries: &[String], merges: &[(String, String)]) -> Chapter {
+        let mut chapter = Chapter {
+            name,
+            files: vec![],
+            chapter: vec![],
+            order: vec![],
         };
 
+        for entry in entries {
+            let merge = merges
+                .iter()
+                .map(|(source, target)| (source.trim_matches('/'), target.trim_matches('/')))
+                .filter(|(source, _)| entry.strip_prefix(source).is_some_and(|r| r.starts_with('/')))
+                .max_by_key(|(source, _)| source.len());
+            let path = match merge {
+                Some((source, "")) => entry[source.len() + 1..].to_string(),
+                Some((source, target)) => format!("{}{}", target, &entry[source.len()..]),
+                None => entry.to_string(),
+            };
+            chapter.add_entry(path.split('/').collect::<Vec<_>>(), entry);
+        }
+
+        chapter
+    }
+
+    // This is a recursive function to add new chapters and files to an existing chapter,
+    // `entry` are the chapters down to the `file`.
+    fn add_entry(&mut self, entry: Vec<&str>, file: &str) {
         if entry.len() > 1 {
-            if let Some(chapter) = self.chapter.iter_mut().find(|c| c.name == entry[0]) {
-                chapter.add_entry(entry[1..].to_owned(), &new_root)
+            // directories named as NFD and NFC (e.g. from macOS and Linux) are the same chapter
+            if let Some(chapter) = self.chapter.iter_mut().find(|c| nfc(&c.name) == nfc(entry[0])) {
+                chapter.add_entry(entry[1..].to_owned(), file)
             } else {
                 let mut chapter = Chapter {
                     name: entry[0].to_string(),
                     files: vec![],
                     chapter: vec![],
+                    order: vec![],
                 };
-                chapter.add_entry(entry[1..].to_owned(), &new_root);
+                chapter.add_entry(entry[1..].to_owned(), file);
 
                 self.chapter.push(chapter);
             }
         } else {
-            self.files.push(new_root)
+            self.files.push(file.to_string())
         }
     }
 
     pub fn get_summary_file(&self, format: &Format, prefered_chapter: &Option<Vec<String>>) -> String {
-        // create markdown summary file
-        /*
-        gitbook format:
-        # Summary
-
-        * [First page's title](page1/README.md)
-            * [Some child page](page1/page1-1.md)
-            * [Some other child page](part1/page1-2.md)
-        * [Second page's title](page2/README.md)
-            * [Some child page](page2/page2-1.md)
-            * [Some other child page](part2/page2-2.md)
-
-        mdbook format:
-        # Summary
-
-        - [mdBook](README.md)
-        - [Command Line Tool](cli/README.md)
-            - [init](cli/init.md)
-            - [build](cli/build.md)
-            - [watch](cli/watch.md)
-            - [serve](cli/serve.md)
-            - [test](cli/test.md)
-            - [clean](cli/clean.md)
-        */
-
-        let indent_level = 0;
-        let mut summary: String = "".to_string();
-        summary.push_str(&format!("# {}\n\n", self.name));
-        match format {
-            Format::Md(list_char) => summary += &print_files(&self.files, list_char, indent_level),
-            Format::Git(list_char) => summary += &print_files(&self.files, list_char, indent_level),
-        }
+        self.get_summary_file_with_titles(format, prefered_chapter, &Titles::new())
+    }
+
+    // Same as `get_summary_file`, but entries found in `titles` (keyed by their path) use the
+    // resolved title instead of the one derived from the file or chapter name.
+    pub fn get_summary_file_with_titles(
+        &self,
+        format: &Format,
+        prefered_chapter: &Option<Vec<String>>,
+        titles: &Titles,
+    ) -> String {
+        let context = RenderContext {
+            sort: prefered_chapter,
+            titles,
+            introduction: None,
+            glossary: None,
+            index: None,
+            labels: &Labels::default(),
+            slugger: &Slugger::default(),
+            readme_names: &default_readme_names(),
+            meta: &ChapterMetas::new(),
+            max_title_len: None,
+            updated: &HashMap::new(),
+            no_readme: None,
+            drafts: &HashSet::new(),
+        };
+        format.renderer().render(self, &context)
+    }
+
+    /// Sub chapters with the prefered chapters (case insensitive) first, in the given order
+    pub fn sorted_chapters(&self, prefered_chapter: &Option<Vec<String>>) -> Vec<&Chapter> {
+        let mut chapters: Vec<&Chapter> = vec![];
 
-        // first prefered chapters (sort)
         if let Some(chapter_names) = prefered_chapter {
             for chapter_name in chapter_names {
                 if let Some(chapter) = self
@@ -110,104 +136,140 @@ impl Chapter {
                     .iter()
                     .find(|c| c.name.to_lowercase() == chapter_name.to_lowercase())
                 {
-                    summary += &chapter.create_tree_for_summary(&format, indent_level);
-
-                    // match format {
-                        // Format::Md(list_char) => summary += &chapter.create_tree_for_summary(list_char, indent_level),
-                        // Format::Git(list_char) => summary += &chapter.create_tree_for_summary(list_char, indent_level),
-                    // }
+                    if !chapters.iter().any(|p| std::ptr::eq(*p, chapter)) {
+                        chapters.push(chapter);
+                    }
                 }
             }
         }
 
         for c in &self.chapter {
-            if let Some(chapter_names) = prefered_chapter {
-                if chapter_names
-                    .iter()
-                    .map(|n| n.to_lowercase())
-                    .any(|x| x == c.name.to_lowercase())
-                {
-                    continue;
+            if !chapters.iter().any(|p| std::ptr::eq(*p, c)) {
+                chapters.push(c);
+            }
+        }
+        chapters
+    }
+
+    /// Remove `file` from this chapter or its sub chapters, dropping chapters left empty.
+    /// Returns whether the file was found.
+    pub fn remove_file(&mut self, file: &str) -> bool {
+        if let Some(pos) = self.files.iter().position(|f| f == file) {
+            self.files.remove(pos);
+            return true;
+        }
+        match self.chapter.iter_mut().position(|c| c.remove_file(file)) {
+            Some(pos) => {
+                if self.chapter[pos].files.is_empty() && self.chapter[pos].chapter.is_empty() {
+                    self.chapter.remove(pos);
                 }
+                true
             }
+            None => false,
+        }
+    }
 
-            summary += &c.create_tree_for_summary(&format, indent_level);
+    /// Keep only the files for which `keep` returns true, dropping chapters left empty
+    pub fn retain_files<F: Fn(&str) -> bool>(&mut self, keep: &F) {
+        self.files.retain(|f| keep(f));
+        for c in &mut self.chapter {
+            c.retain_files(keep);
+        }
+        self.chapter.retain(|c| !c.files.is_empty() || !c.chapter.is_empty());
+    }
 
-            // match format {
-                // Format::Md(list_char) => summary += &c.create_tree_for_summary(list_char, indent_level),
-                // Format::Git(list_char) => summary += &c.create_tree_for_summary(list_char, indent_level),
-            // }
+    /// Order the files by their file name and the sub chapters by their name with `compare`
+    pub fn sort_by<F: Fn(&str, &str) -> Ordering>(&mut self, compare: &F) {
+        let file_name = |file: &String| file.rsplit('/').next().unwrap_or(file).to_string();
+        self.files.sort_by(|a, b| compare(&file_name(a), &file_name(b)));
+        for c in &mut self.chapter {
+            c.sort_by(compare);
         }
-        summary
+        self.chapter.sort_by(|a, b| compare(&a.name, &b.name));
     }
 
-    fn create_tree_for_summary(&self, format: &Format, indent: usize) -> String {
-        let mut summary: String = " ".repeat(4 * indent);
-        let list_char = match format {
-            Format::Md(c) => c,
-            Format::Git(c) => c,
-        };
+    /// Order the files and sub chapters like the entry paths of `order` (e.g. those of an
+    /// existing summary), a chapter by its first entry, and keep how they are mixed.
+    /// Entries not in `order` come last, in their current order.
+    pub fn keep_order(&mut self, order: &[String]) {
+        let position = |file: &String| order.iter().position(|o| o == file).unwrap_or(usize::MAX);
+        self.files.sort_by_key(position);
+        for c in &mut self.chapter {
+            c.keep_order(order);
+        }
+        let first = |c: &Chapter| c.all_files().into_iter().map(position).min().unwrap_or(usize::MAX);
+        self.chapter.sort_by_key(first);
 
-        if let Some(readme) = self
+        let mut items = self
             .files
             .iter()
-            .find(|f| f.to_lowercase().ends_with("/readme.md"))
-        {
-            summary += &format!(
-                "{} [{}]({})\n",
-                list_char,
-                make_title_case(&self.name),
-                readme
-            )
-        } else {
-            match format {
-                Format::Md(_) => summary.push_str(&format!(
-                        "{} [{}](#)\n",
-                        list_char,
-                        make_title_case(&self.name)
-                )),
-                Format::Git(_) => summary.push_str(&format!(
-                        "{} {}\n",
-                        list_char,
-                        make_title_case(&self.name)
-                )),
-            }
-        }
+            .map(|f| (position(f), Item::File(f.clone())))
+            .chain(self.chapter.iter().map(|c| (first(c), Item::Chapter(c.name.clone()))))
+            .collect::<Vec<_>>();
+        items.sort_by_key(|(position, _)| *position);
+        self.order = items.into_iter().map(|(_, item)| item).collect();
+    }
 
-        summary += &print_files(&self.files, list_char, indent + 1);
+    /// Position of `item` in the kept order, entries not in it come last
+    pub fn position(&self, item: &Item) -> usize {
+        self.order.iter().position(|o| o == item).unwrap_or(usize::MAX)
+    }
 
+    /// Files of this chapter and all its sub chapters
+    pub fn all_files(&self) -> Vec<&String> {
+        let mut files = self.files.iter().collect::<Vec<_>>();
         for c in &self.chapter {
-            summary += &c.create_tree_for_summary(&format, indent + 1);
+            files.extend(c.all_files());
         }
-        summary
+        files
     }
-}
 
-fn print_files(files: &[String], list_char: &char, indent: usize) -> String {
-    files
-        .iter()
-        .filter(|f| !f.to_lowercase().ends_with("/readme.md"))
-        .map(|f| {
-            format!(
-                "{}{} [{}]({})\n",
-                " ".repeat(4 * indent),
-                list_char,
-                make_title_case(Path::new(&f).file_stem().unwrap().to_str().unwrap()),
-                &f
-            )
+    /// The README of this chapter, the file named like the first of `names` (case
+    /// insensitive) found. Files at the root of the book are never a README.
+    pub fn readme(&self, names: &[String]) -> Option<&String> {
+        names.iter().find_map(|name| {
+            self.files.iter().find(|f| match f.rsplit_once('/') {
+                Some((_, file_name)) => file_name.eq_ignore_ascii_case(name),
+                None => false,
+            })
         })
-        .collect::<Vec<String>>()
-        .join("")
+    }
+
+    /// Files of this chapter without its README
+    pub fn pages<'a>(&'a self, names: &[String]) -> impl Iterator<Item = &'a String> {
+        let readme = self.readme(names);
+        self.files.iter().filter(move |f| Some(*f) != readme)
+    }
+}
+
+/// README names used unless configured otherwise
+pub fn default_readme_names() -> Vec<String> {
+    vec!["README.md".to_string()]
+}
+
+pub(crate) fn make_title_case(name: &str) -> String {
+    titlecase(&title_words(name))
+}
+
+/// Resolved title of `file`, its title cased file name if it has none
+pub(crate) fn file_title(titles: &Titles, file: &str) -> String {
+    match titles.get(file) {
+        Some(title) => title.clone(),
+        None => make_title_case(&Path::new(file).file_stem().unwrap_or_default().to_string_lossy()),
+    }
 }
 
-fn make_title_case(name: &str) -> String {
-    titlecase(
-        &name
-            .chars()
-            .skip_while(|c| !c.is_alphabetic())
-            .map(|c| if ['_', '-'].contains(&c) { ' ' } else { c })
-            .collect::<String>(),
-    )
+// `name` without leading non-letters (`01_`) and with spaces for `_` and `-`.
+// Names without any letters (`2024`, `2024-01-05`) are kept as they are.
+pub(crate) fn title_words(name: &str) -> String {
+    let name = nfc(name);
+    if !name.chars().any(char::is_alphabetic) {
+        return name.trim().to_string();
+    }
+    name.chars()
+        .skip_while(|c| !c.is_alphabetic())
+        .map(|c| if ['_', '-'].contains(&c) { ' ' } else { c })
+        .collect()
 }
 
 #[cfg(test)]
@@ -219,18 +281,142 @@ mod tests {
         assert_eq!("Chapter 1", make_title_case("1-chapter_1"));
         assert_eq!("Chapter 23", make_title_case("chapter_23"));
         assert_eq!("Chapter 25", make_title_case("chapter-25"));
+        assert_eq!("0001", make_title_case("0001"));
+        assert_eq!("2024-01-05", make_title_case("2024-01-05"));
+    }
+
+    #[test]
+    fn sorted_chapters_test() {
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &["a/file.md".to_string(), "b/file.md".to_string(), "c/file.md".to_string()],
+        );
+        let names = |sort| {
+            book.sorted_chapters(&sort)
+                .iter()
+                .map(|c| c.name.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(vec!["a", "b", "c"], names(None));
+        assert_eq!(
+            vec!["c", "a", "b"],
+            names(Some(vec!["C".to_string(), "x".to_string(), "c".to_string()]))
+        );
+    }
+
+    #[test]
+    fn readme_test() {
+        let chapter = Chapter {
+            name: "part1".to_string(),
+            files: vec![
+                "part1/NOTREADME.md".to_string(),
+                "part1/index.md".to_string(),
+                "part1/readme.md".to_string(),
+            ],
+            chapter: vec![],
+            order: vec![],
+        };
+        let names = vec!["README.md".to_string(), "index.md".to_string()];
+
+        assert_eq!(Some(&"part1/readme.md".to_string()), chapter.readme(&names));
+        assert_eq!(Some(&"part1/index.md".to_string()), chapter.readme(&names[1..]));
+        assert_eq!(None, chapter.readme(&["_index.md".to_string()]));
+        assert_eq!(
+            vec!["part1/NOTREADME.md", "part1/index.md"],
+            chapter.pages(&names).collect::<Vec<_>>()
+        );
+
+        let book = Chapter::new("Summary".to_string(), &["README.md".to_string()]);
+        assert_eq!(None, book.readme(&names));
     }
 
     #[test]
-    fn file_print_test() {
-        let expected = r#"- [WritingIsGood](part1/WritingIsGood.md)
-- [GitbookIsNice](part1/GitbookIsNice.md)
-"#;
-        let input = vec![
-            "part1/README.md".to_string(),
-            "part1/WritingIsGood.md".to_string(),
-            "part1/GitbookIsNice.md".to_string(),
+    fn remove_file_test() {
+        let mut book = Chapter::new(
+            "Summary".to_string(),
+            &["index.md".to_string(), "a/file.md".to_string(), "b/file.md".to_string()],
+        );
+
+        assert!(book.remove_file("index.md"));
+        assert!(book.remove_file("a/file.md"));
+        assert!(!book.remove_file("c/file.md"));
+        assert_eq!(Chapter::new("Summary".to_string(), &["b/file.md".to_string()]), book);
+    }
+
+    #[test]
+    fn merged_test() {
+        let entries = ["notes/rust/a.md", "archive/rust-old/b.md", "archive/rust-old/x/c.md"]
+            .iter()
+            .chain(&["old/d.md"])
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>();
+        let merges = vec![
+            ("archive/rust-old".to_string(), "notes/rust".to_string()),
+            ("archive".to_string(), "Archive".to_string()),
+            ("old/".to_string(), "".to_string()),
         ];
-        assert_eq!(expected, print_files(&input, &'-', 0));
+        let book = Chapter::merged("Summary".to_string(), &entries, &merges);
+
+        assert_eq!(vec!["old/d.md"], book.files);
+        assert_eq!(vec!["notes"], book.chapter.iter().map(|c| &c.name).collect::<Vec<_>>());
+        let rust = &book.chapter[0].chapter[0];
+        assert_eq!(vec!["notes/rust/a.md", "archive/rust-old/b.md"], rust.files);
+        assert_eq!(vec!["archive/rust-old/x/c.md"], rust.chapter[0].files);
+        let book = Chapter::merged("Summary".to_string(), &entries, &[]);
+        assert_eq!(Chapter::new("Summary".to_string(), &entries), book);
+    }
+
+    #[test]
+    fn keep_order_test() {
+        let mut book = Chapter::new(
+            "Summary".to_string(),
+            &[
+                "a/new.md".to_string(),
+                "a/x.md".to_string(),
+                "b/README.md".to_string(),
+                "c/y.md".to_string(),
+                "z.md".to_string(),
+            ],
+        );
+        let order = ["z.md", "c/y.md", "a/x.md"].iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        book.keep_order(&order);
+
+        assert_eq!(vec!["z.md"], book.files);
+        assert_eq!(
+            vec!["c", "a", "b"],
+            book.chapter.iter().map(|c| c.name.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(vec!["a/x.md", "a/new.md"], book.chapter[1].files);
+    }
+
+    #[test]
+    fn retain_files_test() {
+        let mut book = Chapter::new(
+            "Summary".to_string(),
+            &["index.md".to_string(), "a/image.png".to_string(), "b/file.md".to_string()],
+        );
+
+        book.retain_files(&|f| f.ends_with(".md"));
+        assert_eq!(
+            Chapter::new("Summary".to_string(), &["index.md".to_string(), "b/file.md".to_string()]),
+            book
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_roundtrip_test() {
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &["file.md".to_string(), "chapter1/file1.md".to_string()],
+        );
+
+        let json = serde_json::to_string(&book).unwrap();
+        assert_eq!(book, serde_json::from_str::<Chapter>(&json).unwrap());
+
+        let format = serde_json::to_string(&Format::gitbook()).unwrap();
+        assert_eq!("\"git\"", format);
+        assert_eq!(Format::gitbook(), serde_json::from_str(&format).unwrap());
     }
 }
@@ -0,0 +1,106 @@
+use crate::render::{entries, target};
+
+/// Entries added, removed or renamed between two summaries, as `(title, path)`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Changes {
+    pub added: Vec<(String, String)>,
+    pub removed: Vec<(String, String)>,
+    /// The old path and the new entry
+    pub renamed: Vec<(String, (String, String))>,
+}
+
+impl Changes {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.renamed.is_empty()
+    }
+}
+
+/// Changes of the entries of the summary `after` to those of `before`, leaving
+/// out `ignored` paths. An entry removed and one added with the same title or
+/// file name count as renamed.
+pub fn diff(before: &str, after: &str, ignored: &[&str]) -> Changes {
+    let keep = |(_, path): &(String, String)| !ignored.contains(&path.as_str());
+    let before = entries(before).into_iter().filter(keep).collect::<Vec<_>>();
+    let after = entries(after).into_iter().filter(keep).collect::<Vec<_>>();
+    let mut added = after
+        .iter()
+        .filter(|(_, path)| !before.iter().any(|(_, p)| p == path))
+        .cloned()
+        .collect::<Vec<_>>();
+    let removed = before
+        .iter()
+        .filter(|(_, path)| !after.iter().any(|(_, p)| p == path))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let file_name = |path: &str| path.rsplit('/').next().unwrap_or(path).to_lowercase();
+    let mut changes = Changes::default();
+    for (title, path) in removed {
+        let renamed = added
+            .iter()
+            .position(|(t, p)| *t == title || file_name(p) == file_name(&path));
+        match renamed {
+            Some(i) => changes.renamed.push((path, added.remove(i))),
+            None => changes.removed.push((title, path)),
+        }
+    }
+    changes.added = added;
+    changes
+}
+
+/// Changelog `existing` (if any) with a section of `changes` dated `date` on top
+pub fn prepend(existing: Option<&str>, date: &str, changes: &Changes) -> String {
+    let mut section = format!("## {}\n\n", date);
+    for (title, path) in &changes.added {
+        section += &format!("- Added [{}]({})\n", title, target(path));
+    }
+    for (old, (title, path)) in &changes.renamed {
+        section += &format!("- Moved `{}` to [{}]({})\n", old, title, target(path));
+    }
+    for (title, path) in &changes.removed {
+        section += &format!("- Removed {} (`{}`)\n", title, path);
+    }
+
+    match existing {
+        Some(existing) => match existing.find("\n## ") {
+            Some(pos) => format!("{}\n{}\n{}", &existing[..pos], section, &existing[pos + 1..]),
+            None => format!("{}\n\n{}", existing.trim_end(), section),
+        },
+        None => format!("# What's new\n\n{}", section),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_test() {
+        let before = "# Summary\n\n- [Guide](#)\n    - [Setup](guide/setup.md)\n    - [Old](guide/old.md)\n\
+                      - [About](about.md)\n- [News](NEWS.md)\n";
+        let after = "# Summary\n\n* [Setup](setup/setup.md)\n* [New](new.md)\n* [About](about.md)\n";
+        let changes = diff(before, after, &["NEWS.md"]);
+        assert_eq!(
+            Changes {
+                added: vec![("New".to_string(), "new.md".to_string())],
+                removed: vec![("Old".to_string(), "guide/old.md".to_string())],
+                renamed: vec![(
+                    "guide/setup.md".to_string(),
+                    ("Setup".to_string(), "setup/setup.md".to_string())
+                )],
+            },
+            changes
+        );
+
+        let changelog = prepend(None, "2021-03-04", &changes);
+        assert_eq!(
+            "# What's new\n\n## 2021-03-04\n\n- Added [New](new.md)\n\
+             - Moved `guide/setup.md` to [Setup](setup/setup.md)\n- Removed Old (`guide/old.md`)\n",
+            changelog
+        );
+        let changes = diff(after, "* [About](about.md)\n", &[]);
+        assert!(prepend(Some(&changelog), "2021-03-05", &changes)
+            .starts_with("# What's new\n\n## 2021-03-05\n\n- Removed Setup"));
+        assert!(diff(after, after, &[]).is_empty());
+    }
+}
@@ -0,0 +1,243 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// Order of files and chapters with the same parent
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Collation {
+    /// By unicode code point, `Z` before `a` and `ä` after `z`
+    #[default]
+    Codepoint,
+    /// The way native readers of the language expect, e.g. `de` or `sv_SE`
+    Locale(String),
+    /// Chinese characters by their pinyin, after all other characters
+    Pinyin,
+}
+
+// Languages with a tailoring below or whose expectations are met by the base rules.
+// Not ICU collation: accents and case only break ties, letters of other scripts come
+// by code point.
+const LOCALES: &[&str] = &[
+    "da", "de", "en", "es", "fi", "fr", "it", "nb", "nl", "nn", "no", "pt", "sv",
+];
+
+impl FromStr for Collation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "codepoint" => return Ok(Collation::Codepoint),
+            "pinyin" => return Ok(Collation::Pinyin),
+            _ => {}
+        }
+        // `de`, `sv_SE`, `sv-SE` or `sv_SE.UTF-8` from the environment, but no variants
+        // like `de-u-co-phonebk` the rules don't have
+        let (locale, _charset) = s.split_once('.').unwrap_or((s, ""));
+        let mut parts = locale.split(['_', '-']);
+        let lang = parts.next().unwrap_or("").to_lowercase();
+        let region = parts.next().is_none_or(|r| r.len() == 2 && r.chars().all(|c| c.is_ascii_alphabetic()));
+        if LOCALES.contains(&lang.as_str()) && region && parts.next().is_none() {
+            Ok(Collation::Locale(lang))
+        } else {
+            Err(format!(
+                "Invalid collation {} (one of codepoint, pinyin, {})",
+                s,
+                LOCALES.join(", ")
+            ))
+        }
+    }
+}
+
+impl Collation {
+    /// Names starting with a number (`01_intro`, `2-setup`, `10 usage`) come in the order of
+    /// these numbers, the others and those with the same number by the collation
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        let by_number = match (number_prefix(a), number_prefix(b)) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            _ => Ordering::Equal,
+        };
+        by_number.then_with(|| self.compare_names(a, b))
+    }
+
+    fn compare_names(&self, a: &str, b: &str) -> Ordering {
+        match self {
+            Collation::Codepoint => a.cmp(b),
+            Collation::Locale(lang) => {
+                let (ka, kb) = (sort_key(lang, a), sort_key(lang, b));
+                ka.cmp(&kb).then_with(|| a.cmp(b))
+            }
+            Collation::Pinyin => {
+                let (ka, kb) = (sort_key("zh", a), sort_key("zh", b));
+                ka.cmp(&kb).then_with(|| a.cmp(b))
+            }
+        }
+    }
+}
+
+// Value of the digits `name` starts with
+fn number_prefix(name: &str) -> Option<u128> {
+    let end = name.find(|c: char| !c.is_ascii_digit()).unwrap_or(name.len());
+    name[..end].parse().ok()
+}
+
+// Rank of a han character in pinyin order
+fn pinyin_rank(c: char) -> Option<u32> {
+    static RANKS: OnceLock<HashMap<char, u32>> = OnceLock::new();
+    RANKS
+        .get_or_init(|| {
+            include_str!("../data/pinyin.txt")
+                .lines()
+                .filter(|l| !l.starts_with('#'))
+                .flat_map(|l| l.chars())
+                .zip(0..)
+                .collect()
+        })
+        .get(&c)
+        .copied()
+}
+
+// Letters sorted as their own letter after a base letter, as (letter, base, rank).
+// The rank keeps several letters after the same base in order.
+fn tailoring(lang: &str) -> &'static [(char, char, u32)] {
+    match lang {
+        "sv" | "fi" => &[('å', 'z', 1), ('ä', 'z', 2), ('æ', 'z', 2), ('ö', 'z', 3), ('ø', 'z', 3)],
+        "da" | "nb" | "nn" | "no" => &[('æ', 'z', 1), ('ä', 'z', 1), ('ø', 'z', 2), ('ö', 'z', 2), ('å', 'z', 3)],
+        "es" => &[('ñ', 'n', 1)],
+        _ => &[],
+    }
+}
+
+// Sort key in three levels: base letters, then accents, then case
+fn sort_key(lang: &str, s: &str) -> (Vec<u32>, Vec<u32>, Vec<bool>) {
+    let mut primary = vec![];
+    let mut secondary = vec![];
+    let mut tertiary = vec![];
+
+    for c in s.chars() {
+        let lower = c.to_lowercase().next().unwrap_or(c);
+        tertiary.push(c != lower);
+
+        if let Some((_, base, rank)) = tailoring(lang).iter().find(|(l, _, _)| *l == lower) {
+            // after every other letter following the base letter
+            primary.push(*base as u32);
+            primary.push(0x10FFFF + rank);
+            secondary.push(0);
+            continue;
+        }
+        if lang == "zh" {
+            if let Some(rank) = pinyin_rank(c) {
+                primary.push(0x110000 + rank);
+                secondary.push(0);
+                continue;
+            }
+        }
+        match fold(lower) {
+            Some(base) => {
+                for b in base.chars() {
+                    primary.push(b as u32);
+                }
+                secondary.push(lower as u32);
+            }
+            None => {
+                primary.push(lower as u32);
+                secondary.push(0);
+            }
+        }
+    }
+    (primary, secondary, tertiary)
+}
+
+// Base letters of accented latin letters
+pub(crate) fn fold(c: char) -> Option<&'static str> {
+    const FOLDS: &[(&str, &str)] = &[
+        ("àáâãäåāăą", "a"),
+        ("æ", "ae"),
+        ("çćĉċč", "c"),
+        ("ďđ", "d"),
+        ("èéêëēĕėęě", "e"),
+        ("ĝğġģ", "g"),
+        ("ĥħ", "h"),
+        ("ìíîïĩīĭįı", "i"),
+        ("ĵ", "j"),
+        ("ķ", "k"),
+        ("ĺļľŀł", "l"),
+        ("ñńņňŉ", "n"),
+        ("òóôõöøōŏő", "o"),
+        ("œ", "oe"),
+        ("ŕŗř", "r"),
+        ("śŝşš", "s"),
+        ("ß", "ss"),
+        ("ţťŧ", "t"),
+        ("ùúûüũūŭůűų", "u"),
+        ("ŵ", "w"),
+        ("ýÿŷ", "y"),
+        ("źżž", "z"),
+    ];
+    FOLDS
+        .iter()
+        .find(|(letters, _)| letters.contains(c))
+        .map(|(_, base)| *base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(collation: &str, names: &[&str]) -> Vec<String> {
+        let collation: Collation = collation.parse().unwrap();
+        let mut names = names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        names.sort_by(|a, b| collation.compare(a, b));
+        names
+    }
+
+    #[test]
+    fn german_test() {
+        assert_eq!(
+            vec!["Apfel", "Äpfel", "Straße", "Strasse2", "Zebra"],
+            sorted("de", &["Zebra", "Äpfel", "Strasse2", "Straße", "Apfel"])
+        );
+    }
+
+    #[test]
+    fn swedish_test() {
+        assert_eq!(
+            vec!["apa", "zebra", "åka", "ärta", "öga"],
+            sorted("sv_SE", &["öga", "åka", "zebra", "ärta", "apa"])
+        );
+    }
+
+    #[test]
+    fn french_test() {
+        assert_eq!(
+            vec!["cote", "coté", "côte", "côté", "dé"],
+            sorted("fr", &["dé", "côté", "coté", "côte", "cote"])
+        );
+    }
+
+    #[test]
+    fn pinyin_test() {
+        // an, bei, ren, shu, zhong
+        assert_eq!(
+            vec!["intro", "安装", "北京", "人工", "数据", "中文"],
+            sorted("pinyin", &["中文", "数据", "北京", "intro", "人工", "安装"])
+        );
+    }
+
+    #[test]
+    fn codepoint_test() {
+        assert_eq!(vec!["Zebra", "apa", "äpple"], sorted("codepoint", &["äpple", "apa", "Zebra"]));
+        assert!("xx".parse::<Collation>().is_err());
+        assert_eq!(Ok(Collation::Locale("sv".to_string())), "sv_SE.UTF-8".parse());
+        assert!("de-u-co-phonebk".parse::<Collation>().is_err());
+    }
+
+    #[test]
+    fn number_prefix_test() {
+        assert_eq!(
+            vec!["01_intro", "02_usage", "2-setup", "10_faq", "about"],
+            sorted("codepoint", &["10_faq", "about", "02_usage", "01_intro", "2-setup"])
+        );
+        assert_eq!(vec!["9.md", "10.md", "Ärger"], sorted("de", &["Ärger", "10.md", "9.md"]));
+    }
+}
@@ -0,0 +1,168 @@
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Order of the files of a chapter
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum SortMode {
+    /// By name, see `Collation`
+    #[default]
+    Name,
+    /// By the date in the front matter, newest first, falling back to the
+    /// modification time
+    FrontMatterDate,
+}
+
+impl FromStr for SortMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(SortMode::Name),
+            "frontmatter-date" => Ok(SortMode::FrontMatterDate),
+            _ => Err(format!("Invalid sort mode {} (one of name, frontmatter-date)", s)),
+        }
+    }
+}
+
+/// Formats tried for front matter dates unless configured otherwise
+pub fn default_date_formats() -> Vec<String> {
+    ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%d"]
+        .iter()
+        .map(|f| f.to_string())
+        .collect()
+}
+
+/// Seconds since the unix epoch of `value`, parsed by the first matching of
+/// `formats`. Formats know `%Y`, `%m`, `%d`, `%H`, `%M` and `%S`, anything
+/// after the matched part (like a time zone) is ignored.
+pub fn parse(value: &str, formats: &[String]) -> Option<i64> {
+    formats.iter().find_map(|format| parse_format(value.trim(), format))
+}
+
+fn parse_format(value: &str, format: &str) -> Option<i64> {
+    // year, month, day, hour, minute, second
+    let mut fields = [1970, 1, 1, 0, 0, 0];
+    let mut rest = value;
+    let mut spec = format.chars();
+    while let Some(c) = spec.next() {
+        if c != '%' {
+            rest = rest.strip_prefix(c)?;
+            continue;
+        }
+        let (field, width) = match spec.next()? {
+            'Y' => (0, 4),
+            'm' => (1, 2),
+            'd' => (2, 2),
+            'H' => (3, 2),
+            'M' => (4, 2),
+            'S' => (5, 2),
+            '%' => {
+                rest = rest.strip_prefix('%')?;
+                continue;
+            }
+            _ => return None,
+        };
+        let digits = rest.chars().take(width).take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 {
+            return None;
+        }
+        fields[field] = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+    }
+
+    let [year, month, day, hour, minute, second] = fields;
+    let valid = (1..=12).contains(&month) && (1..=31).contains(&day);
+    if !valid || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    Some(days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second)
+}
+
+// Days since 1970-01-01 of a date of the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = (month + 9) % 12;
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+// Date of the proleptic Gregorian calendar `days` since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// `timestamp` (seconds since the unix epoch, UTC) written in `format`, which
+/// knows the same specifiers as [`parse`]
+pub fn format(timestamp: i64, format: &str) -> String {
+    let (year, month, day) = civil_from_days(timestamp.div_euclid(86400));
+    let seconds = timestamp.rem_euclid(86400);
+    let mut formatted = String::new();
+    let mut spec = format.chars();
+    while let Some(c) = spec.next() {
+        if c != '%' {
+            formatted.push(c);
+            continue;
+        }
+        match spec.next() {
+            Some('Y') => formatted += &format!("{:04}", year),
+            Some('m') => formatted += &format!("{:02}", month),
+            Some('d') => formatted += &format!("{:02}", day),
+            Some('H') => formatted += &format!("{:02}", seconds / 3600),
+            Some('M') => formatted += &format!("{:02}", seconds / 60 % 60),
+            Some('S') => formatted += &format!("{:02}", seconds % 60),
+            Some(other) => formatted.push(other),
+            None => formatted.push('%'),
+        }
+    }
+    formatted
+}
+
+/// Seconds since the unix epoch of `time`
+pub fn timestamp(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs() as i64,
+        Err(before) => -(before.duration().as_secs() as i64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        let formats = default_date_formats();
+        assert_eq!(Some(0), parse("1970-01-01", &formats));
+        assert_eq!(Some(1614816000), parse("2021-03-04", &formats));
+        assert_eq!(Some(1614852000), parse("2021-03-04T10:00:00+01:00", &formats));
+        assert_eq!(Some(1614852000), parse("2021-03-04 10:00", &formats));
+        assert_eq!(Some(1614816000), parse("04.03.2021", &["%d.%m.%Y".to_string()]));
+        assert_eq!(None, parse("March 4th", &formats));
+        assert_eq!(None, parse("2021-13-04", &formats));
+    }
+
+    #[test]
+    fn format_test() {
+        assert_eq!("1970-01-01", format(0, "%Y-%m-%d"));
+        assert_eq!("04.03.2021 10:00:00", format(1614852000, "%d.%m.%Y %H:%M:%S"));
+        assert_eq!("2000-02-29 100%", format(951782400, "%Y-%m-%d 100%%"));
+        assert_eq!("1969-12-31 23:59", format(-60, "%Y-%m-%d %H:%M"));
+        for date in ["1600-03-01", "2024-12-31", "2100-02-28"] {
+            let formats = [String::from("%Y-%m-%d")];
+            assert_eq!(date, format(parse(date, &formats).unwrap(), "%Y-%m-%d"));
+        }
+    }
+}
@@ -0,0 +1,453 @@
+use crate::frontmatter::{read_head, FrontMatter};
+use crate::{Result, SummaryError};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::DirEntry;
+
+/// Entry of the notes dir as seen by the filters
+pub struct Entry<'a> {
+    /// Path relative to the notes dir, `/` separated
+    pub path: &'a str,
+    pub direntry: &'a DirEntry,
+}
+
+impl<'a> Entry<'a> {
+    pub fn is_dir(&self) -> bool {
+        self.direntry.file_type().is_dir()
+    }
+
+    pub fn file_name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(self.path)
+    }
+}
+
+/// Decides during the walk which entries end up in the summary.
+///
+/// Filters return the reason why an entry is skipped, or `None` to keep it.
+/// A skipped directory is not descended into.
+pub trait EntryFilter {
+    /// Name shown with the reason in verbose mode
+    fn name(&self) -> &str;
+
+    fn skip(&self, entry: &Entry) -> Option<String>;
+}
+
+/// Asks the filters in order, the first one skipping `entry` wins.
+/// Returns the name of that filter and its reason.
+pub fn skip<'a>(filters: &[&'a dyn EntryFilter], entry: &Entry) -> Option<(&'a str, String)> {
+    filters
+        .iter()
+        .find_map(|f| f.skip(entry).map(|reason| (f.name(), reason)))
+}
+
+/// Skips files and directories starting with a `.`
+pub struct Hidden;
+
+impl EntryFilter for Hidden {
+    fn name(&self) -> &str {
+        "hidden"
+    }
+
+    fn skip(&self, entry: &Entry) -> Option<String> {
+        if entry.file_name().starts_with('.') {
+            Some("hidden entry".to_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// Keeps only files with one of the given extensions (case insensitive)
+pub struct Extensions(pub Vec<String>);
+
+impl EntryFilter for Extensions {
+    fn name(&self) -> &str {
+        "extension"
+    }
+
+    fn skip(&self, entry: &Entry) -> Option<String> {
+        if entry.is_dir() {
+            return None;
+        }
+        let ext = Path::new(entry.path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("");
+        if self.0.iter().any(|e| e.eq_ignore_ascii_case(ext)) {
+            None
+        } else {
+            Some(format!("not a {} file", self.0.join("/")))
+        }
+    }
+}
+
+/// Skips the summary file itself (case insensitive). A name without `/`
+/// is skipped in any directory.
+pub struct OutputFile(pub String);
+
+impl EntryFilter for OutputFile {
+    fn name(&self) -> &str {
+        "output"
+    }
+
+    fn skip(&self, entry: &Entry) -> Option<String> {
+        let path = match self.0.contains('/') {
+            true => entry.path,
+            false => entry.file_name(),
+        };
+        if !entry.is_dir() && path.eq_ignore_ascii_case(&self.0) {
+            Some("output file".to_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// Skips the README of the book at the root of the notes dir, given the README names
+pub struct RootReadme(pub Vec<String>);
+
+impl EntryFilter for RootReadme {
+    fn name(&self) -> &str {
+        "root-readme"
+    }
+
+    fn skip(&self, entry: &Entry) -> Option<String> {
+        if self.0.iter().any(|name| entry.path.eq_ignore_ascii_case(name)) {
+            Some("README of the book".to_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// Skips files or chapters given by path or glob (`*`, `**`, `?`)
+pub struct Globs(pub Vec<String>);
+
+impl EntryFilter for Globs {
+    fn name(&self) -> &str {
+        "exclude"
+    }
+
+    fn skip(&self, entry: &Entry) -> Option<String> {
+        self.0
+            .iter()
+            .find(|pattern| is_excluded(entry.path, pattern))
+            .map(|pattern| format!("excluded by {}", pattern))
+    }
+}
+
+/// Skips entries matching the patterns of an ignore file, one per line, read as
+/// `.gitignore`: a pattern with a `/` at its start or in the middle is anchored at
+/// the dir of the file, any other matches at any depth, a trailing `/` only matches
+/// dirs and `!` includes an entry again. The last matching pattern wins. Empty lines
+/// and lines starting with `#` are ignored.
+pub struct IgnoreFile {
+    path: PathBuf,
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreFile {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| SummaryError::Io(path.to_path_buf(), e.to_string()))?;
+        Ok(IgnoreFile {
+            path: path.to_path_buf(),
+            rules: parse_ignore(&content).iter().map(|l| IgnoreRule::new(l)).collect(),
+        })
+    }
+}
+
+impl EntryFilter for IgnoreFile {
+    fn name(&self) -> &str {
+        "ignore-file"
+    }
+
+    fn skip(&self, entry: &Entry) -> Option<String> {
+        // the entries of an ignored dir stay ignored, even when listed on their own
+        let dirs = entry.path.match_indices('/').map(|(i, _)| (&entry.path[..i], true));
+        dirs.chain(std::iter::once((entry.path, entry.is_dir())))
+            .find_map(|(path, is_dir)| ignored_by(&self.rules, path, is_dir))
+            .map(|rule| format!("excluded by {} in {}", rule.line, self.path.display()))
+    }
+}
+
+// A pattern of an ignore file
+struct IgnoreRule {
+    line: String,
+    glob: String,
+    negated: bool,
+    dir_only: bool,
+    anchored: bool,
+}
+
+impl IgnoreRule {
+    fn new(line: &str) -> Self {
+        let (negated, pattern) = match line.strip_prefix('!') {
+            Some(pattern) => (true, pattern),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let dir_only = pattern.ends_with('/');
+        let pattern = pattern.trim_end_matches('/');
+        IgnoreRule {
+            line: line.to_string(),
+            glob: pattern.trim_start_matches('/').to_string(),
+            negated,
+            dir_only,
+            anchored: pattern.contains('/'),
+        }
+    }
+
+    fn matches(&self, path: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        match self.anchored {
+            true => glob_match(&self.glob, path),
+            false => glob_match(&self.glob, path.rsplit('/').next().unwrap_or(path)),
+        }
+    }
+}
+
+// The rule ignoring `path`, `None` if no rule matches or the last matching one is negated
+fn ignored_by<'a>(rules: &'a [IgnoreRule], path: &str, is_dir: bool) -> Option<&'a IgnoreRule> {
+    rules.iter().rev().find(|r| r.matches(path, is_dir)).filter(|r| !r.negated)
+}
+
+/// Ignore file of the mdBook tooling in the notes dir, whose globs are skipped as well
+pub const MDBOOK_IGNORE: &str = ".mdbookignore";
+
+/// Marker file leaving the dir it is in out of the summary, together with everything below it
+pub const NO_SUMMARY: &str = ".nosummary";
+
+/// Skips directories containing the given marker file
+pub struct Marker(pub String);
+
+impl EntryFilter for Marker {
+    fn name(&self) -> &str {
+        "marker"
+    }
+
+    fn skip(&self, entry: &Entry) -> Option<String> {
+        if entry.is_dir() && entry.direntry.path().join(&self.0).exists() {
+            Some(format!("contains {}", self.0))
+        } else {
+            None
+        }
+    }
+}
+
+/// Skips chapters containing a `.private` marker file or whose README front matter
+/// has `publish: false` or `private: true`, given the README names and the number
+/// of bytes to read of a README at most
+pub struct Private(pub Vec<String>, pub Option<u64>);
+
+impl EntryFilter for Private {
+    fn name(&self) -> &str {
+        "private"
+    }
+
+    fn skip(&self, entry: &Entry) -> Option<String> {
+        if !entry.is_dir() {
+            return None;
+        }
+        let dir = entry.direntry.path();
+        if dir.join(".private").exists() {
+            return Some("contains .private".to_string());
+        }
+
+        let files = fs::read_dir(dir).ok()?.filter_map(|f| f.ok()).collect::<Vec<_>>();
+        let readme = self.0.iter().find_map(|name| {
+            files
+                .iter()
+                .find(|f| f.file_name().to_str().map(|f| f.eq_ignore_ascii_case(name)) == Some(true))
+        })?;
+        let front_matter = FrontMatter::parse(&read_head(&readme.path(), self.1).ok()?)?;
+        if front_matter.get_bool("publish") == Some(false) {
+            Some("README has publish: false".to_string())
+        } else if front_matter.get_bool("private") == Some(true) {
+            Some("README has private: true".to_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// Skips markdown files whose front matter has `nav_exclude: true` or `summary: false`,
+/// given the number of bytes to read of a file at most
+pub struct NavExclude(pub Option<u64>);
+
+impl EntryFilter for NavExclude {
+    fn name(&self) -> &str {
+        "nav_exclude"
+    }
+
+    fn skip(&self, entry: &Entry) -> Option<String> {
+        let ext = Path::new(entry.path).extension().and_then(|e| e.to_str())?;
+        if entry.is_dir() || !ext.eq_ignore_ascii_case("md") {
+            return None;
+        }
+        let front_matter = FrontMatter::parse(&read_head(entry.direntry.path(), self.0).ok()?)?;
+        if front_matter.get_bool("nav_exclude") == Some(true) {
+            Some("front matter has nav_exclude: true".to_string())
+        } else if front_matter.get_bool("summary") == Some(false) {
+            Some("front matter has summary: false".to_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// Skips markdown files whose front matter `status` is not one of the given ones (case
+/// insensitive), given the number of bytes to read of a file at most. Files without a
+/// status are kept.
+pub struct Status(pub Vec<String>, pub Option<u64>);
+
+impl EntryFilter for Status {
+    fn name(&self) -> &str {
+        "status"
+    }
+
+    fn skip(&self, entry: &Entry) -> Option<String> {
+        let ext = Path::new(entry.path).extension().and_then(|e| e.to_str())?;
+        if entry.is_dir() || !ext.eq_ignore_ascii_case("md") {
+            return None;
+        }
+        let front_matter = FrontMatter::parse(&read_head(entry.direntry.path(), self.1).ok()?)?;
+        let status = front_matter.get_str("status")?;
+        match self.0.iter().any(|s| s.eq_ignore_ascii_case(status.trim())) {
+            true => None,
+            false => Some(format!("status {} is not one of {}", status, self.0.join(", "))),
+        }
+    }
+}
+
+/// Skips files larger than the given number of bytes
+pub struct MaxSize(pub u64);
+
+impl EntryFilter for MaxSize {
+    fn name(&self) -> &str {
+        "size"
+    }
+
+    fn skip(&self, entry: &Entry) -> Option<String> {
+        if entry.is_dir() {
+            return None;
+        }
+        let size = entry.direntry.metadata().ok()?.len();
+        if size > self.0 {
+            Some(format!("{} bytes exceed the limit of {}", size, self.0))
+        } else {
+            None
+        }
+    }
+}
+
+fn parse_ignore(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(|l| l.to_string())
+        .collect()
+}
+
+// A plain path excludes the entry and everything below it, a glob without `/`
+// matches the file name at any depth and any other glob the whole path.
+pub(crate) fn is_excluded(entry: &str, pattern: &str) -> bool {
+    let pattern = pattern.trim_end_matches('/');
+    if !pattern.contains(['*', '?']) {
+        return entry == pattern || entry.starts_with(&format!("{}/", pattern));
+    }
+    if pattern.contains('/') {
+        glob_match(pattern.trim_start_matches('/'), entry)
+    } else {
+        let name = entry.rsplit('/').next().unwrap_or(entry);
+        glob_match(pattern, name)
+    }
+}
+
+fn glob_match(pattern: &str, text: &str) -> bool {
+    // by chars, so `?` matches a multibyte char as well
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    glob_chars(&pattern, &text)
+}
+
+fn glob_chars(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            let rest = pattern[2..].strip_prefix(&['/']).unwrap_or(&pattern[2..]);
+            (0..=text.len()).any(|i| {
+                (i == 0 || text[i - 1] == '/' || rest.is_empty()) && glob_chars(rest, &text[i..])
+            })
+        }
+        Some('*') => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != '/')
+            .any(|i| glob_chars(&pattern[1..], &text[i..])),
+        Some('?') => !text.is_empty() && text[0] != '/' && glob_chars(&pattern[1..], &text[1..]),
+        Some(c) => text.first() == Some(c) && glob_chars(&pattern[1..], &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_excluded_test() {
+        assert!(is_excluded("chapter3/file1.md", "chapter3/"));
+        assert!(is_excluded("chapter3", "chapter3"));
+        assert!(!is_excluded("chapter30/file1.md", "chapter3"));
+        assert!(is_excluded("chapter1/draft.md", "draft*"));
+        assert!(is_excluded("chapter1/sub/file.md", "chapter1/**/*.md"));
+        assert!(is_excluded("chapter1/file.md", "chapter1/**/*.md"));
+        assert!(!is_excluded("chapter1/sub/file.md", "chapter1/*.md"));
+        assert!(is_excluded("chapter1/file1.md", "chapter?/file?.md"));
+        assert!(is_excluded("nötes/ünits.md", "n?tes/?nits.md"));
+        assert!(!is_excluded("nöötes", "n?tes"));
+    }
+
+    #[test]
+    fn ignore_file_test() {
+        let rules =
+            |content: &str| parse_ignore(content).iter().map(|l| IgnoreRule::new(l)).collect::<Vec<_>>();
+        let ignored = |rules: &[IgnoreRule], path, is_dir| ignored_by(rules, path, is_dir).is_some();
+
+        // anchored at the dir of the ignore file
+        let anchored = rules("/drafts\n");
+        assert!(ignored(&anchored, "drafts", true));
+        assert!(!ignored(&anchored, "guide/drafts", true));
+
+        // at any depth
+        let anywhere = rules("drafts\n");
+        assert!(ignored(&anywhere, "drafts", true));
+        assert!(ignored(&anywhere, "guide/drafts", true));
+        assert!(ignored(&anywhere, "guide/drafts", false));
+
+        // dirs only
+        let dirs = rules("drafts/\n");
+        assert!(ignored(&dirs, "guide/drafts", true));
+        assert!(!ignored(&dirs, "guide/drafts", false));
+
+        // the last matching pattern wins
+        let negated = rules("*.md\n!a.md\n");
+        assert!(ignored(&negated, "b.md", false));
+        assert!(!ignored(&negated, "a.md", false));
+        assert!(!ignored(&negated, "guide/a.md", false));
+        assert!(ignored(&rules("!a.md\n*.md\n"), "a.md", false));
+
+        // with a slash in the middle anchored as well
+        let nested = rules("guide/*.tmp.md\n");
+        assert!(ignored(&nested, "guide/setup.tmp.md", false));
+        assert!(!ignored(&nested, "other/guide/setup.tmp.md", false));
+    }
+
+    #[test]
+    fn parse_ignore_test() {
+        assert_eq!(
+            vec!["drafts/".to_string(), "*.tmp.md".to_string()],
+            parse_ignore("# comment\ndrafts/\n\n  *.tmp.md\n")
+        );
+    }
+}
@@ -0,0 +1,43 @@
+//! Notes dirs for the tests, each in a temp dir of its own
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+/// Temp dir of a test, removed when dropped, also when an assert of the test fails
+pub struct Fixture(PathBuf);
+
+impl std::ops::Deref for Fixture {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for Fixture {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A new temp dir with the `files` as `(path, content)`, their dirs created as needed
+pub fn fixture(files: &[(&str, &str)]) -> Fixture {
+    let created = CREATED.fetch_add(1, Ordering::Relaxed);
+    let name = format!("book-summary-{}-{}", std::process::id(), created);
+    let dir = Fixture(std::env::temp_dir().join(name));
+    std::fs::create_dir_all(&dir).unwrap();
+    for (path, content) in files {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    dir
+}
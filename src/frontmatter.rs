@@ -0,0 +1,321 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// A value of a front matter key.
+///
+/// Only the small subset of YAML which is used in front matter blocks is
+/// supported: plain scalars, lists (inline `[a, b]` or `- a` items) and one
+/// level of nested `key: value` pairs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Scalar(String),
+    List(Vec<String>),
+    Map(BTreeMap<String, String>),
+}
+
+/// Parsed front matter of a markdown file
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FrontMatter {
+    values: BTreeMap<String, Value>,
+}
+
+impl FrontMatter {
+    /// Parse the front matter at the start of `content`, `None` if there is none.
+    ///
+    /// YAML (`---`) and TOML (`+++`) delimited blocks are recognized.
+    pub fn parse(content: &str) -> Option<FrontMatter> {
+        let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+        let (block, _) = split(content)?;
+        if content.starts_with("+++") {
+            return Some(parse_toml(block));
+        }
+        Some(parse_yaml(block))
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.values.get(key)
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        match self.values.get(key) {
+            Some(Value::Scalar(s)) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        match self.get_str(key)?.to_lowercase().as_str() {
+            "true" | "yes" | "on" => Some(true),
+            "false" | "no" | "off" => Some(false),
+            _ => None,
+        }
+    }
+
+    /// A list value, a single scalar is treated as a list with one element
+    pub fn get_list(&self, key: &str) -> Option<Vec<String>> {
+        match self.values.get(key) {
+            Some(Value::List(l)) => Some(l.clone()),
+            Some(Value::Scalar(s)) if !s.is_empty() => Some(vec![s.clone()]),
+            _ => None,
+        }
+    }
+
+    pub fn get_map(&self, key: &str) -> Option<&BTreeMap<String, String>> {
+        match self.values.get(key) {
+            Some(Value::Map(m)) => Some(m),
+            _ => None,
+        }
+    }
+}
+
+/// Split `content` into the front matter block and the remaining body
+pub fn split(content: &str) -> Option<(&str, &str)> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let delimiter = if content.starts_with("---") {
+        "---"
+    } else if content.starts_with("+++") {
+        "+++"
+    } else {
+        return None;
+    };
+
+    let first_line_end = content.find('\n')?;
+    if content[..first_line_end].trim_end() != delimiter {
+        return None;
+    }
+
+    let rest = &content[first_line_end + 1..];
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == delimiter {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// The content without its front matter block
+pub fn strip(content: &str) -> &str {
+    match split(content) {
+        Some((_, body)) => body,
+        None => content,
+    }
+}
+
+/// `content` with the top level `key` of its front matter set to `value`, given
+/// as TOML/YAML literal (`3`, `"Title"`). A TOML front matter is added if there
+/// is none.
+pub fn set(content: &str, key: &str, value: &str) -> String {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let (block, body) = match split(content) {
+        Some(parts) => parts,
+        None => return format!("+++\n{} = {}\n+++\n{}", key, value, content),
+    };
+    let toml = content.starts_with("+++");
+    let (delimiter, line) = match toml {
+        true => ("+++", format!("{} = {}", key, value)),
+        false => ("---", format!("{}: {}", key, value)),
+    };
+
+    let mut lines = block.lines().map(|l| l.to_string()).collect::<Vec<_>>();
+    // top level keys of TOML come before the first table
+    let end = match toml {
+        true => lines.iter().position(|l| l.trim_start().starts_with('[')).unwrap_or(lines.len()),
+        false => lines.len(),
+    };
+    let existing = lines[..end].iter().position(|l| {
+        let name = l.split([':', '=']).next().unwrap_or("");
+        !l.starts_with([' ', '\t']) && name.trim() == key && l.len() > name.len()
+    });
+    match existing {
+        Some(i) => lines[i] = line,
+        None => lines.insert(end, line),
+    }
+    format!("{}\n{}\n{}\n{}", delimiter, lines.join("\n"), delimiter, body)
+}
+
+/// Content of the file at `path` for titles and front matter, only its first
+/// `max_bytes` if given. Invalid UTF-8 is replaced.
+pub(crate) fn read_head(path: &Path, max_bytes: Option<u64>) -> io::Result<String> {
+    let mut content = vec![];
+    let file = File::open(path)?;
+    match max_bytes {
+        Some(max) => file.take(max).read_to_end(&mut content)?,
+        None => (&file).read_to_end(&mut content)?,
+    };
+    let content = String::from_utf8_lossy(&content);
+    // a char cut off at the limit
+    Ok(content.trim_end_matches('\u{fffd}').to_string())
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    if value.len() >= 2
+        && ((value.starts_with('"') && value.ends_with('"'))
+            || (value.starts_with('\'') && value.ends_with('\'')))
+    {
+        value[1..value.len() - 1].to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn parse_yaml(block: &str) -> FrontMatter {
+    let mut values = BTreeMap::new();
+    let mut current: Option<(String, Value)> = None;
+
+    for line in block.lines() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        let indented = line.starts_with(' ') || line.starts_with('\t');
+        let line = line.trim();
+
+        if indented || line.starts_with("- ") || line == "-" {
+            // continuation of the previous key: list item or nested map entry
+            if let Some((_, value)) = current.as_mut() {
+                if let Some(item) = line.strip_prefix('-') {
+                    if let Value::Scalar(s) = value {
+                        if s.is_empty() {
+                            *value = Value::List(vec![]);
+                        }
+                    }
+                    if let Value::List(items) = value {
+                        items.push(unquote(item));
+                    }
+                } else if let Some((k, v)) = line.split_once(':') {
+                    if let Value::Scalar(s) = value {
+                        if s.is_empty() {
+                            *value = Value::Map(BTreeMap::new());
+                        }
+                    }
+                    if let Value::Map(map) = value {
+                        map.insert(unquote(k), unquote(v));
+                    }
+                }
+            }
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once(':') {
+            if let Some((k, v)) = current.take() {
+                values.insert(k, v);
+            }
+            let value = value.trim();
+            let value = if value.starts_with('[') && value.ends_with(']') {
+                Value::List(
+                    value[1..value.len() - 1]
+                        .split(',')
+                        .map(unquote)
+                        .filter(|s| !s.is_empty())
+                        .collect(),
+                )
+            } else {
+                Value::Scalar(unquote(value))
+            };
+            current = Some((key.trim().to_string(), value));
+        }
+    }
+
+    if let Some((k, v)) = current {
+        values.insert(k, v);
+    }
+    FrontMatter { values }
+}
+
+fn toml_to_string(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(s) => s.clone(),
+        v => v.to_string(),
+    }
+}
+
+fn parse_toml(block: &str) -> FrontMatter {
+    let mut values = BTreeMap::new();
+    if let Ok(toml::Value::Table(table)) = block.parse::<toml::Value>() {
+        for (key, value) in table {
+            let value = match value {
+                toml::Value::Array(a) => Value::List(a.iter().map(toml_to_string).collect()),
+                toml::Value::Table(t) => Value::Map(
+                    t.iter()
+                        .map(|(k, v)| (k.clone(), toml_to_string(v)))
+                        .collect(),
+                ),
+                v => Value::Scalar(toml_to_string(&v)),
+            };
+            values.insert(key, value);
+        }
+    }
+    FrontMatter { values }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_yaml_test() {
+        let content = r#"---
+title: "My Title"
+draft: yes
+tags: [rust, "cli"]
+authors:
+  - Jane
+  - John
+glossary:
+  API: Application programming interface
+---
+# Heading
+"#;
+        let fm = FrontMatter::parse(content).unwrap();
+
+        assert_eq!(Some("My Title"), fm.get_str("title"));
+        assert_eq!(Some(true), fm.get_bool("draft"));
+        assert_eq!(
+            Some(vec!["rust".to_string(), "cli".to_string()]),
+            fm.get_list("tags")
+        );
+        assert_eq!(
+            Some(vec!["Jane".to_string(), "John".to_string()]),
+            fm.get_list("authors")
+        );
+        assert_eq!(
+            Some(&"Application programming interface".to_string()),
+            fm.get_map("glossary").unwrap().get("API")
+        );
+        assert_eq!("# Heading\n", strip(content));
+    }
+
+    #[test]
+    fn parse_toml_test() {
+        let content = "+++\ntitle = \"Toml\"\nweight = 3\ntags = [\"a\"]\n+++\nbody";
+        let fm = FrontMatter::parse(content).unwrap();
+
+        assert_eq!(Some("Toml"), fm.get_str("title"));
+        assert_eq!(Some("3"), fm.get_str("weight"));
+        assert_eq!(Some(vec!["a".to_string()]), fm.get_list("tags"));
+    }
+
+    #[test]
+    fn set_test() {
+        assert_eq!("+++\nweight = 2\n+++\n# Guide\n", set("# Guide\n", "weight", "2"));
+        assert_eq!(
+            "+++\ntitle = \"Guide\"\nweight = 2\n[extra]\nweight = 9\n+++\nbody",
+            set("+++\ntitle = \"Guide\"\nweight = 1\n[extra]\nweight = 9\n+++\nbody", "weight", "2")
+        );
+        assert_eq!(
+            "---\ntitle: Guide\nmenu:\n  weight: 9\nweight: 2\n---\n",
+            set("---\ntitle: Guide\nmenu:\n  weight: 9\n---\n", "weight", "2")
+        );
+    }
+
+    #[test]
+    fn no_front_matter_test() {
+        assert_eq!(None, FrontMatter::parse("# Title\n---\n"));
+        assert_eq!(None, FrontMatter::parse("---\ntitle: unterminated\n"));
+    }
+}
@@ -0,0 +1,131 @@
+use crate::book::Chapter;
+use crate::frontmatter::{self, FrontMatter};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A term with its definition
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub term: String,
+    pub definition: String,
+}
+
+/// Terms keyed by their lowercase spelling, the first definition found wins
+pub type Glossary = BTreeMap<String, Entry>;
+
+/// Collect the terms of all markdown files of `book`, from a `glossary` map in
+/// the front matter and from definition lists in the text:
+///
+/// ```md
+/// API
+/// : Application programming interface
+/// ```
+pub fn collect(book: &Chapter, root: &Path) -> Glossary {
+    let mut glossary = Glossary::new();
+    for file in book.all_files() {
+        if !file.to_lowercase().ends_with(".md") {
+            continue;
+        }
+        let content = match std::fs::read_to_string(root.join(file)) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let mut entries = vec![];
+        if let Some(map) = FrontMatter::parse(&content).as_ref().and_then(|f| f.get_map("glossary")) {
+            entries.extend(map.iter().map(|(term, definition)| (term.clone(), definition.clone())));
+        }
+        entries.extend(definitions(frontmatter::strip(&content)));
+        for (term, definition) in entries {
+            glossary.entry(term.to_lowercase()).or_insert(Entry { term, definition });
+        }
+    }
+    glossary
+}
+
+/// Terms and definitions of the definition lists of the markdown `content`, the
+/// definitions of a term with several ones are joined
+pub fn definitions(content: &str) -> Vec<(String, String)> {
+    let mut definitions: Vec<(String, String)> = vec![];
+    let mut previous = "";
+    let mut in_code = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+        }
+        if in_code {
+            previous = "";
+            continue;
+        }
+        match line.strip_prefix(": ") {
+            Some(definition) if !previous.is_empty() => {
+                let definition = definition.trim().to_string();
+                match definitions.last_mut() {
+                    Some((term, text)) if term == previous => {
+                        text.push(' ');
+                        text.push_str(&definition);
+                    }
+                    _ => definitions.push((previous.to_string(), definition)),
+                }
+            }
+            // no term before it
+            Some(_) => {}
+            None => previous = trimmed.trim_start_matches('#').trim(),
+        }
+    }
+    definitions
+        .into_iter()
+        .filter(|(term, definition)| !term.is_empty() && !definition.is_empty())
+        .collect()
+}
+
+/// The glossary in the GitBook `GLOSSARY.md` format, a heading per term
+/// followed by its definition
+pub fn render(glossary: &Glossary, heading: &str) -> String {
+    let mut content = format!("# {}\n", heading);
+    for entry in glossary.values() {
+        content += &format!("\n## {}\n\n{}\n", entry.term, entry.definition);
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::fixture;
+
+    #[test]
+    fn definitions_test() {
+        let content = "# Terms\n\nAPI\n: Application programming interface\n: Also a contract\n\n\
+                       ```\nNot\n: a definition\n```\nCLI\n: Command line interface\n";
+        assert_eq!(
+            vec![
+                (
+                    "API".to_string(),
+                    "Application programming interface Also a contract".to_string()
+                ),
+                ("CLI".to_string(), "Command line interface".to_string()),
+            ],
+            definitions(content)
+        );
+    }
+
+    #[test]
+    fn collect_test() {
+        let dir = fixture(&[]);
+        std::fs::write(
+            dir.join("a.md"),
+            "---\nglossary:\n  SDK: Software development kit\n---\nAPI\n: Application programming interface\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("b.md"), "api\n: Another definition\n").unwrap();
+        let book = Chapter::new("Summary".to_string(), &["a.md".to_string(), "b.md".to_string()]);
+
+        let glossary = collect(&book, &dir);
+
+        assert_eq!(
+            "# Glossary\n\n## API\n\nApplication programming interface\n\n## SDK\n\nSoftware development kit\n",
+            render(&glossary, "Glossary")
+        );
+    }
+}
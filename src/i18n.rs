@@ -0,0 +1,226 @@
+use std::str::FromStr;
+
+/// Generated names in the summary, which are not taken from the notes
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Labels {
+    /// Heading of the summary, unless a title is given
+    pub summary: String,
+    /// Title of the introduction entry
+    pub introduction: String,
+    /// Title of the generated tag index chapter
+    pub tags: String,
+    /// Title of the generated author index chapter
+    pub authors: String,
+    /// Heading of the backlinks section of a file
+    pub backlinks: String,
+    /// Title of the generated glossary
+    pub glossary: String,
+    /// Added to the title of a chapter continued in the next part of a split summary
+    pub continued: String,
+    /// Title of the page listing the overflow of a chapter with too many entries
+    pub more: String,
+    /// Title of the generated A–Z index of all pages
+    pub index: String,
+}
+
+impl Default for Labels {
+    fn default() -> Self {
+        Labels {
+            summary: "Summary".to_string(),
+            introduction: "Introduction".to_string(),
+            tags: "Tags".to_string(),
+            authors: "Authors".to_string(),
+            backlinks: "Backlinks".to_string(),
+            glossary: "Glossary".to_string(),
+            continued: "continued".to_string(),
+            more: "More…".to_string(),
+            index: "Index".to_string(),
+        }
+    }
+}
+
+// (locale, summary, introduction, tags, authors, backlinks, glossary, continued, more, index)
+type Locale = (
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+);
+
+const LOCALES: &[Locale] = &[
+    ("en", "Summary", "Introduction", "Tags", "Authors", "Backlinks", "Glossary", "continued", "More…", "Index"),
+    ("de", "Inhalt", "Einführung", "Schlagwörter", "Autoren", "Rückverweise", "Glossar", "Fortsetzung", "Weitere…", "Register"),
+    ("fr", "Sommaire", "Introduction", "Étiquettes", "Auteurs", "Rétroliens", "Glossaire", "suite", "Plus…", "Index"),
+    ("es", "Índice", "Introducción", "Etiquetas", "Autores", "Enlaces entrantes", "Glosario", "continuación", "Más…", "Índice alfabético"),
+    ("it", "Indice", "Introduzione", "Tag", "Autori", "Collegamenti in entrata", "Glossario", "continua", "Altro…", "Indice analitico"),
+    ("nl", "Inhoud", "Inleiding", "Tags", "Auteurs", "Terugverwijzingen", "Woordenlijst", "vervolg", "Meer…", "Register"),
+    ("pt", "Sumário", "Introdução", "Etiquetas", "Autores", "Links de entrada", "Glossário", "continuação", "Mais…", "Índice remissivo"),
+    ("sv", "Innehåll", "Introduktion", "Taggar", "Författare", "Bakåtlänkar", "Ordlista", "fortsättning", "Mer…", "Register"),
+];
+
+// (code, name of the language in itself) of the language folders of multilingual books
+const LANGUAGES: &[(&str, &str)] = &[
+    ("ar", "العربية"),
+    ("de", "Deutsch"),
+    ("en", "English"),
+    ("es", "Español"),
+    ("fr", "Français"),
+    ("it", "Italiano"),
+    ("ja", "日本語"),
+    ("ko", "한국어"),
+    ("nl", "Nederlands"),
+    ("pl", "Polski"),
+    ("pt", "Português"),
+    ("ru", "Русский"),
+    ("sv", "Svenska"),
+    ("tr", "Türkçe"),
+    ("uk", "Українська"),
+    ("zh", "中文"),
+];
+
+/// Name of the language of the folder `name` in itself, if it is a language code like
+/// `de`, `pt-br` (`Português (BR)`) or `zh_Hans`
+pub fn language_name(name: &str) -> Option<String> {
+    let (code, region) = match name.split_once(['-', '_']) {
+        Some((code, region)) => (code, Some(region)),
+        None => (name, None),
+    };
+    let language = LANGUAGES.iter().find(|(c, _)| *c == code)?.1;
+    match region {
+        None => Some(language.to_string()),
+        Some(region) if !(2..=4).contains(&region.len()) => None,
+        Some(region) if !region.chars().all(|c| c.is_ascii_alphanumeric()) => None,
+        Some(region) => Some(format!("{} ({})", language, region.to_uppercase())),
+    }
+}
+
+impl FromStr for Labels {
+    type Err = String;
+
+    /// Built-in labels of a locale, `de` and `de_DE.UTF-8` both pick german
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lang = s
+            .split(['_', '-', '.'])
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+        LOCALES
+            .iter()
+            .find(|(locale, ..)| *locale == lang)
+            .map(|(_, summary, introduction, tags, authors, backlinks, glossary, continued, more, index)| {
+                Labels {
+                    summary: summary.to_string(),
+                    introduction: introduction.to_string(),
+                    tags: tags.to_string(),
+                    authors: authors.to_string(),
+                    backlinks: backlinks.to_string(),
+                    glossary: glossary.to_string(),
+                    continued: continued.to_string(),
+                    more: more.to_string(),
+                    index: index.to_string(),
+                }
+            })
+            .ok_or_else(|| {
+                let locales = LOCALES.iter().map(|l| l.0).collect::<Vec<_>>();
+                format!("Invalid locale {} (one of {})", s, locales.join(", "))
+            })
+    }
+}
+
+impl Labels {
+    /// Override labels by an `[i18n]` table, e.g. from the `book.toml`:
+    ///
+    /// ```toml
+    /// [i18n]
+    /// locale = "de"
+    /// summary = "Zusammenfassung"
+    /// ```
+    ///
+    /// `locale` replaces all labels, the other keys a single one.
+    pub fn merge_toml(&mut self, table: &toml::Value) -> Result<(), String> {
+        if let Some(locale) = table.get("locale").and_then(|l| l.as_str()) {
+            *self = locale.parse()?;
+        }
+        if let Some(summary) = table.get("summary").and_then(|s| s.as_str()) {
+            self.summary = summary.to_string();
+        }
+        if let Some(introduction) = table.get("introduction").and_then(|s| s.as_str()) {
+            self.introduction = introduction.to_string();
+        }
+        if let Some(tags) = table.get("tags").and_then(|s| s.as_str()) {
+            self.tags = tags.to_string();
+        }
+        if let Some(authors) = table.get("authors").and_then(|s| s.as_str()) {
+            self.authors = authors.to_string();
+        }
+        if let Some(backlinks) = table.get("backlinks").and_then(|s| s.as_str()) {
+            self.backlinks = backlinks.to_string();
+        }
+        if let Some(glossary) = table.get("glossary").and_then(|s| s.as_str()) {
+            self.glossary = glossary.to_string();
+        }
+        if let Some(continued) = table.get("continued").and_then(|s| s.as_str()) {
+            self.continued = continued.to_string();
+        }
+        if let Some(more) = table.get("more").and_then(|s| s.as_str()) {
+            self.more = more.to_string();
+        }
+        if let Some(index) = table.get("index").and_then(|s| s.as_str()) {
+            self.index = index.to_string();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locale_test() {
+        assert_eq!("Inhalt", "de_DE.UTF-8".parse::<Labels>().unwrap().summary);
+        assert_eq!("Sommaire", "fr".parse::<Labels>().unwrap().summary);
+        assert!("xx".parse::<Labels>().is_err());
+    }
+
+    #[test]
+    fn language_name_test() {
+        assert_eq!(Some("Deutsch".to_string()), language_name("de"));
+        assert_eq!(Some("Português (BR)".to_string()), language_name("pt-br"));
+        assert_eq!(Some("中文 (HANS)".to_string()), language_name("zh_Hans"));
+        assert_eq!(None, language_name("api"));
+        assert_eq!(None, language_name("En"));
+        assert_eq!(None, language_name("de-guide"));
+    }
+
+    #[test]
+    fn merge_toml_test() {
+        let table = "locale = \"de\"\nsummary = \"Zusammenfassung\""
+            .parse::<toml::Value>()
+            .unwrap();
+        let mut labels = Labels::default();
+        labels.merge_toml(&table).unwrap();
+
+        assert_eq!(
+            Labels {
+                summary: "Zusammenfassung".to_string(),
+                introduction: "Einführung".to_string(),
+                tags: "Schlagwörter".to_string(),
+                authors: "Autoren".to_string(),
+                backlinks: "Rückverweise".to_string(),
+                glossary: "Glossar".to_string(),
+                continued: "Fortsetzung".to_string(),
+                more: "Weitere…".to_string(),
+                index: "Register".to_string(),
+            },
+            labels
+        );
+    }
+}
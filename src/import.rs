@@ -0,0 +1,65 @@
+use crate::{Result, SummaryError};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use walkdir::WalkDir;
+
+/// Extensions of the documents pandoc converts, with its reader for them
+const READERS: &[(&str, &str)] = &[("docx", "docx"), ("html", "html"), ("htm", "html")];
+
+/// The pandoc reader of the document at `path`, `None` if it isn't converted
+pub fn reader(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    READERS.iter().find(|(e, _)| *e == extension).map(|(_, reader)| *reader)
+}
+
+/// The document at `path` as markdown, converted by pandoc
+pub fn to_markdown(path: &Path) -> Result<String> {
+    let reader = reader(path)
+        .ok_or_else(|| SummaryError::Io(path.to_path_buf(), "not a .docx or .html document".to_string()))?;
+    let output = Command::new("pandoc")
+        .args(["--from", reader, "--to", "gfm", "--wrap", "none"])
+        .arg(path)
+        .output()
+        .map_err(|e| SummaryError::Io(path.to_path_buf(), format!("couldn't run pandoc: {}", e)))?;
+    if !output.status.success() {
+        let why = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(Box::new(SummaryError::Io(path.to_path_buf(), why)));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The markdown of each `.docx` and `.html` document of `dir` (hidden dirs left out)
+/// as `(path, content)`, next to the document with the `.md` extension. Documents
+/// with a markdown file of that name are already converted.
+pub fn convert_dir(dir: &Path) -> Result<Vec<(String, String)>> {
+    let documents = WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && reader(e.path()).is_some())
+        .map(|e| e.into_path())
+        .collect::<Vec<PathBuf>>();
+    let mut files = vec![];
+    for document in documents {
+        let markdown = document.with_extension("md");
+        if markdown.exists() {
+            continue;
+        }
+        let file = markdown.strip_prefix(dir).unwrap().to_string_lossy().replace('\\', "/");
+        files.push((file, to_markdown(&document)?));
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reader_test() {
+        assert_eq!(Some("docx"), reader(Path::new("export/Handbook.DOCX")));
+        assert_eq!(Some("html"), reader(Path::new("wiki/page.htm")));
+        assert_eq!(None, reader(Path::new("notes/setup.md")));
+    }
+}
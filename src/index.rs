@@ -0,0 +1,90 @@
+use crate::book::{file_title, make_title_case, Chapter};
+use crate::collate::Collation;
+use crate::render::target;
+use crate::title::Titles;
+
+/// File name of the generated A–Z index, relative to the notes dir
+pub const INDEX_FILE: &str = "INDEX.md";
+
+/// Every page of `book` as `(title, file)` sorted by title. READMEs have the title of
+/// their chapter, as in the summary.
+pub fn collect(book: &Chapter, titles: &Titles, readme_names: &[String], collation: &Collation) -> Vec<(String, String)> {
+    let mut entries = vec![];
+    collect_chapter(book, "", titles, readme_names, &mut entries);
+    entries.sort_by(|(a, file_a), (b, file_b)| {
+        collation.compare(&a.to_lowercase(), &b.to_lowercase()).then_with(|| file_a.cmp(file_b))
+    });
+    entries
+}
+
+fn collect_chapter(
+    chapter: &Chapter,
+    path: &str,
+    titles: &Titles,
+    readme_names: &[String],
+    entries: &mut Vec<(String, String)>,
+) {
+    let readme = match path {
+        "" => None,
+        _ => chapter.readme(readme_names),
+    };
+    for file in &chapter.files {
+        let title = match (titles.get(path), Some(file) == readme) {
+            (Some(title), true) => title.clone(),
+            (None, true) => make_title_case(&chapter.name),
+            _ => file_title(titles, file),
+        };
+        entries.push((title, file.clone()));
+    }
+    for c in &chapter.chapter {
+        let sub = match path {
+            "" => c.name.clone(),
+            _ => format!("{}/{}", path, c.name),
+        };
+        collect_chapter(c, &sub, titles, readme_names, entries);
+    }
+}
+
+/// The index titled `heading` with a section per initial letter, titles not starting
+/// with a letter come first under `#`
+pub fn page(entries: &[(String, String)], heading: &str) -> String {
+    let mut content = format!("# {}\n", heading);
+    let mut section = None;
+    for (title, file) in entries {
+        let letter = match title.chars().next() {
+            Some(c) if c.is_alphabetic() => c.to_uppercase().collect(),
+            _ => "#".to_string(),
+        };
+        if section.as_ref() != Some(&letter) {
+            content += &format!("\n## {}\n\n", letter);
+            section = Some(letter);
+        }
+        content += &format!("- [{}]({})\n", title, target(file));
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_test() {
+        let entries = ["zebra.md", "guide/README.md", "guide/about.md", "2fa.md"];
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &entries.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+        );
+        let mut titles = Titles::new();
+        titles.insert("guide/about.md".to_string(), "apis".to_string());
+        titles.insert("2fa.md".to_string(), "2FA login".to_string());
+
+        let entries = collect(&book, &titles, &["README.md".to_string()], &Collation::Codepoint);
+
+        assert_eq!(
+            "# Index\n\n## #\n\n- [2FA login](2fa.md)\n\n## A\n\n- [apis](guide/about.md)\n\n\
+             ## G\n\n- [Guide](guide/README.md)\n\n## Z\n\n- [Zebra](zebra.md)\n",
+            page(&entries, "Index")
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! Create automatically a summary file for gitbook/mdbook.
+//!
+//! The binary is a thin wrapper around this library, so the chapter tree
+//! and its formats can also be used from other tools.
+
+use std::fmt;
+use std::path::PathBuf;
+
+pub mod authors;
+pub mod backlinks;
+pub mod book;
+pub mod changelog;
+pub mod collate;
+pub mod date;
+pub mod filter;
+#[cfg(any(test, feature = "fixture"))]
+#[doc(hidden)]
+pub mod fixture;
+pub mod frontmatter;
+pub mod glossary;
+pub mod i18n;
+#[cfg(feature = "import")]
+pub mod import;
+pub mod index;
+pub mod lint;
+pub mod links;
+pub mod meta;
+pub mod migrate;
+mod normalize;
+pub mod overflow;
+pub mod owners;
+mod progress;
+pub mod redirects;
+pub mod render;
+pub mod report;
+pub mod search;
+pub mod slug;
+pub mod split;
+mod suggest;
+mod summary;
+pub mod tags;
+pub mod title;
+pub mod warning;
+
+pub use book::{Chapter, Item};
+pub use collate::Collation;
+pub use filter::EntryFilter;
+pub use i18n::Labels;
+pub use render::{Format, RenderContext, Renderer};
+pub use slug::Slugger;
+pub use summary::{Resolved, SummaryBuilder};
+pub use title::{TitleProvider, TitleSource};
+pub use warning::Warning;
+
+#[derive(Debug, PartialEq)]
+pub enum SummaryError {
+    /// The notes dir does not exist or is not a directory
+    DirNotFound(PathBuf),
+    /// A file could not be read or written
+    Io(PathBuf, String),
+    /// A config or mapping file is invalid
+    Config(PathBuf, String),
+    /// Warnings in strict mode
+    Strict(Vec<Warning>),
+    /// No renderer is registered under the name
+    UnknownFormat {
+        name: String,
+        /// The closest valid format, if any is close
+        suggestion: Option<String>,
+        valid: Vec<String>,
+    },
+}
+
+impl fmt::Display for SummaryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SummaryError::DirNotFound(path) => write!(f, "Path {} not found!", path.display()),
+            SummaryError::Io(path, why) => write!(f, "Couldn't access {}: {}", path.display(), why),
+            SummaryError::Config(path, why) => write!(f, "Couldn't parse {}: {}", path.display(), why),
+            SummaryError::Strict(warnings) => {
+                write!(f, "{} warning(s) in strict mode", warnings.len())?;
+                for warning in warnings {
+                    write!(f, "\n  {}", warning)?;
+                }
+                Ok(())
+            }
+            SummaryError::UnknownFormat { name, suggestion, valid } => {
+                write!(f, "Invalid format {}", name)?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, ", did you mean '{}'?", suggestion)?;
+                }
+                write!(f, " (one of {})", valid.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for SummaryError {}
+
+pub type Result<T> = std::result::Result<T, Box<SummaryError>>;
@@ -0,0 +1,255 @@
+/// Targets of the inline links and images (`[text](target "title")`) of the
+/// markdown `content`, leaving out code blocks and code spans
+pub fn targets(content: &str) -> Vec<String> {
+    let mut targets = vec![];
+    for line in prose_lines(content) {
+        let line = strip_code_spans(line);
+        let mut rest = line.as_str();
+        while let Some(start) = rest.find("](") {
+            rest = &rest[start + 2..];
+            if let Some((target, len)) = link_target(rest) {
+                targets.push(target);
+                rest = &rest[len..];
+            }
+        }
+    }
+    targets
+}
+
+/// Names of the wiki links (`[[Note]]`, `[[Note|shown text]]`, `[[Note#Heading]]`)
+/// of the markdown `content`
+pub fn wikilinks(content: &str) -> Vec<String> {
+    let mut names = vec![];
+    for line in prose_lines(content) {
+        let line = strip_code_spans(line);
+        let mut rest = line.as_str();
+        while let Some(start) = rest.find("[[") {
+            rest = &rest[start + 2..];
+            let end = match rest.find("]]") {
+                Some(end) => end,
+                None => break,
+            };
+            let name = rest[..end].split(['|', '#']).next().unwrap_or("").trim();
+            if !name.is_empty() {
+                names.push(name.to_string());
+            }
+            rest = &rest[end + 2..];
+        }
+    }
+    names
+}
+
+/// `content` with the targets of its inline links replaced by `rewrite`, if it returns
+/// a new one. Code blocks and code spans are kept as they are.
+pub fn rewrite_targets(content: &str, rewrite: impl Fn(&str) -> Option<String>) -> String {
+    let mut fence: Option<&str> = None;
+    let mut rewritten = String::with_capacity(content.len());
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        match fence {
+            Some(f) if trimmed.starts_with(f) => fence = None,
+            Some(_) => {}
+            None if trimmed.starts_with("```") || trimmed.starts_with("~~~") => {
+                fence = Some(&trimmed[..3])
+            }
+            None => {
+                rewritten += &rewrite_line(line, &rewrite);
+                continue;
+            }
+        }
+        rewritten += line;
+    }
+    rewritten
+}
+
+// `line` with its link targets outside of code spans rewritten
+fn rewrite_line(line: &str, rewrite: &impl Fn(&str) -> Option<String>) -> String {
+    let mut rewritten = String::new();
+    for (i, part) in line.split('`').enumerate() {
+        if i > 0 {
+            rewritten.push('`');
+        }
+        if i % 2 == 1 {
+            rewritten += part;
+            continue;
+        }
+        let mut rest = part;
+        while let Some(start) = rest.find("](") {
+            rewritten += &rest[..start + 2];
+            rest = &rest[start + 2..];
+            let (target, len) = match link_target(rest) {
+                Some(link) => link,
+                None => continue,
+            };
+            // targets in `<...>` or after spaces are kept
+            match rewrite(&target).filter(|_| rest.starts_with(target.as_str())) {
+                Some(new) => {
+                    rewritten += &new;
+                    rest = &rest[target.len()..];
+                }
+                None => {
+                    rewritten += &rest[..len - 1];
+                    rest = &rest[len - 1..];
+                }
+            }
+        }
+        rewritten += rest;
+    }
+    rewritten
+}
+
+// Lines outside of fenced code blocks
+fn prose_lines(content: &str) -> impl Iterator<Item = &str> {
+    let mut fence: Option<&str> = None;
+    content.lines().filter(move |line| {
+        let trimmed = line.trim_start();
+        match fence {
+            Some(f) if trimmed.starts_with(f) => {
+                fence = None;
+                false
+            }
+            Some(_) => false,
+            None if trimmed.starts_with("```") || trimmed.starts_with("~~~") => {
+                fence = Some(&trimmed[..3]);
+                false
+            }
+            None => true,
+        }
+    })
+}
+
+fn strip_code_spans(line: &str) -> String {
+    line.split('`').step_by(2).collect::<Vec<_>>().join(" ")
+}
+
+// Target at the start of `rest` (after `](`) and the length of the link up to its `)`
+fn link_target(rest: &str) -> Option<(String, usize)> {
+    if let Some(inner) = rest.strip_prefix('<') {
+        let end = inner.find('>')?;
+        let close = inner[end..].find(')')?;
+        return Some((inner[..end].to_string(), end + close + 2));
+    }
+    let mut depth = 0;
+    for (i, c) in rest.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth > 0 => depth -= 1,
+            ')' => {
+                let target = rest[..i].split_whitespace().next().unwrap_or("");
+                return Some((target.to_string(), i + 1));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Whether `target` leaves the book (`https://...`, `mailto:...`, `/abs`) or
+/// only points into the same page (`#anchor`)
+pub fn is_external(target: &str) -> bool {
+    let scheme = target.split_once(':').map(|(scheme, _)| scheme);
+    let has_scheme = scheme.is_some_and(|s| {
+        !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+    });
+    has_scheme || target.starts_with('/') || target.starts_with('#') || target.is_empty()
+}
+
+/// Path (relative to the notes dir) of the relative link `target` in `file`,
+/// without anchor or query. `None` for external links and links leaving the notes.
+pub fn resolve(file: &str, target: &str) -> Option<String> {
+    if is_external(target) {
+        return None;
+    }
+    let target = target.split(['#', '?']).next().unwrap_or("");
+    let mut parts = file.split('/').collect::<Vec<_>>();
+    parts.pop();
+    let decoded = percent_decode(target);
+    for part in decoded.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            _ => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+/// Relative link from `from` to `to`, both relative to the notes dir
+pub fn relative(from: &str, to: &str) -> String {
+    let from = from.split('/').collect::<Vec<_>>();
+    let to = to.split('/').collect::<Vec<_>>();
+    let dirs = &from[..from.len() - 1];
+    let common = dirs.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut parts = vec![".."; dirs.len() - common];
+    parts.extend(&to[common..]);
+    parts.join("/")
+}
+
+// `%20` and the other escaped bytes of `s`, invalid escapes are kept
+pub(crate) fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = s.get(i + 1..i + 3).and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn targets_test() {
+        let content = "See [a](a.md) and ![img](img/x.png \"Title\"), [b](<my note.md>).\n\
+                       `[not](code.md)` [c](https://example.com/(x))\n\
+                       ```\n[no](fenced.md)\n```\n[[Wiki Note|shown]] [[Other#Part]]\n";
+        assert_eq!(
+            vec!["a.md", "img/x.png", "my note.md", "https://example.com/(x)"],
+            targets(content)
+        );
+        assert_eq!(vec!["Wiki Note", "Other"], wikilinks(content));
+    }
+
+    #[test]
+    fn rewrite_targets_test() {
+        let content = "[a](a.md) `[b](a.md)` ![c](a.md \"Title\")\n```\n[d](a.md)\n```\n[e](b.md)\n";
+        let rewrite = |target: &str| (target == "a.md").then(|| "x.md".to_string());
+        assert_eq!(
+            "[a](x.md) `[b](a.md)` ![c](x.md \"Title\")\n```\n[d](a.md)\n```\n[e](b.md)\n",
+            rewrite_targets(content, rewrite)
+        );
+    }
+
+    #[test]
+    fn resolve_test() {
+        assert_eq!(Some("guide/b.md".to_string()), resolve("guide/a.md", "b.md#part"));
+        assert_eq!(Some("c.md".to_string()), resolve("guide/a.md", "../c.md"));
+        assert_eq!(Some("my note.md".to_string()), resolve("a.md", "./my%20note.md"));
+        assert_eq!(None, resolve("a.md", "../outside.md"));
+        assert_eq!(None, resolve("a.md", "https://example.com"));
+        assert_eq!(None, resolve("a.md", "mailto:me@example.com"));
+        assert_eq!(None, resolve("a.md", "#anchor"));
+    }
+
+    #[test]
+    fn relative_test() {
+        assert_eq!("b.md", relative("a.md", "b.md"));
+        assert_eq!("../b.md", relative("guide/a.md", "b.md"));
+        assert_eq!("setup/b.md", relative("guide/a.md", "guide/setup/b.md"));
+        assert_eq!("../../other/b.md", relative("guide/x/a.md", "other/b.md"));
+    }
+}
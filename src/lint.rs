@@ -0,0 +1,423 @@
+use crate::frontmatter::{self, FrontMatter};
+use crate::links;
+use crate::warning::Warning;
+use crate::{Result, SummaryError};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Age in seconds after which a cached result of a link is checked again
+const LINK_CACHE_SECS: u64 = 24 * 60 * 60;
+
+/// A note to check, with its resolved title
+pub struct Note<'a> {
+    /// Path relative to the notes dir, `/` separated
+    pub path: &'a str,
+    pub title: &'a str,
+    pub content: &'a str,
+}
+
+/// Checks the content of each markdown note while generating the summary, its
+/// findings are warnings (errors in strict mode).
+pub trait Check: Send + Sync {
+    /// Name of the check
+    fn name(&self) -> &str;
+
+    fn check(&self, note: &Note) -> Vec<Warning>;
+
+    /// Findings across all notes, e.g. notes duplicating each other
+    fn check_all(&self, _notes: &[Note]) -> Vec<Warning> {
+        vec![]
+    }
+}
+
+/// Requires the front matter keys, e.g. `title` and `owner`
+pub struct RequiredKeys(pub Vec<String>);
+
+impl Check for RequiredKeys {
+    fn name(&self) -> &str {
+        "required-keys"
+    }
+
+    fn check(&self, note: &Note) -> Vec<Warning> {
+        let front_matter = FrontMatter::parse(note.content).unwrap_or_default();
+        self.0
+            .iter()
+            .filter(|key| front_matter.get(key).is_none())
+            .map(|key| lint(note.path, 1, format!("front matter key `{}` is missing", key)))
+            .collect()
+    }
+}
+
+/// Forbids markers like `TODO` or `FIXME` outside of code blocks
+pub struct Forbidden(pub Vec<String>);
+
+impl Check for Forbidden {
+    fn name(&self) -> &str {
+        "forbidden"
+    }
+
+    fn check(&self, note: &Note) -> Vec<Warning> {
+        let mut warnings = vec![];
+        let mut fenced = false;
+        for (i, line) in note.content.lines().enumerate() {
+            if line.trim_start().starts_with("```") {
+                fenced = !fenced;
+                continue;
+            }
+            if fenced {
+                continue;
+            }
+            for marker in self.0.iter().filter(|m| line.contains(m.as_str())) {
+                warnings.push(lint(note.path, i + 1, format!("forbidden `{}`", marker)));
+            }
+        }
+        warnings
+    }
+}
+
+/// Spellchecks the titles against a dictionary of words, one per line. Words with
+/// digits and acronyms are not checked.
+pub struct Spelling(pub HashSet<String>);
+
+impl Spelling {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| SummaryError::Io(path.to_path_buf(), e.to_string()))?;
+        Ok(Spelling(content.lines().map(|w| w.trim().to_lowercase()).filter(|w| !w.is_empty()).collect()))
+    }
+}
+
+impl Check for Spelling {
+    fn name(&self) -> &str {
+        "spelling"
+    }
+
+    fn check(&self, note: &Note) -> Vec<Warning> {
+        let body = frontmatter::strip(note.content);
+        let offset = note.content[..note.content.len() - body.len()].lines().count();
+        // reported at the heading with the title, if there is one
+        let line = body
+            .lines()
+            .position(|l| l.trim_start_matches('#').trim() == note.title && l.starts_with('#'))
+            .map_or(0, |i| offset + i + 1);
+        note.title
+            .split(|c: char| !c.is_alphanumeric() && c != '\'')
+            .map(|w| w.trim_matches('\''))
+            .filter(|w| w.chars().count() > 1 && w.chars().all(char::is_alphabetic))
+            .filter(|w| !w.chars().all(char::is_uppercase))
+            .filter(|w| !self.0.contains(&w.to_lowercase()))
+            .map(|w| lint(note.path, line, format!("`{}` of the title is not in the dictionary", w)))
+            .collect()
+    }
+}
+
+/// Finds stubs, notes smaller than the size in bytes or with nothing but headings
+pub struct Stubs(pub u64);
+
+impl Check for Stubs {
+    fn name(&self) -> &str {
+        "stubs"
+    }
+
+    fn check(&self, note: &Note) -> Vec<Warning> {
+        if !is_stub(note.content, self.0) {
+            return vec![];
+        }
+        let message = match headings_only(note.content) {
+            true => "stub with nothing but headings".to_string(),
+            false => format!("stub of {} bytes", note.content.len()),
+        };
+        vec![lint(note.path, 0, message)]
+    }
+}
+
+/// Whether the note with `content` is a stub: smaller than `min_size` bytes, or with
+/// nothing but headings besides its front matter
+pub fn is_stub(content: &str, min_size: u64) -> bool {
+    (content.len() as u64) < min_size || headings_only(content)
+}
+
+fn headings_only(content: &str) -> bool {
+    frontmatter::strip(content).lines().all(|l| l.trim().is_empty() || l.trim_start().starts_with('#'))
+}
+
+/// Finds notes of different chapters with the same content, or nearly (at least 90% of
+/// their distinct lines shared), e.g. copied templates. Front matter, blank lines, case
+/// and whitespace don't count.
+pub struct Duplicates;
+
+impl Check for Duplicates {
+    fn name(&self) -> &str {
+        "duplicates"
+    }
+
+    fn check(&self, _note: &Note) -> Vec<Warning> {
+        vec![]
+    }
+
+    fn check_all(&self, notes: &[Note]) -> Vec<Warning> {
+        let lines = notes.iter().map(|n| normalized_lines(n.content)).collect::<Vec<_>>();
+        let sets = lines.iter().map(|l| l.iter().map(String::as_str).collect()).collect::<Vec<HashSet<&str>>>();
+        let chapter = |path: &str| path.rsplit_once('/').map_or("", |(dir, _)| dir).to_string();
+        let mut identical: HashMap<&[String], usize> = HashMap::new();
+        let mut warnings = vec![];
+        for (i, note) in notes.iter().enumerate().filter(|(i, _)| !lines[*i].is_empty()) {
+            // the first note of another chapter with the same lines, or else most of them
+            let same = identical.get(lines[i].as_slice()).copied();
+            let same = same.filter(|j| chapter(notes[*j].path) != chapter(note.path));
+            let near = || {
+                (0..i)
+                    .filter(|j| !lines[*j].is_empty() && chapter(notes[*j].path) != chapter(note.path))
+                    .map(|j| (j, similarity(&sets[i], &sets[j])))
+                    .find(|(_, similarity)| *similarity >= 0.9)
+            };
+            match same.map(|j| (j, 1.0)).or_else(near) {
+                Some((j, similarity)) if similarity >= 1.0 => {
+                    warnings.push(lint(note.path, 0, format!("same content as {}", notes[j].path)))
+                }
+                Some((j, similarity)) => warnings.push(lint(
+                    note.path,
+                    0,
+                    format!("{:.0}% of the content is the same as {}", similarity * 100.0, notes[j].path),
+                )),
+                None => {}
+            }
+            identical.entry(lines[i].as_slice()).or_insert(i);
+        }
+        warnings
+    }
+}
+
+/// Checks the `http` and `https` links of the notes with a HEAD request by `curl`,
+/// waiting `interval` between the requests. Links without response, `404`, `410` or
+/// a server error are dead. The results are cached in the `cache` file for a day, so
+/// a rerun only asks for the links it hasn't checked lately.
+pub struct ExternalLinks {
+    pub cache: Option<PathBuf>,
+    pub interval: Duration,
+}
+
+impl Check for ExternalLinks {
+    fn name(&self) -> &str {
+        "external-links"
+    }
+
+    fn check(&self, _note: &Note) -> Vec<Warning> {
+        vec![]
+    }
+
+    fn check_all(&self, notes: &[Note]) -> Vec<Warning> {
+        // each link once, reported at the first line of each note linking it
+        let mut found: BTreeMap<String, Vec<(&str, usize)>> = BTreeMap::new();
+        for note in notes {
+            for url in links::targets(note.content).into_iter().filter(|t| is_web_link(t)) {
+                let line = note.content.lines().position(|l| l.contains(url.as_str())).map_or(0, |i| i + 1);
+                let linked = found.entry(url).or_default();
+                if !linked.contains(&(note.path, line)) {
+                    linked.push((note.path, line));
+                }
+            }
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let mut cache = self.cache.as_deref().map(read_link_cache).unwrap_or_default();
+        let mut warnings = vec![];
+        let mut requested = false;
+        for (url, linked) in &found {
+            let status = match cache.get(url) {
+                Some((checked, status)) if now.saturating_sub(*checked) < LINK_CACHE_SECS => *status,
+                _ => {
+                    if requested {
+                        std::thread::sleep(self.interval);
+                    }
+                    requested = true;
+                    match head_status(url) {
+                        Ok(status) => {
+                            cache.insert(url.clone(), (now, status));
+                            status
+                        }
+                        Err(why) => {
+                            let (path, line) = linked[0];
+                            warnings.push(lint(path, line, format!("couldn't check {}: {}", url, why)));
+                            break;
+                        }
+                    }
+                }
+            };
+            if matches!(status, 0 | 404 | 410 | 500..=599) {
+                let message = match status {
+                    0 => format!("dead link {}, no response", url),
+                    _ => format!("dead link {}, status {}", url, status),
+                };
+                warnings.extend(linked.iter().map(|(path, line)| lint(path, *line, message.clone())));
+            }
+        }
+        if let Some(file) = &self.cache {
+            // the cache only saves requests, a run doesn't fail without it
+            let _ = write_link_cache(file, &cache);
+        }
+        warnings
+    }
+}
+
+fn is_web_link(target: &str) -> bool {
+    let scheme = target.split_once("://").map(|(scheme, _)| scheme.to_lowercase());
+    matches!(scheme.as_deref(), Some("http" | "https"))
+}
+
+// Status of the response to a HEAD request of `url` following redirects, 0 without
+// a response. An error if curl can't be run.
+fn head_status(url: &str) -> std::result::Result<u16, String> {
+    let output = Command::new("curl")
+        .args(["--head", "--silent", "--location", "--max-time", "10", "--write-out", "\n%{http_code}"])
+        .arg(url)
+        .output()
+        .map_err(|why| format!("couldn't run curl: {}", why))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().last().and_then(|code| code.trim().parse().ok()).unwrap_or(0))
+}
+
+// Checked links of the cache `file` as `url` => `(checked, status)`, a line
+// `<checked> <status> <url>` each with the time of the check in seconds since the epoch
+fn read_link_cache(file: &Path) -> HashMap<String, (u64, u16)> {
+    let content = fs::read_to_string(file).unwrap_or_default();
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, ' ');
+            let checked = fields.next()?.parse().ok()?;
+            let status = fields.next()?.parse().ok()?;
+            Some((fields.next()?.to_string(), (checked, status)))
+        })
+        .collect()
+}
+
+fn write_link_cache(file: &Path, cache: &HashMap<String, (u64, u16)>) -> std::io::Result<()> {
+    let mut lines = cache
+        .iter()
+        .map(|(url, (checked, status))| format!("{} {} {}\n", checked, status, url))
+        .collect::<Vec<_>>();
+    lines.sort();
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(file, lines.concat())
+}
+
+// The lines of the body of `content` without blank lines, lowercased with single spaces
+fn normalized_lines(content: &str) -> Vec<String> {
+    frontmatter::strip(content)
+        .lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase())
+        .filter(|l| !l.is_empty())
+        .collect()
+}
+
+// Share of the distinct lines of both notes they have in common
+fn similarity(a: &HashSet<&str>, b: &HashSet<&str>) -> f64 {
+    let (min, max) = (a.len().min(b.len()), a.len().max(b.len()));
+    // too different in length to share enough lines
+    if max == 0 || (min as f64) < max as f64 * 0.9 {
+        return 0.0;
+    }
+    let shared = a.intersection(b).count();
+    shared as f64 / (a.len() + b.len() - shared) as f64
+}
+
+fn lint(path: &str, line: usize, message: String) -> Warning {
+    Warning::Lint(path.to_string(), line, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::fixture;
+
+    #[test]
+    fn checks_test() {
+        let content = "---\ntitle: Setup\n---\n# Setpu the CLI\n\nTODO: more\n```\n// TODO\n```\n";
+        let note = Note {
+            path: "guide/setup.md",
+            title: "Setpu the CLI",
+            content,
+        };
+
+        assert_eq!(
+            vec![Warning::Lint("guide/setup.md".to_string(), 1, "front matter key `owner` is missing".to_string())],
+            RequiredKeys(vec!["title".to_string(), "owner".to_string()]).check(&note)
+        );
+        assert_eq!(
+            vec![Warning::Lint("guide/setup.md".to_string(), 6, "forbidden `TODO`".to_string())],
+            Forbidden(vec!["TODO".to_string()]).check(&note)
+        );
+        let dictionary = ["setup", "the"].iter().map(|w| w.to_string()).collect();
+        assert_eq!(
+            vec![Warning::Lint(
+                "guide/setup.md".to_string(),
+                4,
+                "`Setpu` of the title is not in the dictionary".to_string()
+            )],
+            Spelling(dictionary).check(&note)
+        );
+    }
+
+    #[test]
+    fn stubs_test() {
+        let note = |content| Note { path: "ideas.md", title: "Ideas", content };
+
+        assert_eq!(
+            vec![Warning::Lint("ideas.md".to_string(), 0, "stub with nothing but headings".to_string())],
+            Stubs(0).check(&note("---\ntitle: Ideas\n---\n# Ideas\n\n## Later\n"))
+        );
+        assert_eq!(
+            vec![Warning::Lint("ideas.md".to_string(), 0, "stub of 13 bytes".to_string())],
+            Stubs(100).check(&note("# Ideas\n\nTBD\n"))
+        );
+        assert!(Stubs(10).check(&note("# Ideas\n\nTBD\n")).is_empty());
+    }
+
+    #[test]
+    fn duplicates_test() {
+        let template = "---\ntitle: Retro\n---\n# Retro\n\n## Went well\n\n## To improve\n";
+        let lines = (1..=10).map(|i| format!("Step {}\n", i)).collect::<String>();
+        let notes = [
+            Note { path: "team-a/retro.md", title: "Retro", content: template },
+            Note { path: "team-a/retro-2.md", title: "Retro", content: template },
+            Note { path: "team-b/retro.md", title: "Retro", content: "# Retro\n\n##   went WELL\n## To improve\n" },
+            Note { path: "setup.md", title: "Setup", content: &lines },
+            Note { path: "guide/setup.md", title: "Setup", content: &format!("{}Step 11\n", lines) },
+        ];
+
+        assert_eq!(
+            vec![
+                Warning::Lint("team-b/retro.md".to_string(), 0, "same content as team-a/retro.md".to_string()),
+                Warning::Lint(
+                    "guide/setup.md".to_string(),
+                    0,
+                    "91% of the content is the same as setup.md".to_string()
+                ),
+            ],
+            Duplicates.check_all(&notes)
+        );
+    }
+
+    #[test]
+    fn external_links_test() {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let cache = format!("{0} 200 https://example.com/\n{0} 404 https://example.com/gone\n", now);
+        let dir = fixture(&[("external-links.txt", &cache)]);
+        let content = "# Links\n\n[Home](https://example.com/) and [gone](https://example.com/gone)\n\
+                       ```\n[code](https://example.com/code)\n```\n[setup](setup.md)\n";
+        let notes = [Note { path: "links.md", title: "Links", content }];
+
+        let check = ExternalLinks { cache: Some(dir.join("external-links.txt")), interval: Duration::ZERO };
+
+        assert_eq!(
+            vec![Warning::Lint("links.md".to_string(), 3, "dead link https://example.com/gone, status 404".to_string())],
+            check.check_all(&notes)
+        );
+        assert!(is_web_link("HTTPS://example.com") && !is_web_link("mailto:ada@example.com"));
+    }
+}
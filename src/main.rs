@@ -1,34 +1,56 @@
+use book_summary::changelog;
+use book_summary::lint::{Duplicates, ExternalLinks, Forbidden, RequiredKeys, Spelling, Stubs};
+use book_summary::migrate::Migration;
+use book_summary::date::{self, SortMode};
+use book_summary::filter::MaxSize;
+#[cfg(feature = "import")]
+use book_summary::import;
+use book_summary::report::ReportFormat;
+use book_summary::render::{self, count_entries, EpubNav, LinkStyle, ListFormat, Ncx, NoReadmeStyle, JSON_SCHEMA};
+use book_summary::split;
+use book_summary::title::{Dots, NumericNames, TitleStyle};
+use book_summary::{
+    Chapter, Collation, Format, Labels, Resolved, Slugger, SummaryBuilder, SummaryError, TitleSource, Warning,
+};
 use serde_json::Value as jsonValue;
+use std::borrow::Borrow;
 use std::env;
-use std::fmt;
 use std::fs::File;
 use std::io;
 use std::io::prelude::*;
-use std::path::Path;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+use structopt::clap::ArgMatches;
 use structopt::StructOpt;
 use toml::Value;
-use walkdir::{DirEntry, WalkDir};
-
-mod book;
-use book::Chapter;
-use book::Format;
-
-#[derive(Debug, PartialEq)]
-enum SummaryError {}
-
-impl fmt::Display for SummaryError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "There is an error: {}", self)
-    }
-}
-
-type Result<T> = std::result::Result<T, Box<SummaryError>>;
+use walkdir::WalkDir;
+
+// Share of entries (in percent) the summary may lose without --force
+const MAX_ENTRY_DROP: usize = 40;
+// entries of a notes dir without book config from which on it isn't scanned by accident
+const MAX_UNCONFIGURED_ENTRIES: usize = 50_000;
+
+/// Config file in the notes dir with defaults and profiles for the options
+const CONFIG_FILE: &str = "book-summary.toml";
+// the files marking the root of a project, found from its subdirs
+const ROOT_FILES: &[&str] = &["book.toml", "book.json", CONFIG_FILE];
+// the personal defaults in the XDG config dir, below the book-summary.toml
+const USER_CONFIG_FILE: &str = "config.toml";
+// the results of --check-external in the cache dir
+const LINK_CACHE_FILE: &str = "external-links.txt";
+
+// Exit codes, so that pipelines can tell the failures apart without parsing the output
+const EXIT_ERROR: i32 = 1;
+const EXIT_CONFIG: i32 = 2;
+const EXIT_IO: i32 = 3;
+const EXIT_LINT: i32 = 4;
 
 #[derive(StructOpt, Debug)]
 #[structopt()]
 struct Opt {
     /// Activate debug mode
+    #[allow(dead_code)]
     #[structopt(name = "debug", short, long)]
     debug: bool,
 
@@ -41,33 +63,470 @@ struct Opt {
     #[structopt(name = "mdheader", short, long)]
     mdheader: bool,
 
-    /// Format md/git book
-    #[structopt(name = "format", short, long, default_value = "md")]
-    format: Format,
+    /// Keep HTML entities (&amp;) and smart quotes in titles instead of decoding and straightening them
+    #[structopt(name = "raw-titles", long)]
+    raw_titles: bool,
 
-    /// Title for summary
-    #[structopt(name = "title", short, long, default_value = "Summary")]
-    title: String,
+    /// Where to take titles from, first match wins (filename, h1, frontmatter, bold, sentence, map:<file>)
+    #[structopt(name = "title-from", long)]
+    title_from: Vec<TitleSource>,
+
+    /// Format md/git book, docsify, json, quartz, filelist, epub-nav, ncx or auto (from the book config files)
+    #[structopt(name = "format", short, long, env = "BOOK_SUMMARY_FORMAT", default_value = "md")]
+    format: FormatArg,
+
+    /// Title for summary, by default the H1 of the root README or `Summary`
+    #[structopt(name = "title", short, long, env = "BOOK_SUMMARY_TITLE")]
+    title: Option<String>,
+
+    /// Keep the inner extension of file names in titles, drop it or use it as subtitle (keep, strip, subtitle)
+    #[structopt(name = "dots", long, env = "BOOK_SUMMARY_DOTS", default_value = "keep")]
+    dots: Dots,
+
+    /// Capitalization of titles from file and chapter names (title, sentence, asis)
+    #[structopt(name = "title-style", long, env = "BOOK_SUMMARY_TITLE_STYLE", default_value = "title")]
+    title_style: TitleStyle,
+
+    /// Titles of files named without letters like 2024.md: the name, parent title and number, or from the content (keep, parent, content)
+    #[structopt(name = "numeric-names", long, env = "BOOK_SUMMARY_NUMERIC_NAMES", default_value = "keep")]
+    numeric_names: NumericNames,
+
+    /// How chapters without README are listed (draft, text, link-first-child, skip) [default: of the format]
+    #[structopt(name = "no-readme-style", long, env = "BOOK_SUMMARY_NO_README_STYLE")]
+    no_readme_style: Option<NoReadmeStyle>,
+
+    /// Link the files relative to the summary or from the root of the notes dir, /guide/setup.md (relative, absolute)
+    #[structopt(name = "link-style", long, env = "BOOK_SUMMARY_LINK_STYLE", default_value = "relative")]
+    link_style: LinkStyle,
+
+    /// Link the markdown files without their .md extension, for sites with pretty URLs (md, git, docsify)
+    #[structopt(name = "strip-extension", long)]
+    strip_extension: bool,
+
+    /// Put in place of the stripped .md extension, e.g. .html or / [default: nothing]
+    #[structopt(name = "link-suffix", long, env = "BOOK_SUMMARY_LINK_SUFFIX", requires = "strip-extension")]
+    link_suffix: Option<String>,
+
+    /// Read at most this many bytes of each file for titles and front matter
+    #[structopt(name = "max-scan-bytes", long, env = "BOOK_SUMMARY_MAX_SCAN_BYTES")]
+    max_scan_bytes: Option<u64>,
+
+    /// Leave out files larger than this many bytes, e.g. exported dumps
+    #[structopt(name = "max-file-size", long, env = "BOOK_SUMMARY_MAX_FILE_SIZE")]
+    max_file_size: Option<u64>,
+
+    /// Cut titles longer than this many chars off with an ellipsis
+    #[structopt(name = "max-title-len", long, env = "BOOK_SUMMARY_MAX_TITLE_LEN")]
+    max_title_len: Option<usize>,
+
+    /// Split the mdBook summary into parts of at most this many entries
+    #[structopt(name = "max-part-entries", long, env = "BOOK_SUMMARY_MAX_PART_ENTRIES")]
+    max_part_entries: Option<usize>,
+
+    /// List at most this many files per chapter, the rest on a generated MORE.md page of the chapter
+    #[structopt(name = "max-entries-per-chapter", long, env = "BOOK_SUMMARY_MAX_ENTRIES_PER_CHAPTER")]
+    max_entries_per_chapter: Option<usize>,
+
+    /// Append the last modified date to each entry (md, git)
+    #[structopt(name = "timestamps", long)]
+    timestamps: bool,
+
+    /// Format of the dates of --timestamps (%Y-%m-%d, %d.%m.%Y %H:%M, ...)
+    #[structopt(name = "timestamp-format", long, env = "BOOK_SUMMARY_TIMESTAMP_FORMAT", default_value = "%Y-%m-%d")]
+    timestamp_format: String,
 
     /// Start with following chapters (space seperate)
     #[structopt(name = "sort", short, long)]
     sort: Option<Vec<String>>,
 
-    /// Output file
-    #[structopt(name = "outputfile", short, long, default_value = "SUMMARY.md")]
-    outputfile: String,
+    /// Order of the files of a chapter (name, frontmatter-date)
+    #[structopt(name = "sort-mode", long, env = "BOOK_SUMMARY_SORT_MODE", default_value = "name")]
+    sort_mode: SortMode,
+
+    /// Keep the order of the entries of the existing output file, only append new ones
+    #[structopt(name = "append-new-only", long)]
+    append_new_only: bool,
+
+    /// Front matter key of the date for --sort-mode frontmatter-date
+    #[structopt(name = "date-key", long, env = "BOOK_SUMMARY_DATE_KEY", default_value = "date")]
+    date_key: String,
+
+    /// Formats of front matter dates, the first matching one wins (%Y-%m-%d, %d.%m.%Y %H:%M, ...)
+    #[structopt(name = "date-format", long)]
+    date_format: Vec<String>,
+
+    /// Sort files and chapters the way readers of a language expect, by built-in rules rather than ICU (da, de, en, es, fi, fr, it, nb, nl, nn, no, pt, sv, pinyin)
+    #[structopt(name = "collate", long, env = "BOOK_SUMMARY_COLLATE", default_value = "codepoint")]
+    collate: Collation,
+
+    /// Separator of words in ids of entries (json)
+    #[structopt(name = "slug-separator", long, env = "BOOK_SUMMARY_SLUG_SEPARATOR", default_value = "-")]
+    slug_separator: char,
+
+    /// File names of chapter READMEs, the first one found wins (comma separate)
+    #[structopt(name = "readme-names", long, use_delimiter = true, default_value = "README.md")]
+    readme_names: Vec<String>,
+
+    /// Output file [default: of the format, SUMMARY.md, _sidebar.md (docsify), summary.json (json), ...]
+    #[structopt(name = "outputfile", short, long, env = "BOOK_SUMMARY_OUTPUTFILE")]
+    outputfile: Option<String>,
+
+    /// Summary of only this chapter of the notes dir (e.g. guide/setup), written to its folder
+    #[structopt(name = "root-chapter", long, env = "BOOK_SUMMARY_ROOT_CHAPTER")]
+    root_chapter: Option<String>,
+
+    /// Besides the summary, migrate the notes to another tool (gitbook-to-mdbook)
+    #[structopt(name = "migrate", long, env = "BOOK_SUMMARY_MIGRATE")]
+    migrate: Option<Migration>,
+
+    /// Extension of the content documents linked by the epub-nav and ncx formats
+    #[structopt(name = "epub-extension", long, env = "BOOK_SUMMARY_EPUB_EXTENSION", default_value = "xhtml")]
+    epub_extension: String,
+
+    /// Print the summary in this format to stdout instead of writing files (filelist, md, ...)
+    #[structopt(name = "emit", long, env = "BOOK_SUMMARY_EMIT")]
+    emit: Option<Format>,
+
+    /// Write several outputs from one scan instead of the output file (SUMMARY.md:md _sidebar.md:docsify)
+    #[structopt(name = "out", long)]
+    out: Vec<Output>,
 
     /// Notes dir where to parse all your notes from
-    #[structopt(name = "notesdir", short, long, default_value = ".")]
+    #[structopt(name = "notesdir", short, long, env = "BOOK_SUMMARY_NOTESDIR", default_value = ".")]
     dir: PathBuf,
 
+    /// Print the summary of a remote git repository, the notes dir is relative to it
+    #[structopt(name = "repo", long, env = "BOOK_SUMMARY_REPO")]
+    repo: Option<String>,
+
+    /// Branch or tag of the remote repository [default: its default branch]
+    #[structopt(name = "branch", long, env = "BOOK_SUMMARY_BRANCH", requires = "repo")]
+    branch: Option<String>,
+
+    /// Exclude following files or chapters, also as glob (space seperate)
+    #[structopt(name = "exclude", short, long)]
+    exclude: Vec<String>,
+
+    /// Show a top-level chapter with another title, e.g. part1="Getting started"
+    #[structopt(name = "rename", long)]
+    rename: Vec<Rename>,
+
+    /// List the entries of a dir in another chapter, e.g. archive/rust-old=notes/rust
+    #[structopt(name = "merge", long)]
+    merge: Vec<Merge>,
+
+    /// Only list notes whose front matter status is one of these, notes without one are kept (comma separate)
+    #[structopt(name = "status", long, use_delimiter = true)]
+    status: Vec<String>,
+
+    /// Warn about notes without these front matter keys, e.g. title,owner (comma separate)
+    #[structopt(name = "require-keys", long, use_delimiter = true)]
+    require_keys: Vec<String>,
+
+    /// Warn about these markers in notes outside of code blocks, e.g. TODO,FIXME (comma separate)
+    #[structopt(name = "forbid", long, use_delimiter = true)]
+    forbid: Vec<String>,
+
+    /// Warn about notes of different chapters with the same or nearly the same content
+    #[structopt(name = "duplicates", long)]
+    duplicates: bool,
+
+    /// Warn about dead http(s) links of the notes, checked with curl one per second and cached for a day
+    #[structopt(name = "check-external", long)]
+    check_external: bool,
+
+    /// Warn about chapters mdBook numbers differently than in the previous summary (md)
+    #[structopt(name = "renumbering", long)]
+    renumbering: bool,
+
+    /// Warn about stubs, notes smaller than this many bytes or with nothing but headings
+    #[structopt(name = "stub-size", long, env = "BOOK_SUMMARY_STUB_SIZE")]
+    stub_size: Option<u64>,
+
+    /// List stubs (of the --stub-size, or else with nothing but headings) as draft entries (md, git, docsify)
+    #[structopt(name = "draft-stubs", long)]
+    draft_stubs: bool,
+
+    /// Build the summary from the paths in this file (one per line or a JSON array, - for stdin) instead of scanning
+    #[structopt(name = "from-list", long, env = "BOOK_SUMMARY_FROM_LIST", parse(from_os_str))]
+    from_list: Option<PathBuf>,
+
+    /// Split this markdown file at its H1 and H2 headings into chapters of the notes dir, then generate the summary
+    #[structopt(name = "split", long, env = "BOOK_SUMMARY_SPLIT", parse(from_os_str))]
+    split: Option<PathBuf>,
+
+    /// Convert the .docx and .html documents of the notes dir to markdown with pandoc first (import feature)
+    #[structopt(name = "import", long)]
+    import: bool,
+
+    /// Warn about words of titles missing in this dictionary file, one word per line
+    #[structopt(name = "dictionary", long, env = "BOOK_SUMMARY_DICTIONARY", parse(from_os_str))]
+    dictionary: Option<PathBuf>,
+
+    /// Also link files with the following extensions, if the format allows it (comma separate)
+    #[structopt(name = "include-assets", long, use_delimiter = true)]
+    include_assets: Vec<String>,
+
+    /// Append a chapter with a page per front matter tag, written to the tags dir
+    #[structopt(name = "tag-index", long)]
+    tag_index: bool,
+
+    /// Append an Authors chapter with a page per git author, listing the notes they wrote most of
+    #[structopt(name = "author-index", long)]
+    author_index: bool,
+
+    /// List every page alphabetically by title on a generated INDEX.md, listed last
+    #[structopt(name = "az-index", long)]
+    az_index: bool,
+
+    /// Export the title, headings and first paragraph of each file to search-index.json
+    #[structopt(name = "search-index", long)]
+    search_index: bool,
+
+    /// Annotate the chapters with their owners from the CODEOWNERS of the repository (json)
+    #[structopt(name = "codeowners", long)]
+    codeowners: bool,
+
+    /// Write the title and weight of each chapter to its _index.md (Zola, Hugo) instead of a summary
+    #[structopt(name = "section-index", long)]
+    section_index: bool,
+
+    /// Write a summary per language folder and a LANGS.md instead of a summary (GitBook, HonKit)
+    #[structopt(name = "languages", long)]
+    languages: bool,
+
+    /// Maintain a backlinks section at the end of each file, listing the files linking to it
+    #[structopt(name = "backlinks", long)]
+    backlinks: bool,
+
+    /// List the README (or index.md) of the notes dir as introduction at the top
+    #[structopt(name = "include-root-readme", long)]
+    include_root_readme: bool,
+
+    /// End the markdown summaries with a comment of the version, options and time of the run
+    #[structopt(name = "footer", long)]
+    footer: bool,
+
+    /// Put the entries added, removed or renamed since the last run on top of this file
+    #[structopt(name = "changelog", long, env = "BOOK_SUMMARY_CHANGELOG")]
+    changelog: Option<String>,
+
+    /// Collect the terms of all notes into this glossary file, listed last (GLOSSARY.md)
+    #[structopt(name = "glossary", long, env = "BOOK_SUMMARY_GLOSSARY")]
+    glossary: Option<String>,
+
+    /// Write the redirect_from urls of the front matter to this redirect map (_redirects or JSON)
+    #[structopt(name = "redirects", long, env = "BOOK_SUMMARY_REDIRECTS")]
+    redirects: Option<String>,
+
+    /// List the following file as introduction at the top
+    #[structopt(name = "introduction", long, env = "BOOK_SUMMARY_INTRODUCTION")]
+    introduction: Option<String>,
+
+    /// Language of generated names like the heading (de, fr, ...)
+    #[structopt(name = "locale", long, env = "BOOK_SUMMARY_LOCALE")]
+    locale: Option<Labels>,
+
+    /// `[i18n]` table of the book.toml
+    #[structopt(skip)]
+    i18n: Option<Value>,
+
+    /// Leave out the blank lines of the summary, e.g. after the heading (md, git, docsify)
+    #[structopt(name = "compact", long)]
+    compact: bool,
+
+    /// Number of blank lines between the top-level chapters (md, git, docsify)
+    #[structopt(name = "chapter-spacing", long, env = "BOOK_SUMMARY_CHAPTER_SPACING")]
+    chapter_spacing: Option<usize>,
+
+    /// Fail on warnings (duplicate titles, empty chapters, case conflicts, unreadable files)
+    #[structopt(name = "strict", long)]
+    strict: bool,
+
+    /// What fails the run: warnings (as --strict), errors or never [default: errors]
+    #[structopt(name = "fail-on", long, env = "BOOK_SUMMARY_FAIL_ON")]
+    fail_on: Option<FailOn>,
+
+    /// Write the warnings of the run to a report for CI systems (junit, sarif)
+    #[structopt(name = "report", long, env = "BOOK_SUMMARY_REPORT")]
+    report: Option<ReportFormat>,
+
+    /// File of the report [default: of the format, book-summary-junit.xml (junit), book-summary.sarif (sarif)]
+    #[structopt(name = "report-file", long, env = "BOOK_SUMMARY_REPORT_FILE")]
+    report_file: Option<String>,
+
+    /// Overwrite the summary even if it loses 40% or more of its entries, scan the home dir or a huge dir without book config
+    #[structopt(name = "force", long)]
+    force: bool,
+
     /// Overwrite existing SUMMARY.md file
     #[structopt(name = "yes", short, long = "overwrite")]
     yes: bool,
+
+    /// Use the options of the `[profile.<profile>]` table of the book-summary.toml
+    #[structopt(name = "profile", long, env = "BOOK_SUMMARY_PROFILE")]
+    profile: Option<String>,
+
+    #[structopt(subcommand)]
+    cmd: Option<Command>,
+}
+
+/// Format of the summary, or `auto` to detect it from the files of the notes dir. The
+/// name is resolved once the formats of the config are registered.
+#[derive(Debug, Clone, PartialEq)]
+enum FormatArg {
+    Auto,
+    Name(String),
+}
+
+impl FromStr for FormatArg {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(FormatArg::Auto),
+            _ => Ok(FormatArg::Name(s.to_string())),
+        }
+    }
+}
+
+/// What fails the run, errors are still printed with `never`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FailOn {
+    Warnings,
+    Errors,
+    Never,
+}
+
+impl FromStr for FailOn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "warnings" => Ok(FailOn::Warnings),
+            "errors" => Ok(FailOn::Errors),
+            "never" => Ok(FailOn::Never),
+            _ => Err(format!("Invalid fail-on {}, expected one of: warnings, errors, never", s)),
+        }
+    }
+}
+
+/// Output file with its format, `<file>:<format>`
+#[derive(Debug, Clone, PartialEq)]
+struct Output {
+    file: String,
+    format: Format,
+}
+
+impl FromStr for Output {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.rsplit_once(':') {
+            Some((file, format)) if !file.is_empty() => Ok(Output {
+                file: file.to_string(),
+                format: format.parse().map_err(|e: SummaryError| e.to_string())?,
+            }),
+            _ => Err(format!("Invalid output {}, expected <file>:<format>", s)),
+        }
+    }
+}
+
+/// Dir listed in another chapter, `<dir>=<chapter>`
+#[derive(Debug, Clone, PartialEq)]
+struct Merge {
+    source: String,
+    target: String,
+}
+
+impl FromStr for Merge {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((source, target)) if !source.trim_matches('/').is_empty() => Ok(Merge {
+                source: source.to_string(),
+                target: target.to_string(),
+            }),
+            _ => Err(format!("Invalid merge {}, expected <dir>=<chapter>", s)),
+        }
+    }
+}
+
+/// Top-level chapter shown with another title, `<chapter>=<title>`
+#[derive(Debug, Clone, PartialEq)]
+struct Rename {
+    name: String,
+    title: String,
+}
+
+impl FromStr for Rename {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((name, title)) if !name.is_empty() && !title.trim().is_empty() => Ok(Rename {
+                name: name.to_string(),
+                title: title.trim().to_string(),
+            }),
+            _ => Err(format!("Invalid rename {}, expected <chapter>=<title>", s)),
+        }
+    }
+}
+
+#[derive(StructOpt, Debug)]
+enum Command {
+    /// Print the JSON Schema of the json format
+    Schema,
+    /// Create a page dated today (UTC) with front matter in a chapter, then regenerate the summary
+    New {
+        /// Title of the page
+        title: String,
+        /// Chapter dir the page is created in, relative to the notes dir [default: the notes dir]
+        #[structopt(long)]
+        chapter: Option<String>,
+    },
 }
 
 fn main() {
-    let mut opt = Opt::from_args();
+    let matches = Opt::clap().get_matches();
+    let mut run = Run::default();
+    if let Err(code) = summarize(&matches, &mut run) {
+        run.exit(code)
+    }
+}
+
+// What a run keeps until it ends, whichever stage it ends in
+#[derive(Default)]
+struct Run {
+    // set by `--fail-on never`, the run succeeds whatever happens
+    never_fail: bool,
+    // the clone of the remote repository, removed at the end of the run
+    clone: Option<RepoClone>,
+}
+
+impl Run {
+    // Exit with `code`, or successfully with `--fail-on never`
+    fn exit(self, code: i32) -> ! {
+        let code = if self.never_fail { 0 } else { code };
+        // the process ends without dropping the clone of the repository
+        drop(self);
+        std::process::exit(code)
+    }
+}
+
+// The stages of a run, a failure is printed where it happens and its exit code returned
+fn summarize(matches: &ArgMatches, run: &mut Run) -> Result<(), i32> {
+    let mut opt = Opt::from_clap(matches);
+    if let Err(err) = apply_env(&mut opt, matches, |name| env::var(name).ok()) {
+        eprintln!("Error: {}", err);
+        return Err(EXIT_CONFIG);
+    }
+
+    if let Some(Command::Schema) = opt.cmd {
+        print!("{}", JSON_SCHEMA);
+        return Ok(());
+    }
 
     // print opt in verbose level 3
     if opt.verbose > 2 {
@@ -75,125 +534,952 @@ fn main() {
         println!("{:?}", env::current_dir().unwrap().display());
     }
 
-    // parse book.js OR book.toml
-    match opt.format {
-        Format::Md(_) => parse_config_file(&format!("{}{}", opt.dir.display(), "/book.toml"), &mut opt),
-        Format::Git(_) => {
-            parse_config_file(&format!("{}{}", opt.dir.display(), "/book.json"), &mut opt);
-            parse_config_file(&format!("{}{}", opt.dir.display(), "/book.js"), &mut opt);
-        },
+    let format = load(&mut opt, matches, run)?;
+    // of the footer, before the options are taken apart
+    let options = options_hash(&opt);
+    let builder = configure(&opt, format)?;
+
+    // written however the run ends, the warnings failing strict mode included
+    let root = run.clone.as_ref().map(|c| c.0.clone()).unwrap_or_else(|| env::current_dir().unwrap());
+    let report = opt.report.map(|format| {
+        let file = opt.report_file.clone().unwrap_or_else(|| format.default_file().to_string());
+        (format, file, report_base(&opt.dir, &root))
+    });
+    let generated = generate(&opt, &builder, &options);
+    let reported = write_report(&report, &builder.reported());
+    generated.and(reported)
+}
+
+// Resolve the notes dir, the config files, the profile and the format of the run into
+// `opt`, returns the format
+fn load(opt: &mut Opt, matches: &ArgMatches, run: &mut Run) -> Result<Format, i32> {
+    // a shallow clone of the remote repository, removed at the end of the run
+    if let Some(repo) = &opt.repo {
+        let clone = RepoClone(clone_dir());
+        if let Err(err) = clone_repo(repo, opt.branch.as_deref(), &clone.0) {
+            eprintln!("Error: {}", err);
+            return Err(EXIT_IO);
+        }
+        opt.dir = clone.0.join(&opt.dir);
+        run.clone = Some(clone);
     }
 
-    if opt.dir == PathBuf::from("./") {
-        opt.dir = env::current_dir().unwrap();
+    // run in a dir of a project, its root is the notes dir (or has the config of it)
+    let notesdir = matches.occurrences_of("notesdir") > 0 || env::var("BOOK_SUMMARY_NOTESDIR").is_ok();
+    if run.clone.is_none() && !notesdir {
+        if let Some(root) = find_root(&env::current_dir().unwrap()) {
+            if opt.verbose > 0 {
+                println!("Found project root {}", root.display());
+            }
+            opt.dir = root;
+        }
+    }
+    let configured = ROOT_FILES.iter().any(|f| opt.dir.join(f).is_file());
+
+    // options from the command line or the environment win over the profile, the
+    // book-summary.toml over the personal defaults
+    let config = opt.dir.join(CONFIG_FILE);
+    let user_config = config_dir(|name| env::var(name).ok()).map(|dir| dir.join(USER_CONFIG_FILE));
+    let configs = user_config.iter().chain(std::iter::once(&config)).filter(|c| c.is_file());
+    let mut values: Option<Value> = None;
+    for file in configs.collect::<Vec<_>>() {
+        let read = std::fs::read_to_string(file)
+            .map_err(|why| why.to_string())
+            .and_then(|content| content.parse::<Value>().map_err(|why| why.to_string()));
+        match read {
+            Ok(read) => {
+                values = Some(match values.take() {
+                    Some(values) => merge_config(values, read),
+                    None => read,
+                })
+            }
+            Err(err) => {
+                eprintln!("Error: Couldn't parse {}: {}", file.display(), err);
+                return Err(EXIT_CONFIG);
+            }
+        }
+    }
+    if let Some(values) = values {
+        let profile = opt.profile.clone();
+        let var = |name: &str| env::var(name).ok();
+        if let Err(err) = apply_profile(opt, matches, &values, profile.as_deref(), var) {
+            let file = if config.is_file() { &config } else { user_config.as_ref().unwrap() };
+            eprintln!("Error: Couldn't parse {}: {}", file.display(), err);
+            return Err(EXIT_CONFIG);
+        }
+    } else if let Some(profile) = &opt.profile {
+        eprintln!("Error: Profile {} not found, {} doesn't exist", profile, config.display());
+        return Err(EXIT_CONFIG);
     }
 
-    if !opt.dir.is_dir() {
-        eprintln!("Error: Path {} not found!", opt.dir.display());
-        std::process::exit(1)
+    match opt.fail_on {
+        Some(FailOn::Warnings) => opt.strict = true,
+        Some(FailOn::Never) => run.never_fail = true,
+        _ => {}
     }
 
-    let entries = match get_dir(&opt.dir, &opt.outputfile) {
-        Ok(e) => e,
-        Err(err) => {
-            eprintln!("Error: {:?}", err);
-            std::process::exit(1)
+    // the format of the book tool whose files are found, mdBook without any
+    let mut format = match &opt.format {
+        FormatArg::Name(name) => name.parse::<Format>().map_err(failed)?,
+        FormatArg::Auto => {
+            let format = render::detect(&opt.dir).unwrap_or_else(Format::mdbook);
+            if opt.verbose > 0 {
+                println!("Detected format {}", format);
+            }
+            format
         }
     };
 
-    // SUMMARY.md file check if exists
-    if Path::new(&format!("{}/{}", &opt.dir.display(), &opt.outputfile)).exists() && !opt.yes {
-        loop {
-            println!(
-                "File {} already exists, do you want to overwrite it? [Y/n]",
-                &opt.outputfile
-            );
-            let mut input = String::new();
-            match io::stdin().read_line(&mut input) {
-                Ok(_) if &input == "y\n" || &input == "Y\n" || &input == "\n" => break,
-                Ok(_) if &input == "n\n" || &input == "N\n" => return,
-                _ => {}
-            }
+    // the EPUB formats link the content documents the markdown files become
+    if opt.epub_extension != "xhtml" {
+        let extension = opt.epub_extension.trim_start_matches('.').to_string();
+        render::register(EpubNav(extension.clone()));
+        render::register(Ncx(extension));
+        let outputs = opt.out.iter_mut().map(|o| &mut o.format);
+        let formats = std::iter::once(&mut format).chain(opt.emit.as_mut()).chain(outputs);
+        for format in formats.filter(|f| ["epub-nav", "ncx"].contains(&f.name())) {
+            *format = format.name().parse().unwrap();
         }
     }
 
-    if opt.verbose > 2 {
-        dbg!(&entries);
+    // the first output takes the place of the output file
+    if !opt.out.is_empty() {
+        let first = opt.out.remove(0);
+        opt.outputfile = Some(first.file);
+        format = first.format;
     }
 
-    let book = Chapter::new(opt.title, &entries);
+    // there is no checkout to write the summary of a remote repository to
+    if run.clone.is_some() && opt.emit.is_none() {
+        opt.emit = Some(format.clone());
+    }
 
-    create_file(
-        &opt.dir.to_str().unwrap(),
-        &opt.outputfile,
-        // &book.get_summary_file(&opt.format),
-        &book.get_summary_file(&opt.format, &opt.sort),
-    );
+    // parse the book config files of the format (book.js OR book.toml)
+    for config in format.renderer().config_files() {
+        let path = opt.dir.join(config);
+        if let Err(err) = parse_config_file(path.to_str().unwrap(), opt) {
+            eprintln!("Error: Couldn't parse {}: {}", path.display(), err);
+            return Err(EXIT_CONFIG);
+        }
+    }
+
+    if is_current_dir(&opt.dir) {
+        opt.dir = env::current_dir().unwrap();
+    }
+
+    // a mini book of the chapter, with the config of the whole book
+    if let Some(root) = &opt.root_chapter {
+        let dir = opt.dir.join(root.trim_matches('/'));
+        if !dir.is_dir() {
+            eprintln!("Error: Root chapter {} not found in {}", root, opt.dir.display());
+            return Err(EXIT_IO);
+        }
+        opt.dir = dir;
+    }
+
+    // e.g. run in the wrong terminal, a scan for minutes and a junk summary
+    if !opt.force && !configured && run.clone.is_none() && opt.from_list.is_none() {
+        let home = env::var("HOME").ok().map(PathBuf::from);
+        if let Err(err) = guard_dir(&opt.dir, home.as_deref(), MAX_UNCONFIGURED_ENTRIES) {
+            eprintln!("Error: {}, use --force to scan it anyway", err);
+            return Err(EXIT_ERROR);
+        }
+    }
+
+    // a new page is created before the summary is regenerated with it
+    if let Some(Command::New { title, chapter }) = &opt.cmd {
+        // in UTC like the other dates, there is no time zone database to ask
+        let today = date::format(date::timestamp(SystemTime::now()), "%Y-%m-%d");
+        let (file, content) = new_page(title, chapter.as_deref(), &today);
+        if opt.dir.join(&file).exists() {
+            eprintln!("Error: {} already exists", opt.dir.join(&file).display());
+            return Err(EXIT_IO);
+        }
+        create_dirs(&opt.dir, &file)?;
+        create_file(opt.dir.to_str().unwrap(), &file, &content)?;
+        // the page is only registered once the summary is overwritten
+        opt.yes = true;
+    }
+
+    // stdin is taken by the list, so there is no one to ask before overwriting
+    if opt.from_list.as_deref() == Some(Path::new("-")) {
+        opt.yes = true;
+    }
+    Ok(format)
+}
+
+// The builder of the summary with the options of the run, printed ones in `format`
+fn configure(opt: &Opt, format: Format) -> Result<SummaryBuilder, i32> {
+    // the locale from the command line wins over the one from the config
+    let mut labels = Labels::default();
+    if let Some(i18n) = &opt.i18n {
+        if let Err(err) = labels.merge_toml(i18n) {
+            eprintln!("Error: {}", err);
+            return Err(EXIT_CONFIG);
+        }
+    }
+    if let Some(locale) = &opt.locale {
+        labels = locale.clone();
+    }
+
+    let mut builder = SummaryBuilder::new()
+        .labels(labels)
+        .format(opt.emit.clone().unwrap_or(format))
+        .collate(opt.collate.clone())
+        .sort_mode(opt.sort_mode.clone())
+        .append_new_only(opt.append_new_only)
+        .date_key(opt.date_key.clone())
+        .readme_names(opt.readme_names.clone())
+        .slugger(Slugger::new(opt.slug_separator))
+        .include_root_readme(opt.include_root_readme)
+        .tag_index(opt.tag_index)
+        .author_index(opt.author_index)
+        .az_index(opt.az_index)
+        .search_index(opt.search_index)
+        .codeowners(opt.codeowners)
+        .backlinks(opt.backlinks)
+        .compact(opt.compact)
+        .raw_titles(opt.raw_titles)
+        .strict(opt.strict)
+        .verbose(opt.verbose)
+        .progress(true);
+    if let Some(outputfile) = &opt.outputfile {
+        builder = builder.outputfile(outputfile.as_str());
+    }
+    if let Some(title) = &opt.title {
+        builder = builder.title(title.as_str());
+    }
+    if let Some(sort) = &opt.sort {
+        builder = builder.sort(sort.clone());
+    }
+    if opt.dots != Dots::Keep {
+        builder = builder.dots(opt.dots);
+    }
+    if opt.title_style != TitleStyle::Title {
+        builder = builder.title_style(opt.title_style);
+    }
+    if opt.numeric_names != NumericNames::Keep {
+        builder = builder.numeric_names(opt.numeric_names);
+    }
+    if !opt.date_format.is_empty() {
+        builder = builder.date_formats(opt.date_format.clone());
+    }
+    if let Some(bytes) = opt.max_scan_bytes {
+        builder = builder.max_scan_bytes(bytes);
+    }
+    if let Some(bytes) = opt.max_file_size {
+        builder = builder.filter(MaxSize(bytes));
+    }
+    builder = builder.link_style(opt.link_style);
+    if opt.strip_extension {
+        builder = builder.strip_extension(opt.link_suffix.clone().unwrap_or_default());
+    }
+    if let Some(style) = opt.no_readme_style {
+        builder = builder.no_readme_style(style);
+    }
+    if let Some(len) = opt.max_title_len {
+        builder = builder.max_title_len(len);
+    }
+    if let Some(max) = opt.max_part_entries {
+        builder = builder.max_part_entries(max);
+    }
+    if let Some(max) = opt.max_entries_per_chapter {
+        builder = builder.max_entries_per_chapter(max);
+    }
+    if opt.timestamps {
+        builder = builder.timestamps(opt.timestamp_format.clone());
+    }
+    if let Some(introduction) = &opt.introduction {
+        builder = builder.introduction(introduction.as_str());
+    }
+    if let Some(glossary) = &opt.glossary {
+        builder = builder.glossary(glossary.as_str());
+    }
+    if let Some(changelog) = &opt.changelog {
+        builder = builder.changelog(changelog.as_str());
+    }
+    if let Some(redirects) = &opt.redirects {
+        builder = builder.redirects(redirects.as_str());
+    }
+    if let Some(migration) = opt.migrate {
+        builder = builder.migrate(migration);
+    }
+    builder = builder.languages(opt.languages);
+    if !opt.include_assets.is_empty() {
+        builder = builder.include_assets(opt.include_assets.clone());
+    }
+    if !opt.status.is_empty() {
+        builder = builder.status(opt.status.clone());
+    }
+    if !opt.require_keys.is_empty() {
+        builder = builder.check(RequiredKeys(opt.require_keys.clone()));
+    }
+    if !opt.forbid.is_empty() {
+        builder = builder.check(Forbidden(opt.forbid.clone()));
+    }
+    if opt.duplicates {
+        builder = builder.check(Duplicates);
+    }
+    if opt.check_external {
+        let cache = cache_dir(|name| env::var(name).ok()).join(LINK_CACHE_FILE);
+        builder = builder.check(ExternalLinks { cache: Some(cache), interval: Duration::from_secs(1) });
+    }
+    if let Some(size) = opt.stub_size {
+        builder = builder.check(Stubs(size));
+    }
+    builder = builder.renumbering(opt.renumbering);
+    if let Some(lines) = opt.chapter_spacing {
+        builder = builder.chapter_spacing(lines);
+    }
+    if opt.draft_stubs {
+        builder = builder.draft_stubs(opt.stub_size.unwrap_or(0));
+    }
+    if let Some(list) = &opt.from_list {
+        match read_list(list) {
+            Ok(paths) => builder = builder.from_list(paths),
+            Err(err) => {
+                eprintln!("Error: {}", err);
+                return Err(EXIT_IO);
+            }
+        }
+    }
+    if let Some(dictionary) = &opt.dictionary {
+        builder = builder.check(Spelling::from_file(dictionary).map_err(failed)?);
+    }
+    for exclude in &opt.exclude {
+        builder = builder.exclude(exclude.as_str());
+    }
+    for rename in &opt.rename {
+        builder = builder.rename(rename.name.as_str(), rename.title.as_str());
+    }
+    for merge in &opt.merge {
+        builder = builder.merge(merge.source.as_str(), merge.target.as_str());
+    }
+    for out in &opt.out {
+        builder = builder.output(out.file.as_str(), out.format.clone());
+    }
+    if !opt.title_from.is_empty() {
+        builder = builder.title_from(opt.title_from.clone());
+    } else if opt.mdheader {
+        builder = builder.title_from(vec![TitleSource::Header, TitleSource::Filename]);
+    }
+    Ok(builder)
+}
+
+// Scan the notes, then write the summaries and the pages generated from the notes
+fn generate(opt: &Opt, builder: &SummaryBuilder, options: &str) -> Result<(), i32> {
+    let (book, resolved) = scan(opt, builder)?;
+    if write_summaries(opt, builder, &book, &resolved, options)? {
+        write_artifacts(opt, builder, &book, &resolved)?;
+    }
 
     if opt.verbose > 2 {
         dbg!(&book);
     }
+    Ok(())
 }
 
-fn is_hidden(entry: &DirEntry) -> bool {
-    entry
-        .file_name()
-        .to_str()
-        .map(|s| s.starts_with('.'))
-        .unwrap_or(false)
+// The chapter tree of the notes with its titles, after the documents to import and
+// split are written to the notes dir
+fn scan(opt: &Opt, builder: &SummaryBuilder) -> Result<(Chapter, Resolved), i32> {
+    // the markdown of imported documents is written before it is scanned
+    if opt.import {
+        match import_dir(&opt.dir) {
+            Ok(files) => create_files(&opt.dir, files)?,
+            Err(err) => {
+                eprintln!("Error: {}", err);
+                return Err(EXIT_IO);
+            }
+        }
+    }
+
+    // as are the chapters of a single exported document
+    if let Some(document) = &opt.split {
+        let content = match read_document(document) {
+            Ok(content) => content,
+            Err(err) => {
+                eprintln!("Error: {}", err);
+                return Err(EXIT_IO);
+            }
+        };
+        for (file, content) in split::split(&content, &Slugger::default()) {
+            if opt.dir.join(&file).exists()
+                && !opt.yes
+                && !confirm(&format!("File {} already exists, do you want to overwrite it? [Y/n]", &file), true)
+            {
+                continue;
+            }
+            create_dirs(&opt.dir, &file)?;
+            create_file(opt.dir.to_str().unwrap(), &file, &content)?;
+        }
+    }
+
+    let book = builder.build(&opt.dir).map_err(failed)?;
+    // the titles are read once for the summaries and all the generated pages
+    let resolved = builder.resolve(&book, &opt.dir).map_err(failed)?;
+    Ok((book, resolved))
 }
 
-fn get_dir(dir: &PathBuf, outputfile: &str) -> Result<Vec<String>> {
-    let mut entries: Vec<String> = vec![];
-    for direntry in WalkDir::new(dir)
-        .sort_by(|a, b| a.file_name().cmp(b.file_name()))
-        .into_iter()
-        .filter_entry(|e| !is_hidden(e))
-        .filter_map(|e| e.ok())
-    {
-        // entry without:
-        // - given root folder
-        // - plain dirnames
-        // - not md files
-        // - not SUMMARY.md file
-        let entry = direntry.path().strip_prefix(dir).unwrap().to_str().unwrap();
-        if !entry.is_empty()
-            && !entry.eq(outputfile)
-            && !entry.to_lowercase().eq("readme.md")
-            && entry.contains(".md")
+// Write the summaries, or print the one to emit. Returns whether the pages generated
+// from the notes are written as well, not for the files replacing the summary.
+fn write_summaries(
+    opt: &Opt,
+    builder: &SummaryBuilder,
+    book: &Chapter,
+    resolved: &Resolved,
+    options: &str,
+) -> Result<bool, i32> {
+    // the menus of Zola and Hugo are ordered by the sections instead of a summary
+    if opt.section_index {
+        create_files(&opt.dir, builder.section_indexes(book, &opt.dir, resolved).map_err(failed)?)?;
+        return Ok(false);
+    }
+
+    // multilingual books have a summary per language instead of one for the whole book
+    let files = builder.language_files(book, &opt.dir).map_err(failed)?;
+    if !files.is_empty() {
+        create_files(&opt.dir, files)?;
+        return Ok(false);
+    }
+
+    // printed for pipelines instead of written
+    if opt.emit.is_some() {
+        print!("{}", builder.render(book, &opt.dir, resolved).map_err(failed)?);
+        return Ok(false);
+    }
+
+    let summaries = builder.render_outputs(book, &opt.dir, resolved).map_err(failed)?;
+    for (i, (outputfile, mut summary)) in summaries.into_iter().enumerate() {
+        if opt.footer && outputfile.ends_with(".md") {
+            // unchanged summaries keep the time of the run generating them
+            let previous = std::fs::read_to_string(opt.dir.join(&outputfile)).unwrap_or_default();
+            if strip_footer(&previous) == (summary.as_str(), Some(options)) {
+                if opt.verbose > 0 {
+                    eprintln!("{} is up to date", outputfile);
+                }
+                continue;
+            }
+            let now = date::format(date::timestamp(SystemTime::now()), "%Y-%m-%dT%H:%M:%SZ");
+            summary = footer(&summary, options, &now);
+        }
+
+        // SUMMARY.md file check if exists
+        if opt.dir.join(&outputfile).exists()
+            && !opt.yes
+            && !confirm(
+                &format!("File {} already exists, do you want to overwrite it? [Y/n]", &outputfile),
+                true,
+            )
         {
-            entries.push(entry.to_owned());
+            continue;
+        }
+
+        // guard against wiping a summary, e.g. when run in the wrong directory
+        let previous = std::fs::read_to_string(opt.dir.join(&outputfile)).ok();
+        if let Some(previous) = &previous {
+            let (before, after) = (count_entries(previous), count_entries(&summary));
+            if !opt.force && before > 0 && after * 100 <= before * (100 - MAX_ENTRY_DROP) {
+                let question = format!(
+                    "The summary {} would shrink from {} to {} entries, do you want to overwrite it anyway? [y/N]",
+                    outputfile, before, after
+                );
+                if opt.yes {
+                    eprintln!(
+                        "Error: The summary {} would shrink from {} to {} entries, use --force to overwrite it",
+                        outputfile, before, after
+                    );
+                    return Err(EXIT_ERROR);
+                } else if !confirm(&question, false) {
+                    continue;
+                }
+            }
+        }
+
+        create_file(opt.dir.to_str().unwrap(), &outputfile, &summary)?;
+
+        // compared to the summary of the last run
+        if let (0, Some(changelog), Some(previous)) = (i, &opt.changelog, &previous) {
+            write_changelog(&opt.dir, changelog, previous, &summary)?;
+        }
+    }
+    Ok(true)
+}
+
+// Write the pages generated from the notes besides the summaries: the tag, author and
+// overflow pages, the glossary, redirects, A–Z index and search metadata, the migrated
+// files and the backlinks
+fn write_artifacts(
+    opt: &Opt,
+    builder: &SummaryBuilder,
+    book: &Chapter,
+    resolved: &Resolved,
+) -> Result<(), i32> {
+    let dir = &opt.dir;
+    // the tag and author pages are generated, so they are always overwritten
+    let mut pages = builder.tag_pages(book, dir, resolved).map_err(failed)?;
+    pages.extend(builder.author_pages(book, dir, resolved).map_err(failed)?);
+    for (file, content) in pages {
+        create_dirs(dir, &file)?;
+        create_file(dir.to_str().unwrap(), &file, &content)?;
+    }
+
+    // as are the overflow pages of chapters with too many entries
+    let mut files = builder.more_pages(book, dir, resolved).map_err(failed)?;
+    // the glossary is generated as well
+    files.extend(builder.glossary_file(book, dir));
+    files.extend(builder.redirects_file(book, dir));
+    // and the A–Z index and search metadata
+    files.extend(builder.az_index_file(book, resolved));
+    files.extend(builder.search_index_file(book, dir, resolved).map_err(failed)?);
+    create_files(dir, files)?;
+
+    // the backlinks are added to the notes as the migration left them
+    create_files(dir, builder.migration_files(book, dir).map_err(failed)?)?;
+    create_files(dir, builder.backlink_files(book, dir, resolved).map_err(failed)?)
+}
+
+fn create_files(dir: &Path, files: Vec<(String, String)>) -> Result<(), i32> {
+    for (file, content) in files {
+        create_file(dir.to_str().unwrap(), &file, &content)?;
+    }
+    Ok(())
+}
+
+// Print the failure `err` of the library, returns its exit code
+fn failed<E: Borrow<SummaryError>>(err: E) -> i32 {
+    eprintln!("Error: {}", err.borrow());
+    exit_code(err.borrow())
+}
+
+// The exit code of the failure `err`
+fn exit_code(err: &SummaryError) -> i32 {
+    match err {
+        SummaryError::DirNotFound(_) | SummaryError::Io(..) => EXIT_IO,
+        SummaryError::Config(..) | SummaryError::UnknownFormat { .. } => EXIT_CONFIG,
+        SummaryError::Strict(_) => EXIT_LINT,
+    }
+}
+
+// Write the report of `warnings` to its file (relative to the current dir), if asked for
+fn write_report(report: &Option<(ReportFormat, String, String)>, warnings: &[Warning]) -> Result<(), i32> {
+    if let Some((format, file, base)) = report {
+        if let Err(why) = std::fs::write(file, format.render(warnings, base)) {
+            eprintln!("Error: Couldn't write {}: {}", file, why);
+            return Err(EXIT_IO);
         }
     }
-    Ok(entries)
+    Ok(())
+}
+
+// The notes `dir` relative to `root` (the current dir or the clone of the repository), so
+// that the files of a report resolve from there. Empty when it is outside of `root`.
+fn report_base(dir: &Path, root: &Path) -> String {
+    let dir = dir.canonicalize().unwrap_or_else(|_| root.join(dir));
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    dir.strip_prefix(root)
+        .map(|base| base.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/"))
+        .unwrap_or_default()
 }
 
-fn parse_config_file(path: &str, opt: &mut Opt) {
+// Flags and lists can't take their value from the environment like the other
+// options (clap would add the values of a list to the given ones), so they are
+// read here unless given on the command line. Flags are set with true (1, yes),
+// lists are comma separated.
+fn apply_env(
+    opt: &mut Opt,
+    matches: &ArgMatches,
+    var: impl Fn(&str) -> Option<String>,
+) -> Result<(), String> {
+    let given = |arg: &str| matches.occurrences_of(arg) > 0;
+    let flag = |name: &str| {
+        var(name)
+            .map(|v| ["1", "true", "yes"].contains(&v.trim().to_lowercase().as_str()))
+            .unwrap_or(false)
+    };
+    let list = |name: &str| {
+        var(name).map(|v| {
+            v.split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>()
+        })
+    };
+
+    opt.debug |= flag("BOOK_SUMMARY_DEBUG");
+    opt.mdheader |= flag("BOOK_SUMMARY_MDHEADER");
+    opt.raw_titles |= flag("BOOK_SUMMARY_RAW_TITLES");
+    opt.include_root_readme |= flag("BOOK_SUMMARY_INCLUDE_ROOT_README");
+    opt.footer |= flag("BOOK_SUMMARY_FOOTER");
+    opt.strict |= flag("BOOK_SUMMARY_STRICT");
+    opt.duplicates |= flag("BOOK_SUMMARY_DUPLICATES");
+    opt.check_external |= flag("BOOK_SUMMARY_CHECK_EXTERNAL");
+    opt.renumbering |= flag("BOOK_SUMMARY_RENUMBERING");
+    opt.draft_stubs |= flag("BOOK_SUMMARY_DRAFT_STUBS");
+    opt.compact |= flag("BOOK_SUMMARY_COMPACT");
+    opt.strip_extension |= flag("BOOK_SUMMARY_STRIP_EXTENSION");
+    opt.timestamps |= flag("BOOK_SUMMARY_TIMESTAMPS");
+    opt.tag_index |= flag("BOOK_SUMMARY_TAG_INDEX");
+    opt.author_index |= flag("BOOK_SUMMARY_AUTHOR_INDEX");
+    opt.az_index |= flag("BOOK_SUMMARY_AZ_INDEX");
+    opt.search_index |= flag("BOOK_SUMMARY_SEARCH_INDEX");
+    opt.import |= flag("BOOK_SUMMARY_IMPORT");
+    opt.codeowners |= flag("BOOK_SUMMARY_CODEOWNERS");
+    opt.backlinks |= flag("BOOK_SUMMARY_BACKLINKS");
+    opt.section_index |= flag("BOOK_SUMMARY_SECTION_INDEX");
+    opt.languages |= flag("BOOK_SUMMARY_LANGUAGES");
+    opt.append_new_only |= flag("BOOK_SUMMARY_APPEND_NEW_ONLY");
+    opt.force |= flag("BOOK_SUMMARY_FORCE");
+    opt.yes |= flag("BOOK_SUMMARY_OVERWRITE");
+    if !given("verbose") {
+        if let Some(verbose) = var("BOOK_SUMMARY_VERBOSE") {
+            opt.verbose = verbose
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid BOOK_SUMMARY_VERBOSE {}", verbose))?;
+        }
+    }
+
+    if !given("title-from") {
+        if let Some(sources) = list("BOOK_SUMMARY_TITLE_FROM") {
+            opt.title_from = sources
+                .iter()
+                .map(|s| s.parse())
+                .collect::<Result<_, _>>()?;
+        }
+    }
+    if !given("sort") {
+        if let Some(sort) = list("BOOK_SUMMARY_SORT") {
+            opt.sort = Some(sort);
+        }
+    }
+    if !given("readme-names") {
+        if let Some(names) = list("BOOK_SUMMARY_README_NAMES") {
+            opt.readme_names = names;
+        }
+    }
+    if !given("exclude") {
+        if let Some(exclude) = list("BOOK_SUMMARY_EXCLUDE") {
+            opt.exclude = exclude;
+        }
+    }
+    if !given("out") {
+        if let Some(outputs) = list("BOOK_SUMMARY_OUT") {
+            opt.out = outputs.iter().map(|o| o.parse()).collect::<Result<_, _>>()?;
+        }
+    }
+    if !given("date-format") {
+        if let Some(formats) = list("BOOK_SUMMARY_DATE_FORMAT") {
+            opt.date_format = formats;
+        }
+    }
+    if !given("rename") {
+        if let Some(renames) = list("BOOK_SUMMARY_RENAME") {
+            opt.rename = renames.iter().map(|r| r.parse()).collect::<Result<_, _>>()?;
+        }
+    }
+    if !given("merge") {
+        if let Some(merges) = list("BOOK_SUMMARY_MERGE") {
+            opt.merge = merges.iter().map(|m| m.parse()).collect::<Result<_, _>>()?;
+        }
+    }
+    if !given("status") {
+        if let Some(status) = list("BOOK_SUMMARY_STATUS") {
+            opt.status = status;
+        }
+    }
+    if !given("require-keys") {
+        if let Some(keys) = list("BOOK_SUMMARY_REQUIRE_KEYS") {
+            opt.require_keys = keys;
+        }
+    }
+    if !given("forbid") {
+        if let Some(markers) = list("BOOK_SUMMARY_FORBID") {
+            opt.forbid = markers;
+        }
+    }
+    if !given("include-assets") {
+        if let Some(assets) = list("BOOK_SUMMARY_INCLUDE_ASSETS") {
+            opt.include_assets = assets;
+        }
+    }
+    Ok(())
+}
+
+// The `book-summary` dir of the XDG base dir `var` (`XDG_CONFIG_HOME`), or else of
+// `fallback` in the home dir (`.config`). Relative base dirs are ignored, as the
+// spec asks.
+fn xdg_dir(var: &str, fallback: &str, env: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    let base = env(var)
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| env("HOME").map(|home| PathBuf::from(home).join(fallback)))?;
+    Some(base.join("book-summary"))
+}
+
+// The dir of the personal config.toml
+fn config_dir(env: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config", env)
+}
+
+// The dir of the clones of remote notes, the temp dir without a home
+fn cache_dir(env: impl Fn(&str) -> Option<String>) -> PathBuf {
+    xdg_dir("XDG_CACHE_HOME", ".cache", env).unwrap_or_else(|| env::temp_dir().join("book-summary"))
+}
+
+// The `user` config with the keys of the `project` config on top, tables like the
+// profiles are merged key by key
+fn merge_config(user: Value, project: Value) -> Value {
+    match (user, project) {
+        (Value::Table(mut user), Value::Table(project)) => {
+            for (key, value) in project {
+                let value = match user.remove(&key) {
+                    Some(below) => merge_config(below, value),
+                    None => value,
+                };
+                user.insert(key, value);
+            }
+            Value::Table(user)
+        }
+        (_, project) => project,
+    }
+}
+
+// Apply the top level keys of the book-summary.toml and those of `profile` on top
+// to the options neither given on the command line nor in the environment:
+//
+// ```toml
+// exclude = ["drafts"]
+//
+// [profile.public]
+// exclude = ["drafts", "internal"]
+// outputfile = "SUMMARY.md"
+//
+// [profile.internal]
+// outputfile = "INTERNAL.md"
+// ```
+fn apply_profile(
+    opt: &mut Opt,
+    matches: &ArgMatches,
+    config: &Value,
+    profile: Option<&str>,
+    var: impl Fn(&str) -> Option<String>,
+) -> Result<(), String> {
+    let mut table = config.as_table().cloned().unwrap_or_default();
+    let profiles = table.remove("profile");
+    if let Some(name) = profile {
+        match profiles.as_ref().and_then(|p| p.get(name)).and_then(|p| p.as_table()) {
+            Some(values) => table.extend(values.clone()),
+            None => return Err(format!("Profile {} not found", name)),
+        }
+    }
+
+    // formats described in the config, selected like the built-in ones
+    if let Some(formats) = table.remove("formats") {
+        let formats = formats.as_table().cloned().ok_or("`formats` must be a table")?;
+        for (name, values) in &formats {
+            render::register(ListFormat::from_toml(name, values)?);
+        }
+    }
+
+    let unset = |arg: &str| {
+        let name = format!("BOOK_SUMMARY_{}", arg.to_uppercase().replace('-', "_"));
+        matches.occurrences_of(arg) == 0 && var(&name).is_none()
+    };
+    for (key, value) in &table {
+        if !unset(key) {
+            continue;
+        }
+        if opt.verbose > 2 {
+            println!("Found `{}` in {}: {}", key, CONFIG_FILE, value);
+        }
+        let string = || {
+            value
+                .as_str()
+                .map(|s| s.to_string())
+                .ok_or(format!("`{}` must be a string", key))
+        };
+        let strings = || match value {
+            Value::String(s) => Ok(vec![s.clone()]),
+            Value::Array(values) => values
+                .iter()
+                .map(|v| v.as_str().map(|s| s.to_string()))
+                .collect::<Option<Vec<_>>>()
+                .ok_or(format!("`{}` must be a list of strings", key)),
+            _ => Err(format!("`{}` must be a list of strings", key)),
+        };
+        let flag = || value.as_bool().ok_or(format!("`{}` must be true or false", key));
+        match key.as_str() {
+            "title" => opt.title = Some(string()?),
+            "dots" => opt.dots = string()?.parse()?,
+            "title-style" => opt.title_style = string()?.parse()?,
+            "numeric-names" => opt.numeric_names = string()?.parse()?,
+            "no-readme-style" => opt.no_readme_style = Some(string()?.parse()?),
+            "link-style" => opt.link_style = string()?.parse()?,
+            "strip-extension" => opt.strip_extension = flag()?,
+            "link-suffix" => opt.link_suffix = Some(string()?),
+            "max-scan-bytes" => {
+                let bytes = value.as_integer().filter(|bytes| *bytes >= 0);
+                opt.max_scan_bytes = Some(bytes.ok_or("`max-scan-bytes` must be a number")? as u64)
+            }
+            "max-file-size" => {
+                let bytes = value.as_integer().filter(|bytes| *bytes >= 0);
+                opt.max_file_size = Some(bytes.ok_or("`max-file-size` must be a number")? as u64)
+            }
+            "max-part-entries" => {
+                let max = value.as_integer().filter(|max| *max > 0);
+                opt.max_part_entries = Some(max.ok_or("`max-part-entries` must be a number")? as usize)
+            }
+            "max-entries-per-chapter" => {
+                let max = value.as_integer().filter(|max| *max > 0);
+                let max = max.ok_or("`max-entries-per-chapter` must be a number")?;
+                opt.max_entries_per_chapter = Some(max as usize)
+            }
+            "max-title-len" => {
+                let len = value.as_integer().filter(|len| *len > 0);
+                opt.max_title_len = Some(len.ok_or("`max-title-len` must be a number")? as usize)
+            }
+            "timestamps" => opt.timestamps = flag()?,
+            "timestamp-format" => opt.timestamp_format = string()?,
+            "format" => opt.format = string()?.parse().unwrap(),
+            "epub-extension" => opt.epub_extension = string()?,
+            "migrate" => opt.migrate = Some(string()?.parse()?),
+            "emit" => opt.emit = Some(string()?.parse().map_err(|e: SummaryError| e.to_string())?),
+            "sort" => opt.sort = Some(strings()?),
+            "collate" => opt.collate = string()?.parse()?,
+            "sort-mode" => opt.sort_mode = string()?.parse()?,
+            "append-new-only" => opt.append_new_only = flag()?,
+            "date-key" => opt.date_key = string()?,
+            "date-format" => opt.date_format = strings()?,
+            "outputfile" => opt.outputfile = Some(string()?),
+            "exclude" => opt.exclude = strings()?,
+            "out" => opt.out = strings()?.iter().map(|o| o.parse()).collect::<Result<_, _>>()?,
+            "include-assets" => opt.include_assets = strings()?,
+            "status" => opt.status = strings()?,
+            "require-keys" => opt.require_keys = strings()?,
+            "forbid" => opt.forbid = strings()?,
+            "duplicates" => opt.duplicates = flag()?,
+            "check-external" => opt.check_external = flag()?,
+            "renumbering" => opt.renumbering = flag()?,
+            "stub-size" => {
+                let size = value.as_integer().filter(|size| *size >= 0);
+                opt.stub_size = Some(size.ok_or("`stub-size` must be a number")? as u64)
+            }
+            "draft-stubs" => opt.draft_stubs = flag()?,
+            "compact" => opt.compact = flag()?,
+            "chapter-spacing" => {
+                let lines = value.as_integer().filter(|lines| *lines >= 0);
+                opt.chapter_spacing = Some(lines.ok_or("`chapter-spacing` must be a number")? as usize)
+            }
+            "dictionary" => opt.dictionary = Some(PathBuf::from(string()?)),
+            "from-list" => opt.from_list = Some(PathBuf::from(string()?)),
+            "split" => opt.split = Some(PathBuf::from(string()?)),
+            "import" => opt.import = flag()?,
+            "rename" => {
+                opt.rename = match value.as_table() {
+                    Some(table) => table
+                        .iter()
+                        .map(|(name, title)| match title.as_str() {
+                            Some(title) => format!("{}={}", name, title).parse(),
+                            None => Err("`rename` must map chapters to titles".to_string()),
+                        })
+                        .collect::<Result<_, _>>()?,
+                    None => strings()?.iter().map(|r| r.parse()).collect::<Result<_, _>>()?,
+                }
+            }
+            // a table of `<dir> = <chapter>` or a list of `<dir>=<chapter>`
+            "merge" => {
+                opt.merge = match value.as_table() {
+                    Some(table) => table
+                        .iter()
+                        .map(|(source, target)| match target.as_str() {
+                            Some(target) => format!("{}={}", source, target).parse(),
+                            None => Err("`merge` must map dirs to chapters".to_string()),
+                        })
+                        .collect::<Result<_, _>>()?,
+                    None => strings()?.iter().map(|m| m.parse()).collect::<Result<_, _>>()?,
+                }
+            }
+            "readme-names" => opt.readme_names = strings()?,
+            "introduction" => opt.introduction = Some(string()?),
+            "root-chapter" => opt.root_chapter = Some(string()?),
+            "glossary" => opt.glossary = Some(string()?),
+            "redirects" => opt.redirects = Some(string()?),
+            "changelog" => opt.changelog = Some(string()?),
+            "locale" => opt.locale = Some(string()?.parse()?),
+            "title-from" => {
+                opt.title_from = strings()?.iter().map(|s| s.parse()).collect::<Result<_, _>>()?
+            }
+            "include-root-readme" => opt.include_root_readme = flag()?,
+            "footer" => opt.footer = flag()?,
+            "tag-index" => opt.tag_index = flag()?,
+            "author-index" => opt.author_index = flag()?,
+            "az-index" => opt.az_index = flag()?,
+            "search-index" => opt.search_index = flag()?,
+            "codeowners" => opt.codeowners = flag()?,
+            "backlinks" => opt.backlinks = flag()?,
+            "section-index" => opt.section_index = flag()?,
+            "languages" => opt.languages = flag()?,
+            "mdheader" => opt.mdheader = flag()?,
+            "raw-titles" => opt.raw_titles = flag()?,
+            "strict" => opt.strict = flag()?,
+            "report" => opt.report = Some(string()?.parse()?),
+            "report-file" => opt.report_file = Some(string()?),
+            "fail-on" => opt.fail_on = Some(string()?.parse()?),
+            _ => return Err(format!("Unknown option `{}`", key)),
+        }
+    }
+
+    // the books of the other profiles are no entries of this one
+    if let Some(profiles) = profiles.as_ref().and_then(|p| p.as_table()) {
+        let outputfile = |values: &Value| {
+            values.get("outputfile").and_then(|o| o.as_str()).map(|o| o.to_string())
+        };
+        let outputs = std::iter::once(outputfile(config).unwrap_or_else(|| "SUMMARY.md".to_string()))
+            .chain(profiles.values().filter_map(outputfile));
+        let current = match (&opt.outputfile, &opt.format) {
+            (Some(file), _) => file.clone(),
+            (None, FormatArg::Name(name)) => match name.parse::<Format>() {
+                Ok(format) => format.renderer().default_output().to_string(),
+                Err(_) => "SUMMARY.md".to_string(),
+            },
+            (None, FormatArg::Auto) => "SUMMARY.md".to_string(),
+        };
+        for output in outputs {
+            if output != current && !opt.exclude.contains(&output) {
+                opt.exclude.push(output);
+            }
+        }
+    }
+    Ok(())
+}
+
+// Ask until answered with yes or no, an empty answer is `default`
+fn confirm(question: &str, default: bool) -> bool {
+    loop {
+        println!("{}", question);
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(_) if &input == "y\n" || &input == "Y\n" => return true,
+            Ok(_) if &input == "n\n" || &input == "N\n" => return false,
+            Ok(_) if &input == "\n" => return default,
+            // no more input (e.g. not a terminal)
+            Ok(0) | Err(_) => return false,
+            _ => {}
+        }
+    }
+}
+
+fn parse_config_file(path: &str, opt: &mut Opt) -> Result<(), String> {
     let path = Path::new(path);
 
     if !path.exists() {
         if opt.verbose > 2 {
             eprintln!("Book config file {} not found.", path.display());
         }
-        return;
+        return Ok(());
     }
 
-    let mut file = match File::open(&path) {
-        Err(why) => panic!(
-            "Error: Couldn't open {}: {}",
-            path.display(),
-            why.to_string()
-        ),
-        Ok(file) => file,
-    };
-
-    let mut content = String::new();
-
-    if let Err(why) = file.read_to_string(&mut content) { panic!(
-        "Error: Couldn't read {}: {}",
-        path.display(),
-        why.to_string()
-    ) }
+    let content = std::fs::read_to_string(path).map_err(|why| why.to_string())?;
 
     if opt.verbose > 2 {
         println!("Found book config file: {}", path.display());
@@ -203,64 +1489,374 @@ fn parse_config_file(path: &str, opt: &mut Opt) {
 
     match ext {
         "toml" => {
-            let values = content.parse::<Value>().unwrap();
-            if opt.dir.to_str().eq(&Some(".")) {
-                if let Some(src) = values["book"]["src"].as_str() {
+            let values = content.parse::<Value>().map_err(|why| why.to_string())?;
+            let book = values.get("book");
+            if is_root_dir(&opt.dir) {
+                if let Some(src) = book.and_then(|b| b.get("src")).and_then(|s| s.as_str()) {
                     if opt.verbose > 2 {
                         println!("Found `src` in book.toml: {}", src);
                     }
-                    opt.dir = PathBuf::from(src);
+                    opt.dir = in_root(&opt.dir, src);
                 }
             }
 
-            if opt.title.eq("Summary") {
-                if let Some(title) = values["book"]["title"].as_str() {
+            if opt.title.is_none() {
+                if let Some(title) = book.and_then(|b| b.get("title")).and_then(|t| t.as_str()) {
                     if opt.verbose > 2 {
                         println!("Found `title` in book.toml: {}", title);
                     }
-                    opt.title = title.to_string();
+                    opt.title = Some(title.to_string());
+                }
+            }
+
+            // shared with the other mdBook tooling, besides the excludes
+            if let Some(ignore) = values.get("summary").and_then(|s| s.get("ignore")) {
+                if opt.verbose > 2 {
+                    println!("Found `summary.ignore` in book.toml: {}", ignore);
+                }
+                let globs = ignore.as_array().into_iter().flatten().filter_map(|g| g.as_str());
+                opt.exclude.extend(globs.map(|g| g.to_string()));
+            }
+
+            if let Some(i18n) = values.get("i18n") {
+                if opt.verbose > 2 {
+                    println!("Found `i18n` in book.toml: {}", i18n);
                 }
+                opt.i18n = Some(i18n.clone());
             }
         }
         "js" | "json" => {
-            let values: jsonValue = serde_json::from_str(&content).unwrap();
-            if opt.dir.to_str().eq(&Some(".")) {
+            let values: jsonValue = match serde_json::from_str(&content) {
+                Ok(values) => values,
+                // a book.js is JavaScript, only read if it is plain JSON
+                Err(why) if ext == "js" => {
+                    if opt.verbose > 2 {
+                        println!("Skipping {}, it isn't plain JSON: {}", path.display(), why);
+                    }
+                    return Ok(());
+                }
+                Err(why) => return Err(why.to_string()),
+            };
+            if is_root_dir(&opt.dir) {
                 if let Some(src) = values["root"].as_str() {
                     if opt.verbose > 2 {
                         println!("Found `root` in book.{}: {}", ext, src);
                     }
-                    opt.dir = PathBuf::from(src);
+                    opt.dir = in_root(&opt.dir, src);
                 }
             }
 
-            if opt.title.eq("Summary") {
+            if opt.title.is_none() {
                 if let Some(title) = values["title"].as_str() {
                     if opt.verbose > 2 {
                         println!("Found `title` in book.{}: {}", ext, title);
                     }
-                    opt.title = title.to_string();
+                    opt.title = Some(title.to_string());
                 }
             }
         }
         _ => {}
     }
+    Ok(())
+}
+
+// Path and content of a new page titled `title` in the `chapter` dir, named after the
+// `date` so the pages of a journal chapter are listed in the order they were written
+fn new_page(title: &str, chapter: Option<&str>, date: &str) -> (String, String) {
+    let name = format!("{}-{}.md", date, Slugger::default().slug(title));
+    let file = match chapter.map(|c| c.trim_matches('/')).filter(|c| !c.is_empty()) {
+        Some(chapter) => format!("{}/{}", chapter, name),
+        None => name,
+    };
+    let content = format!(
+        "---\ntitle: {}\ndate: {}\n---\n\n# {}\n",
+        serde_json::to_string(title).unwrap(),
+        date,
+        title
+    );
+    (file, content)
 }
 
-fn create_file(path: &str, filename: &str, content: &str) {
-    let filepath = format!("{}/{}", path, filename);
-    let path = Path::new(&filepath);
+// The markdown of the document at `path`, converted first if it's a .docx or .html file
+fn read_document(path: &Path) -> Result<String, String> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+    if ["docx", "html", "htm"].contains(&extension.as_str()) {
+        return convert_document(path);
+    }
+    std::fs::read_to_string(path).map_err(|why| format!("Couldn't read {}: {}", path.display(), why))
+}
+
+#[cfg(feature = "import")]
+fn convert_document(path: &Path) -> Result<String, String> {
+    import::to_markdown(path).map_err(|err| err.to_string())
+}
+
+#[cfg(not(feature = "import"))]
+fn convert_document(path: &Path) -> Result<String, String> {
+    Err(format!("Couldn't convert {}, book-summary is built without the import feature", path.display()))
+}
+
+#[cfg(feature = "import")]
+fn import_dir(dir: &Path) -> Result<Vec<(String, String)>, String> {
+    import::convert_dir(dir).map_err(|err| err.to_string())
+}
+
+#[cfg(not(feature = "import"))]
+fn import_dir(_dir: &Path) -> Result<Vec<(String, String)>, String> {
+    Err("--import needs book-summary built with the import feature".to_string())
+}
+
+// Dir of the clone of the remote repository of this run
+fn clone_dir() -> PathBuf {
+    cache_dir(|name| env::var(name).ok()).join(format!("repo-{}", std::process::id()))
+}
+
+// The clone of the remote repository, removed when dropped
+struct RepoClone(PathBuf);
+
+impl Drop for RepoClone {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+// Shallow clone of the git repository at `url` into `into`, of `branch` or else the
+// default branch
+fn clone_repo(url: &str, branch: Option<&str>, into: &Path) -> Result<(), String> {
+    if into.exists() {
+        std::fs::remove_dir_all(into)
+            .map_err(|why| format!("Couldn't remove {}: {}", into.display(), why))?;
+    }
+    let mut git = std::process::Command::new("git");
+    git.args(["clone", "--quiet", "--depth", "1"]);
+    if let Some(branch) = branch {
+        git.args(["--branch", branch]);
+    }
+    let output = git
+        .arg(url)
+        .arg(into)
+        .output()
+        .map_err(|why| format!("Couldn't run git: {}", why))?;
+    match output.status.success() {
+        true => Ok(()),
+        false => Err(format!(
+            "Couldn't clone {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+    }
+}
+
+// The paths of an entry list file, or of stdin for `-`
+fn read_list(path: &Path) -> Result<Vec<String>, String> {
+    let mut content = String::new();
+    let read = match path == Path::new("-") {
+        true => io::stdin().read_to_string(&mut content),
+        false => File::open(path).and_then(|mut file| file.read_to_string(&mut content)),
+    };
+    read.map_err(|why| format!("Couldn't read {}: {}", path.display(), why))?;
+    parse_list(&content).map_err(|why| format!("Invalid list {}: {}", path.display(), why))
+}
+
+// The paths of a JSON array of strings or else of the lines of `content`
+fn parse_list(content: &str) -> Result<Vec<String>, String> {
+    if content.trim_start().starts_with('[') {
+        return serde_json::from_str(content).map_err(|why| why.to_string());
+    }
+    Ok(content.lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect())
+}
+
+// `.`, `./` or `./.`
+fn is_current_dir(dir: &Path) -> bool {
+    dir.components().all(|c| c == Component::CurDir)
+}
+
+// Error for the `home` dir or a dir with more than `max` entries (hidden ones left
+// out) as notes dir, when it has no book config
+fn guard_dir(dir: &Path, home: Option<&Path>, max: usize) -> Result<(), String> {
+    let canonical = |dir: &Path| dir.canonicalize().ok();
+    if home.is_some() && canonical(dir) == home.and_then(canonical) {
+        return Err(format!("{} is the home dir and has no book config", dir.display()));
+    }
+    let entries = WalkDir::new(dir)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| !e.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|e| e.ok())
+        .take(max + 1)
+        .count();
+    match entries > max {
+        true => Err(format!("{} has more than {} entries and no book config", dir.display(), max)),
+        false => Ok(()),
+    }
+}
+
+// The current dir or a project root found above it (`../..`)
+fn is_root_dir(dir: &Path) -> bool {
+    dir.components().all(|c| c == Component::CurDir || c == Component::ParentDir)
+}
+
+// The `src` dir of a book config in the project `root`
+fn in_root(root: &Path, src: &str) -> PathBuf {
+    match is_current_dir(root) {
+        true => PathBuf::from(src),
+        false => root.join(src),
+    }
+}
+
+// The closest dir above `dir` with a book config or book-summary.toml, relative to
+// `dir` (`../..`). Like git, it doesn't look beyond the root of a repository, and
+// `None` if `dir` is a project root itself.
+fn find_root(dir: &Path) -> Option<PathBuf> {
+    let is_root = |dir: &Path| ROOT_FILES.iter().any(|f| dir.join(f).is_file());
+    if is_root(dir) || dir.join(".git").exists() {
+        return None;
+    }
+    let mut up = PathBuf::new();
+    for ancestor in dir.ancestors().skip(1) {
+        up.push("..");
+        if is_root(ancestor) {
+            return Some(up);
+        }
+        if ancestor.join(".git").exists() {
+            return None;
+        }
+    }
+    None
+}
+
+// `summary` with a comment of the tool version, `options` hash and time of the run
+fn footer(summary: &str, options: &str, now: &str) -> String {
+    let version = env!("CARGO_PKG_VERSION");
+    format!("{}\n<!-- book-summary {}, options {}, {} -->\n", summary, version, options, now)
+}
+
+// The `summary` without its footer and the options hash of the footer
+fn strip_footer(summary: &str) -> (&str, Option<&str>) {
+    let footer = summary
+        .strip_suffix(" -->\n")
+        .and_then(|s| s.rsplit_once("\n<!-- book-summary "))
+        .and_then(|(body, footer)| Some((body, footer.split(", ").nth(1)?.strip_prefix("options ")?)));
+    match footer {
+        Some((body, options)) => (body, Some(options)),
+        None => (summary, None),
+    }
+}
+
+// Stable hash of the resolved options which change the summary, in hex. How the run is
+// confirmed and reported and the dir of the book are left out, lists whose order
+// doesn't matter are sorted.
+fn options_hash(opt: &Opt) -> String {
+    let sorted = |list: &[String]| {
+        let mut list = list.to_vec();
+        list.sort();
+        list
+    };
+    let options: [&dyn std::fmt::Debug; 53] = [
+        &opt.mdheader,
+        &opt.raw_titles,
+        &opt.title_from,
+        &opt.format,
+        &opt.title,
+        &opt.dots,
+        &opt.title_style,
+        &opt.numeric_names,
+        &opt.no_readme_style,
+        &opt.link_style,
+        &opt.strip_extension,
+        &opt.link_suffix,
+        &opt.max_scan_bytes,
+        &opt.max_file_size,
+        &opt.max_title_len,
+        &opt.max_part_entries,
+        &opt.max_entries_per_chapter,
+        &opt.timestamps,
+        &opt.timestamp_format,
+        &opt.sort,
+        &opt.sort_mode,
+        &opt.append_new_only,
+        &opt.date_key,
+        &opt.date_format,
+        &opt.collate,
+        &opt.slug_separator,
+        &opt.readme_names,
+        &opt.outputfile,
+        &opt.root_chapter,
+        &opt.epub_extension,
+        &sorted(&opt.exclude),
+        &opt.rename,
+        &opt.merge,
+        &sorted(&opt.status),
+        &opt.stub_size,
+        &opt.draft_stubs,
+        &opt.from_list,
+        &sorted(&opt.include_assets),
+        &opt.tag_index,
+        &opt.author_index,
+        &opt.az_index,
+        &opt.search_index,
+        &opt.codeowners,
+        &opt.include_root_readme,
+        &opt.changelog,
+        &opt.glossary,
+        &opt.introduction,
+        &opt.locale,
+        &opt.i18n,
+        &opt.compact,
+        &opt.chapter_spacing,
+        &opt.footer,
+        &opt.backlinks,
+    ];
+    render::uid(&format!("{:?}", options))
+}
+
+// Put the entries added, removed or renamed since `previous` on top of the changelog
+fn write_changelog(dir: &Path, changelog: &str, previous: &str, summary: &str) -> Result<(), i32> {
+    let changes = changelog::diff(previous, summary, &[changelog]);
+    if changes.is_empty() {
+        return Ok(());
+    }
+    let existing = std::fs::read_to_string(dir.join(changelog)).ok();
+    let today = date::format(date::timestamp(SystemTime::now()), "%Y-%m-%d");
+    let content = changelog::prepend(existing.as_deref(), &today, &changes);
+    create_file(dir.to_str().unwrap(), changelog, &content)
+}
+
+// Create the missing parent dirs of `file` in `dir`
+fn create_dirs(dir: &Path, file: &str) -> Result<(), i32> {
+    if let Some(parent) = dir.join(file).parent() {
+        if let Err(why) = std::fs::create_dir_all(parent) {
+            eprintln!("Error: Couldn't create {}: {}", parent.display(), why);
+            return Err(EXIT_IO);
+        }
+    }
+    Ok(())
+}
+
+fn create_file(path: &str, filename: &str, content: &str) -> Result<(), i32> {
+    let filepath = Path::new(path).join(filename);
+    let path = filepath.as_path();
     let display = path.display();
 
     // Open a file in write-only mode, returns `io::Result<File>`
-    let mut file = match File::create(&path) {
-        Err(why) => panic!("Couldn't create {}: {}", display, why.to_string()),
+    let mut file = match File::create(path) {
+        Err(why) => {
+            eprintln!("Error: Couldn't create {}: {}", display, why);
+            return Err(EXIT_IO);
+        }
         Ok(file) => file,
     };
 
     // Write the `LOREM_IPSUM` string to `file`, returns `io::Result<()>`
     match file.write_all(content.as_bytes()) {
-        Err(why) => panic!("Couldn't write to {}: {}", display, why.to_string()),
-        Ok(_) => println!("Successfully create {}", display),
+        Err(why) => {
+            eprintln!("Error: Couldn't write to {}: {}", display, why);
+            Err(EXIT_IO)
+        }
+        Ok(_) => {
+            println!("Successfully create {}", display);
+            Ok(())
+        }
     }
 }
 
@@ -268,29 +1864,15 @@ fn create_file(path: &str, filename: &str, content: &str) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use book_summary::render::GitBook;
+    use book_summary::Chapter;
+    use book_summary::fixture::fixture;
 
     const TITLE: &str = "Summary";
-    const FORMAT: Format = Format::Git('*');
+    const LIST_CHAR: char = '*';
 
-    // # get file list: no hidden files, filepaths from given folder as root
-    #[test]
-    fn get_file_list_test() {
-        let expected = Ok(vec![
-            "about.md".to_string(),
-            "chapter1/FILE.md".to_string(),
-            "chapter1/file1.md".to_string(),
-            "chapter2/FILE1.md".to_string(),
-            "chapter2/README.md".to_string(),
-            "chapter2/file2.md".to_string(),
-            "chapter2/subchap/info.md".to_string(),
-            "chapter3/file1.md".to_string(),
-            "chapter3/file2.md".to_string(),
-            "chapter3/file3.md".to_string(),
-        ]);
-        assert_eq!(
-            expected,
-            get_dir(&PathBuf::from(r"./examples/gitbook/book"), &"SUMMARY.md")
-        );
+    fn format() -> Format {
+        Format::new(GitBook(LIST_CHAR))
     }
 
     #[test]
@@ -302,6 +1884,7 @@ mod tests {
             name: TITLE.to_string(),
             files: vec![],
             chapter: vec![],
+            order: vec![],
         };
 
         let book = Chapter::new(TITLE.to_string(), &input);
@@ -317,6 +1900,7 @@ mod tests {
             name: TITLE.to_string(),
             files: vec!["file.md".to_string()],
             chapter: vec![],
+            order: vec![],
         };
 
         let book = Chapter::new(TITLE.to_string(), &input);
@@ -336,7 +1920,9 @@ mod tests {
                 name: "chapter1".to_string(),
                 files: vec!["chapter1/file1.md".to_string()],
                 chapter: vec![],
+                order: vec![],
             }],
+            order: vec![],
         };
 
         let book = Chapter::new(TITLE.to_string(), &input);
@@ -362,8 +1948,11 @@ mod tests {
                     name: "subchap".to_string(),
                     files: vec!["chapter1/subchap/file1.md".to_string()],
                     chapter: vec![],
+                    order: vec![],
                 }],
+                order: vec![],
             }],
+            order: vec![],
         };
 
         let book = Chapter::new(TITLE.to_string(), &input);
@@ -377,10 +1966,7 @@ mod tests {
     //      - remove pre numbers in entry
     #[test]
     fn md_output_onefile_test() {
-        let list_char: char = match FORMAT {
-            Format::Md(c) => c,
-            Format::Git(c) => c,
-        };
+        let list_char: char = LIST_CHAR;
 
         // only one file
         let input: Vec<String> = vec!["file1.md".to_string()];
@@ -390,15 +1976,12 @@ mod tests {
         let book = Chapter::new(TITLE.to_string(), &input);
         dbg!(&book);
 
-        assert_eq!(expected, book.get_summary_file(&FORMAT, &None));
+        assert_eq!(expected, book.get_summary_file(&format(), &None));
     }
 
     #[test]
     fn md_output_onechapter_test() {
-        let list_char: char = match FORMAT {
-            Format::Md(c) => c,
-            Format::Git(c) => c,
-        };
+        let list_char: char = LIST_CHAR;
 
         // only one file
         let input: Vec<String> = vec!["file1.md".to_string(), "chapter1/file1.md".to_string()];
@@ -410,15 +1993,12 @@ mod tests {
 
         let book = Chapter::new(TITLE.to_string(), &input);
 
-        assert_eq!(expected, book.get_summary_file(&FORMAT, &None));
+        assert_eq!(expected, book.get_summary_file(&format(), &None));
     }
 
     #[test]
     fn md_output_subchapter_test() {
-        let list_char: char = match FORMAT {
-            Format::Md(c) => c,
-            Format::Git(c) => c,
-        };
+        let list_char: char = LIST_CHAR;
 
         // only one file
         let input: Vec<String> = vec![
@@ -433,7 +2013,7 @@ mod tests {
 
         let book = Chapter::new(TITLE.to_string(), &input);
 
-        assert_eq!(expected, book.get_summary_file(&FORMAT, &None));
+        assert_eq!(expected, book.get_summary_file(&format(), &None));
     }
 
     #[test]
@@ -459,7 +2039,7 @@ mod tests {
 
         let book = Chapter::new(TITLE.to_string(), &input);
 
-        assert_eq!(expected, book.get_summary_file(&FORMAT, &None));
+        assert_eq!(expected, book.get_summary_file(&format(), &None));
     }
 
     #[test]
@@ -472,26 +2052,361 @@ mod tests {
             debug: false,
             verbose: 3,
             mdheader: false,
-            format: FORMAT,
-            title: "Summary".to_string(),
+            raw_titles: false,
+            title_from: vec![],
+            format: FormatArg::Name("md".to_string()),
+            title: None,
+            dots: Dots::Keep,
+            numeric_names: NumericNames::Keep,
+            title_style: TitleStyle::Title,
+            no_readme_style: None,
+            link_style: LinkStyle::Relative,
+            strip_extension: false,
+            link_suffix: None,
+            max_scan_bytes: None,
+            max_file_size: None,
+            max_title_len: None,
+            max_part_entries: None,
+            max_entries_per_chapter: None,
+            timestamps: false,
+            tag_index: false,
+            author_index: false,
+            az_index: false,
+            search_index: false,
+            codeowners: false,
+            backlinks: false,
+            section_index: false,
+            languages: false,
+            timestamp_format: "%Y-%m-%d".to_string(),
             sort: None,
-            outputfile: "SUMMARY.md".to_string(),
+            sort_mode: SortMode::Name,
+            append_new_only: false,
+            date_key: "date".to_string(),
+            date_format: vec![],
+            collate: Collation::Codepoint,
+            slug_separator: '-',
+            readme_names: vec!["README.md".to_string()],
+            exclude: vec![],
+            include_assets: vec![],
+            rename: vec![],
+            merge: vec![],
+            status: vec![],
+            require_keys: vec![],
+            forbid: vec![],
+            duplicates: false,
+            check_external: false,
+            renumbering: false,
+            stub_size: None,
+            draft_stubs: false,
+            compact: false,
+            chapter_spacing: None,
+            dictionary: None,
+            from_list: None,
+            split: None,
+            import: false,
+            include_root_readme: false,
+            footer: false,
+            introduction: None,
+            root_chapter: None,
+            glossary: None,
+            redirects: None,
+            changelog: None,
+            locale: None,
+            i18n: None,
+            strict: false,
+            fail_on: None,
+            report: None,
+            report_file: None,
+            force: false,
+            profile: None,
+            cmd: None,
+            outputfile: None,
+            emit: None,
+            epub_extension: "xhtml".to_string(),
+            migrate: None,
+            out: vec![],
             dir: PathBuf::from("."),
+            repo: None,
+            branch: None,
             yes: true,
         };
 
-        parse_config_file(booktoml, &mut opt);
+        parse_config_file(booktoml, &mut opt).unwrap();
 
         assert_eq!("src", format!("{}", opt.dir.display()));
-        assert_eq!("MyMDBook", opt.title);
+        assert_eq!(Some("MyMDBook".to_string()), opt.title);
+        assert_eq!(vec!["drafts".to_string()], opt.exclude);
 
         opt.dir = PathBuf::from(".");
-        opt.title = "Summary".to_string();
+        opt.title = None;
 
-        parse_config_file(bookjson, &mut opt);
+        parse_config_file(bookjson, &mut opt).unwrap();
 
         assert_eq!("book", format!("{}", opt.dir.display()));
-        assert_eq!("My title", opt.title);
+        assert_eq!(Some("My title".to_string()), opt.title);
+
+        let dir = fixture(&[("book.toml", "[output.html]\n"), ("broken.toml", "[book\ntitle = ")]);
+        opt.dir = PathBuf::from(".");
+        let without_book = parse_config_file(dir.join("book.toml").to_str().unwrap(), &mut opt);
+        let broken = parse_config_file(dir.join("broken.toml").to_str().unwrap(), &mut opt);
+
+        assert_eq!(Ok(()), without_book);
+        assert_eq!(".", format!("{}", opt.dir.display()));
+        assert!(broken.is_err());
+    }
+
+    #[test]
+    fn env_test() {
+        let matches = Opt::clap().get_matches_from(["book-summary", "-o", "NAV.md", "-e", "tmp"]);
+        let mut opt = Opt::from_clap(&matches);
+        assert_eq!(Some("NAV.md".to_string()), opt.outputfile);
+
+        apply_env(&mut opt, &matches, |name| match name {
+            "BOOK_SUMMARY_STRICT" => Some("true".to_string()),
+            "BOOK_SUMMARY_FORCE" => Some("0".to_string()),
+            "BOOK_SUMMARY_VERBOSE" => Some("2".to_string()),
+            "BOOK_SUMMARY_EXCLUDE" => Some("drafts".to_string()),
+            "BOOK_SUMMARY_SORT" => Some("intro, usage".to_string()),
+            _ => None,
+        })
+        .unwrap();
+        assert!(opt.strict);
+        assert!(!opt.force);
+        assert_eq!(2, opt.verbose);
+        assert_eq!(vec!["tmp"], opt.exclude);
+        assert_eq!(Some(vec!["intro".to_string(), "usage".to_string()]), opt.sort);
+    }
+
+    #[test]
+    #[ignore = "clones with the git binary, run with --ignored"]
+    fn clone_repo_test() {
+        let dir = fixture(&[("about.md", "# About\n")]);
+        let clone = fixture(&[]);
+        for args in [&["init", "-q", "-b", "main"][..], &["add", "."], &["commit", "-q", "-m", "notes"]] {
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(&*dir)
+                .args(["-c", "user.name=Ada", "-c", "user.email=a@example.com"])
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+        let url = format!("file://{}", dir.display());
+
+        let missing = clone_repo(&url, Some("gh-pages"), &clone);
+        let cloned = clone_repo(&url, Some("main"), &clone);
+        let about = std::fs::read_to_string(clone.join("about.md"));
+
+        assert_eq!(Ok(()), cloned);
+        assert!(missing.is_err());
+        assert_eq!("# About\n", about.unwrap());
+    }
+
+    #[test]
+    fn parse_list_test() {
+        let paths = vec!["about.md".to_string(), "guide/setup.md".to_string()];
+        assert_eq!(Ok(paths.clone()), parse_list("about.md\n\n  guide/setup.md\n"));
+        assert_eq!(Ok(paths), parse_list(r#"["about.md", "guide/setup.md"]"#));
+        assert!(parse_list("[\"about.md\"").is_err());
+    }
+
+    #[test]
+    fn footer_test() {
+        let summary = "# Summary\n\n- [About](about.md)\n";
+        let hash = |args: &[&str]| options_hash(&Opt::from_clap(&Opt::clap().get_matches_from(args)));
+        let options = hash(&["book-summary", "-e", "a", "-e", "b"]);
+        assert_eq!(options, hash(&["book-summary", "-e", "b", "-e", "a", "-n", "/tmp/notes", "-vy", "--force"]));
+        assert_ne!(options, hash(&["book-summary", "-e", "a", "-e", "b", "-t", "Notes"]));
+        let written = footer(summary, &options, "2024-03-01T12:00:00Z");
+
+        assert_eq!(16, options.len());
+        assert!(written.starts_with("# Summary\n\n- [About](about.md)\n\n<!-- book-summary "));
+        assert!(written.ends_with(&format!(", options {}, 2024-03-01T12:00:00Z -->\n", options)));
+        assert_eq!((summary, Some(options.as_str())), strip_footer(&written));
+        assert_eq!((summary, None), strip_footer(summary));
+    }
+
+    #[test]
+    fn new_page_test() {
+        assert_eq!(
+            (
+                "meetings/2024-03-01-weekly-sync.md".to_string(),
+                "---\ntitle: \"Weekly: sync\"\ndate: 2024-03-01\n---\n\n# Weekly: sync\n".to_string()
+            ),
+            new_page("Weekly: sync", Some("meetings/"), "2024-03-01")
+        );
+        assert_eq!("2024-03-01-ideas.md", new_page("Ideas", None, "2024-03-01").0);
+    }
+
+    #[test]
+    fn formats_test() {
+        let config = r#"
+            format = "wiki"
+
+            [formats.wiki]
+            list = "*"
+            heading = false
+        "#
+        .parse::<Value>()
+        .unwrap();
+        let matches = Opt::clap().get_matches_from(["book-summary"]);
+        let mut opt = Opt::from_clap(&matches);
+
+        apply_profile(&mut opt, &matches, &config, None, |_| None).unwrap();
+        assert_eq!(FormatArg::Name("wiki".to_string()), opt.format);
+        let book = Chapter::new("Summary".to_string(), &["about.md".to_string()]);
+        let wiki = "wiki".parse::<Format>().unwrap();
+        assert_eq!("* [About](about.md)\n", book.get_summary_file(&wiki, &None));
+    }
+
+    #[test]
+    fn find_root_test() {
+        let dir = fixture(&[
+            ("book/book.toml", "[book]\nsrc = \"src\"\n"),
+            ("book-summary.toml", ""),
+        ]);
+        std::fs::create_dir_all(dir.join("book/src/guide")).unwrap();
+        std::fs::create_dir_all(dir.join("repo/.git")).unwrap();
+        std::fs::create_dir_all(dir.join("repo/notes")).unwrap();
+
+        let nested = find_root(&dir.join("book/src/guide"));
+        let root = find_root(&dir.join("book"));
+        let repo = find_root(&dir.join("repo/notes"));
+
+        assert_eq!(Some(PathBuf::from("../..")), nested);
+        assert_eq!(None, root);
+        assert_eq!(None, repo);
+        assert_eq!(PathBuf::from("../../src"), in_root(Path::new("../.."), "src"));
+    }
+
+    #[test]
+    fn guard_dir_test() {
+        let dir = fixture(&[("notes/a.md", ""), ("notes/b.md", ""), ("notes/.git/HEAD", "")]);
+
+        let home = guard_dir(&dir.join("notes/../notes"), Some(&dir.join("notes")), 10);
+        let small = guard_dir(&dir.join("notes"), Some(&dir), 2);
+        let huge = guard_dir(&dir.join("notes"), None, 1);
+
+        assert!(home.unwrap_err().ends_with("is the home dir and has no book config"));
+        assert_eq!(Ok(()), small);
+        assert!(huge.unwrap_err().ends_with("has more than 1 entries and no book config"));
+    }
+
+    #[test]
+    fn xdg_dir_test() {
+        let env = |name: &str| match name {
+            "HOME" => Some("/home/ada".to_string()),
+            "XDG_CACHE_HOME" => Some("cache".to_string()),
+            "XDG_CONFIG_HOME" => Some("/etc/xdg".to_string()),
+            _ => None,
+        };
+        assert_eq!(Some(PathBuf::from("/etc/xdg/book-summary")), config_dir(env));
+        assert_eq!(PathBuf::from("/home/ada/.cache/book-summary"), cache_dir(env));
+        assert_eq!(None, config_dir(|_| None));
+    }
+
+    #[test]
+    fn merge_config_test() {
+        let user = "title-style = \"sentence\"\nexclude = [\"tmp\"]\n\n[profile.public]\nexclude = [\"drafts\"]\n\
+                    strict = true\n"
+            .parse::<Value>()
+            .unwrap();
+        let project = "exclude = [\"internal\"]\n\n[profile.public]\nstrict = false\n".parse::<Value>().unwrap();
+
+        let expected = "title-style = \"sentence\"\nexclude = [\"internal\"]\n\n[profile.public]\n\
+                        exclude = [\"drafts\"]\nstrict = false\n"
+            .parse::<Value>()
+            .unwrap();
+        assert_eq!(expected, merge_config(user, project));
+    }
+
+    #[test]
+    fn apply_profile_test() {
+        let config = r#"
+            exclude = ["drafts"]
+            strict = true
+            fail-on = "never"
+
+            [profile.internal]
+            outputfile = "INTERNAL.md"
+            format = "git"
+        "#
+        .parse::<Value>()
+        .unwrap();
+        let matches = Opt::clap().get_matches_from(["book-summary", "-f", "json"]);
+        let mut opt = Opt::from_clap(&matches);
+
+        apply_profile(&mut opt, &matches, &config, Some("internal"), |_| None).unwrap();
+        assert_eq!(vec!["drafts", "SUMMARY.md"], opt.exclude);
+        assert!(opt.strict);
+        assert_eq!(Some(FailOn::Never), opt.fail_on);
+        assert_eq!(Some("INTERNAL.md".to_string()), opt.outputfile);
+        assert_eq!(FormatArg::Name("json".to_string()), opt.format);
+
+        let mut opt = Opt::from_clap(&matches);
+        apply_profile(&mut opt, &matches, &config, None, |name| match name {
+            "BOOK_SUMMARY_EXCLUDE" => Some("tmp".to_string()),
+            _ => None,
+        })
+        .unwrap();
+        // the json summary is written to summary.json
+        assert_eq!(vec!["SUMMARY.md", "INTERNAL.md"], opt.exclude);
+        assert_eq!(None, opt.outputfile);
+
+        assert!(apply_profile(&mut opt, &matches, &config, Some("public"), |_| None).is_err());
+    }
+
+    #[test]
+    fn output_test() {
+        assert_eq!(
+            Ok(Output { file: "_sidebar.md".to_string(), format: "docsify".parse().unwrap() }),
+            "_sidebar.md:docsify".parse()
+        );
+        assert_eq!(
+            Ok(Output { file: "C:\\book\\SUMMARY.md".to_string(), format: Format::mdbook() }),
+            "C:\\book\\SUMMARY.md:md".parse()
+        );
+        assert!("SUMMARY.md".parse::<Output>().is_err());
+        assert!("SUMMARY.md:mdbok".parse::<Output>().unwrap_err().contains("did you mean 'md'"));
+    }
+
+    #[test]
+    fn rename_test() {
+        let rename = Rename { name: "part1".to_string(), title: "Getting started".to_string() };
+        assert_eq!(Ok(rename), "part1=Getting started".parse());
+        assert!("part1".parse::<Rename>().is_err());
+        assert!("part1= ".parse::<Rename>().is_err());
+    }
+
+    #[test]
+    fn title_test() {
+        let dir = fixture(&[("README.md", "# My Notes\n"), ("a.md", "")]);
+        let title = |args: &[&str], config: &str| {
+            let matches = Opt::clap().get_matches_from(args);
+            let mut opt = Opt::from_clap(&matches);
+            apply_profile(&mut opt, &matches, &config.parse::<Value>().unwrap(), None, |_| None).unwrap();
+            let summary = configure(&opt, Format::mdbook()).unwrap().generate(&dir).unwrap();
+            summary.lines().next().unwrap().to_string()
+        };
+
+        assert_eq!("# My Notes", title(&["book-summary"], ""));
+        assert_eq!("# Summary", title(&["book-summary", "--title", "Summary"], ""));
+        assert_eq!("# Summary", title(&["book-summary"], "title = \"Summary\""));
+    }
+
+    #[test]
+    fn merge_test() {
+        let merge = Merge { source: "archive/rust-old".to_string(), target: "notes/rust".to_string() };
+        assert_eq!(Ok(merge.clone()), "archive/rust-old=notes/rust".parse());
+        assert!("archive".parse::<Merge>().is_err());
+        assert!("=notes".parse::<Merge>().is_err());
+
+        let config = "[merge]\n\"archive/rust-old\" = \"notes/rust\"\n".parse::<Value>().unwrap();
+        let matches = Opt::clap().get_matches_from(["book-summary"]);
+        let mut opt = Opt::from_clap(&matches);
+        apply_profile(&mut opt, &matches, &config, None, |_| None).unwrap();
+        assert_eq!(vec![merge], opt.merge);
     }
 
     #[test]
@@ -522,7 +2437,7 @@ mod tests {
         assert_eq!(
             expected,
             book.get_summary_file(
-                &FORMAT,
+                &format(),
                 &Some(vec![
                     "PART4".to_string(),
                     "part5".to_string(),
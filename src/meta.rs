@@ -0,0 +1,189 @@
+use crate::book::Chapter;
+use crate::date;
+use crate::frontmatter::{read_head, FrontMatter};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Metadata of a chapter, from the front matter of its README:
+///
+/// ```yaml
+/// ---
+/// title: Getting started
+/// weight: 10
+/// icon: 🚀
+/// draft: true
+/// ---
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ChapterMeta {
+    /// Display name instead of the one derived from the folder name
+    pub title: Option<String>,
+    /// Chapters with a weight come first, the lightest one first
+    pub weight: Option<i64>,
+    /// Shown in front of the title
+    pub icon: Option<String>,
+    /// Listed without a link to its README
+    pub draft: bool,
+    /// Teams or people owning the chapter, from the CODEOWNERS of the repository
+    pub owners: Vec<String>,
+}
+
+/// Chapter metadata keyed by the chapter path (relative to the notes dir)
+pub type ChapterMetas = HashMap<String, ChapterMeta>;
+
+impl ChapterMeta {
+    pub fn from_front_matter(front_matter: &FrontMatter) -> Self {
+        ChapterMeta {
+            title: front_matter
+                .get_str("title")
+                .filter(|t| !t.is_empty())
+                .map(|t| t.to_string()),
+            weight: front_matter.get_str("weight").and_then(|w| w.parse().ok()),
+            icon: front_matter
+                .get_str("icon")
+                .filter(|i| !i.is_empty())
+                .map(|i| i.to_string()),
+            draft: front_matter.get_bool("draft").unwrap_or(false),
+            owners: vec![],
+        }
+    }
+}
+
+/// Read the metadata of all chapters of `book` with a README front matter,
+/// reading at most `max_bytes` of each README
+pub fn resolve(
+    book: &Chapter,
+    root: &Path,
+    readme_names: &[String],
+    max_bytes: Option<u64>,
+) -> ChapterMetas {
+    let mut metas = ChapterMetas::new();
+    resolve_chapter(book, "", root, readme_names, max_bytes, &mut metas);
+    metas
+}
+
+fn resolve_chapter(
+    chapter: &Chapter,
+    path: &str,
+    root: &Path,
+    readme_names: &[String],
+    max_bytes: Option<u64>,
+    metas: &mut ChapterMetas,
+) {
+    if let Some(readme) = chapter.readme(readme_names) {
+        let front_matter = read_head(&root.join(readme), max_bytes)
+            .ok()
+            .and_then(|content| FrontMatter::parse(&content));
+        if let Some(front_matter) = front_matter {
+            metas.insert(path.to_string(), ChapterMeta::from_front_matter(&front_matter));
+        }
+    }
+
+    for c in &chapter.chapter {
+        let sub = match path {
+            "" => c.name.clone(),
+            _ => format!("{}/{}", path, c.name),
+        };
+        resolve_chapter(c, &sub, root, readme_names, max_bytes, metas);
+    }
+}
+
+/// Order the files of each chapter of `book` by the date under `key` in their
+/// front matter, newest first. Files without a date (in one of `formats`) use
+/// their modification time.
+pub fn sort_by_date(
+    book: &mut Chapter,
+    root: &Path,
+    key: &str,
+    formats: &[String],
+    max_bytes: Option<u64>,
+) {
+    let date = |file: &String| {
+        let path = root.join(file);
+        let front_matter = read_head(&path, max_bytes)
+            .ok()
+            .and_then(|content| FrontMatter::parse(&content));
+        front_matter
+            .and_then(|f| f.get_str(key).and_then(|value| date::parse(value, formats)))
+            .or_else(|| path.metadata().and_then(|m| m.modified()).ok().map(date::timestamp))
+            .unwrap_or(i64::MIN)
+    };
+    let mut dated = book.files.iter().map(|f| (date(f), f.clone())).collect::<Vec<_>>();
+    dated.sort_by_key(|(date, _)| std::cmp::Reverse(*date));
+    book.files = dated.into_iter().map(|(_, f)| f).collect();
+
+    for c in &mut book.chapter {
+        sort_by_date(c, root, key, formats, max_bytes);
+    }
+}
+
+/// Modification time of every file of `book` written in `format`, keyed by
+/// the file path. Files without one are left out.
+pub fn last_updated(book: &Chapter, root: &Path, format: &str) -> HashMap<String, String> {
+    let mut updated = HashMap::new();
+    for file in &book.files {
+        let modified = root.join(file).metadata().and_then(|m| m.modified());
+        if let Ok(modified) = modified {
+            updated.insert(file.clone(), date::format(date::timestamp(modified), format));
+        }
+    }
+    for c in &book.chapter {
+        updated.extend(last_updated(c, root, format));
+    }
+    updated
+}
+
+/// Order `chapters` (of the chapter at `path`) by their weight, keeping the
+/// order of chapters with the same or without a weight
+pub fn sort_by_weight(chapters: &mut [&Chapter], path: &str, metas: &ChapterMetas) {
+    let weight = |c: &Chapter| {
+        let sub = match path {
+            "" => c.name.clone(),
+            _ => format!("{}/{}", path, c.name),
+        };
+        metas.get(&sub).and_then(|m| m.weight)
+    };
+    chapters.sort_by_key(|c| match weight(c) {
+        Some(w) => (0, w),
+        None => (1, 0),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_front_matter_test() {
+        let front_matter =
+            FrontMatter::parse("---\ntitle: Start\nweight: -2\nicon: 🚀\ndraft: yes\n---\n").unwrap();
+        assert_eq!(
+            ChapterMeta {
+                title: Some("Start".to_string()),
+                weight: Some(-2),
+                icon: Some("🚀".to_string()),
+                draft: true,
+                owners: vec![],
+            },
+            ChapterMeta::from_front_matter(&front_matter)
+        );
+    }
+
+    #[test]
+    fn sort_by_weight_test() {
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &["a/x.md".to_string(), "b/x.md".to_string(), "c/x.md".to_string(), "d/x.md".to_string()],
+        );
+        let mut metas = ChapterMetas::new();
+        metas.insert("c".to_string(), ChapterMeta { weight: Some(2), ..Default::default() });
+        metas.insert("d".to_string(), ChapterMeta { weight: Some(1), ..Default::default() });
+
+        let mut chapters = book.chapter.iter().collect::<Vec<_>>();
+        sort_by_weight(&mut chapters, "", &metas);
+        assert_eq!(
+            vec!["d", "c", "a", "b"],
+            chapters.iter().map(|c| c.name.as_str()).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use crate::book::Chapter;
+use crate::links;
+use crate::warning::Warning;
+use std::path::Path;
+use std::str::FromStr;
+
+/// GitBook glossary, listed last by mdBook as a suffix chapter
+pub const GLOSSARY: &str = "GLOSSARY.md";
+/// GitBook languages index, mdBook has no multi-language books
+pub const LANGS: &str = "LANGS.md";
+
+/// Migration of a book to another tool, besides writing its summary
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Migration {
+    /// Links to folders point to their README, GitBook templates are reported
+    GitbookToMdbook,
+}
+
+impl FromStr for Migration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gitbook-to-mdbook" => Ok(Migration::GitbookToMdbook),
+            _ => Err(format!("Invalid migration {}, expected one of: gitbook-to-mdbook", s)),
+        }
+    }
+}
+
+/// The markdown files of `book` in `dir` whose content changes by migrating them to
+/// mdBook as `(file, content)`, with the constructs mdBook doesn't support
+pub fn gitbook_to_mdbook(book: &Chapter, dir: &Path) -> (Vec<(String, String)>, Vec<Warning>) {
+    let mut files = vec![];
+    let mut warnings = vec![];
+    if dir.join(LANGS).is_file() {
+        warnings.push(Warning::Unsupported(LANGS.to_string(), "languages".to_string()));
+    }
+    for file in book.all_files() {
+        if !file.to_lowercase().ends_with(".md") {
+            continue;
+        }
+        let content = match std::fs::read_to_string(dir.join(file)) {
+            Ok(content) => content,
+            Err(why) => {
+                warnings.push(Warning::Unreadable(dir.join(file), why.to_string()));
+                continue;
+            }
+        };
+        let migrated = readme_links(file, &content, |path| dir.join(path).is_dir());
+        for construct in unsupported(&migrated) {
+            warnings.push(Warning::Unsupported(file.clone(), construct));
+        }
+        if migrated != content {
+            files.push((file.clone(), migrated));
+        }
+    }
+    (files, warnings)
+}
+
+/// `content` of `file` with links to folders (`guide/`), which GitBook resolves to their
+/// README, pointing to the README (`guide/README.md`) as mdBook needs it
+pub fn readme_links(file: &str, content: &str, is_dir: impl Fn(&str) -> bool) -> String {
+    links::rewrite_targets(content, |target| {
+        let resolved = links::resolve(file, target)?;
+        if !is_dir(&resolved) {
+            return None;
+        }
+        let end = target.find(['#', '?']).unwrap_or(target.len());
+        let folder = target[..end].trim_end_matches('/');
+        let folder = if folder.is_empty() { "." } else { folder };
+        Some(format!("{}/README.md{}", folder, &target[end..]))
+    })
+}
+
+/// GitBook templates of the markdown `content` mdBook doesn't render, like
+/// `{% hint %}` blocks or `{{ book.var }}` variables, each named once
+pub fn unsupported(content: &str) -> Vec<String> {
+    let mut constructs = vec![];
+    let mut in_code = false;
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+        }
+        if in_code {
+            continue;
+        }
+        let mut rest = line;
+        while let Some(start) = rest.find("{%") {
+            rest = &rest[start + 2..];
+            let name = rest.split_whitespace().next().unwrap_or("").trim_end_matches("%}");
+            // the end tags belong to the construct
+            if !name.is_empty() && !name.starts_with("end") {
+                constructs.push(format!("{{% {} %}}", name));
+            }
+        }
+        if line.contains("{{") && line[line.find("{{").unwrap()..].contains("}}") {
+            constructs.push("{{ variables }}".to_string());
+        }
+    }
+    let mut unique = vec![];
+    for construct in constructs {
+        if !unique.contains(&construct) {
+            unique.push(construct);
+        }
+    }
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn readme_links_test() {
+        let content = "[Guide](../guide) [Setup](../guide/setup.md) [Up](../#top) [Web](https://x.org/)";
+        let is_dir = |path: &str| ["", "a", "guide"].contains(&path);
+        assert_eq!(
+            "[Guide](../guide/README.md) [Setup](../guide/setup.md) [Up](../README.md#top) \
+             [Web](https://x.org/)",
+            readme_links("a/b.md", content, is_dir)
+        );
+        assert_eq!("[A](./README.md)", readme_links("a/b.md", "[A](./)", is_dir));
+    }
+
+    #[test]
+    fn unsupported_test() {
+        let content = "{% hint style=\"info\" %}\nText {{ book.version }}\n{% endhint %}\n\
+                       {% hint %}\n```\n{% include \"x.md\" %}\n```\n";
+        assert_eq!(vec!["{% hint %}", "{{ variables }}"], unsupported(content));
+    }
+}
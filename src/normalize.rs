@@ -0,0 +1,157 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+// Hangul syllables are composed algorithmically
+const S_BASE: u32 = 0xAC00;
+const L_BASE: u32 = 0x1100;
+const V_BASE: u32 = 0x1161;
+const T_BASE: u32 = 0x11A7;
+const L_COUNT: u32 = 19;
+const V_COUNT: u32 = 21;
+const T_COUNT: u32 = 28;
+const N_COUNT: u32 = V_COUNT * T_COUNT;
+const S_COUNT: u32 = L_COUNT * N_COUNT;
+
+struct Tables {
+    decompositions: HashMap<char, Vec<char>>,
+    compositions: HashMap<(char, char), char>,
+    classes: HashMap<char, u8>,
+}
+
+fn tables() -> &'static Tables {
+    static TABLES: OnceLock<Tables> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let mut tables = Tables {
+            decompositions: HashMap::new(),
+            compositions: HashMap::new(),
+            classes: HashMap::new(),
+        };
+        let mut excluded = vec![];
+        let hex = |s: &str| char::from_u32(u32::from_str_radix(s, 16).unwrap()).unwrap();
+        for line in include_str!("../data/nfc.txt").lines().filter(|l| !l.starts_with('#')) {
+            let fields = line.split(' ').collect::<Vec<_>>();
+            match fields[0] {
+                "d" => {
+                    let chars = fields[2..].iter().map(|f| hex(f)).collect();
+                    tables.decompositions.insert(hex(fields[1]), chars);
+                }
+                "x" => excluded.push(hex(fields[1])),
+                "c" => {
+                    tables.classes.insert(hex(fields[1]), fields[2].parse().unwrap());
+                }
+                _ => {}
+            }
+        }
+        for (c, chars) in &tables.decompositions {
+            if let ([a, b], false) = (chars.as_slice(), excluded.contains(c)) {
+                tables.compositions.insert((*a, *b), *c);
+            }
+        }
+        tables
+    })
+}
+
+fn class(c: char) -> u8 {
+    tables().classes.get(&c).copied().unwrap_or(0)
+}
+
+fn decompose(c: char, out: &mut Vec<char>) {
+    let s = c as u32;
+    if (S_BASE..S_BASE + S_COUNT).contains(&s) {
+        let index = s - S_BASE;
+        out.push(char::from_u32(L_BASE + index / N_COUNT).unwrap());
+        out.push(char::from_u32(V_BASE + (index % N_COUNT) / T_COUNT).unwrap());
+        if index % T_COUNT != 0 {
+            out.push(char::from_u32(T_BASE + index % T_COUNT).unwrap());
+        }
+        return;
+    }
+    match tables().decompositions.get(&c) {
+        Some(chars) => chars.iter().for_each(|c| decompose(*c, out)),
+        None => out.push(c),
+    }
+}
+
+fn compose(a: char, b: char) -> Option<char> {
+    let (a, b) = (a as u32, b as u32);
+    if (L_BASE..L_BASE + L_COUNT).contains(&a) && (V_BASE..V_BASE + V_COUNT).contains(&b) {
+        return char::from_u32(S_BASE + ((a - L_BASE) * V_COUNT + b - V_BASE) * T_COUNT);
+    }
+    if (S_BASE..S_BASE + S_COUNT).contains(&a)
+        && (a - S_BASE) % T_COUNT == 0
+        && (T_BASE + 1..T_BASE + T_COUNT).contains(&b)
+    {
+        return char::from_u32(a + b - T_BASE);
+    }
+    let (a, b) = (char::from_u32(a)?, char::from_u32(b)?);
+    tables().compositions.get(&(a, b)).copied()
+}
+
+/// `s` in Unicode normalization form C, so names written as NFD (like on macOS)
+/// compare, sort and show the same as NFC ones
+pub(crate) fn nfc(s: &str) -> Cow<'_, str> {
+    if s.is_ascii() {
+        return Cow::Borrowed(s);
+    }
+
+    let mut chars = vec![];
+    for c in s.chars() {
+        decompose(c, &mut chars);
+    }
+    // canonical order of the combining marks following a starter
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        while i < chars.len() && class(chars[i]) != 0 {
+            i += 1;
+        }
+        chars[start..i].sort_by_key(|c| class(*c));
+        i = i.max(start + 1);
+    }
+
+    let mut composed: Vec<char> = Vec::with_capacity(chars.len());
+    let mut starter: Option<usize> = None;
+    let mut last_class = 0;
+    for c in chars {
+        let c_class = class(c);
+        if let Some(s) = starter {
+            // a mark blocked by one of the same or a higher class can't be composed
+            let blocked = composed.len() > s + 1 && (last_class == 0 || last_class >= c_class);
+            if !blocked {
+                if let Some(combined) = compose(composed[s], c) {
+                    composed[s] = combined;
+                    continue;
+                }
+            }
+        }
+        if c_class == 0 {
+            starter = Some(composed.len());
+        }
+        last_class = c_class;
+        composed.push(c);
+    }
+
+    let normalized = composed.into_iter().collect::<String>();
+    if normalized == s {
+        Cow::Borrowed(s)
+    } else {
+        Cow::Owned(normalized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nfc_test() {
+        assert_eq!("Café", nfc("Cafe\u{301}"));
+        assert_eq!("Café", nfc("Café"));
+        assert_eq!("Ångström", nfc("\u{212B}ngstro\u{308}m"));
+        assert_eq!("ệ", nfc("e\u{323}\u{302}"));
+        assert_eq!("ệ", nfc("e\u{302}\u{323}"));
+        assert_eq!("한글", nfc("\u{1112}\u{1161}\u{11AB}\u{1100}\u{1173}\u{11AF}"));
+        assert!(matches!(nfc("plain/path.md"), Cow::Borrowed(_)));
+    }
+}
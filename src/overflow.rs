@@ -0,0 +1,96 @@
+use crate::book::{file_title, Chapter};
+use crate::links;
+use crate::render::target;
+use crate::title::Titles;
+
+/// File name of the generated page listing the overflow of a chapter
+pub const MORE_FILE: &str = "MORE.md";
+
+/// Path of the overflow page of the chapter at `path`
+pub fn page(path: &str) -> String {
+    match path {
+        "" => MORE_FILE.to_string(),
+        _ => format!("{}/{}", path, MORE_FILE),
+    }
+}
+
+/// Keep the first `max` files of each chapter besides its README and move the rest
+/// to its overflow page, which takes their place as the last file. Returns the pages
+/// with the files they list.
+pub fn split(book: &mut Chapter, max: usize, readme_names: &[String]) -> Vec<(String, Vec<String>)> {
+    let mut pages = vec![];
+    split_chapter(book, "", max, readme_names, &mut pages);
+    pages
+}
+
+fn split_chapter(
+    chapter: &mut Chapter,
+    path: &str,
+    max: usize,
+    readme_names: &[String],
+    pages: &mut Vec<(String, Vec<String>)>,
+) {
+    let readme = chapter.readme(readme_names).cloned();
+    let mut kept = 0;
+    let mut overflow = vec![];
+    chapter.files.retain(|file| {
+        if Some(file) == readme.as_ref() || kept < max {
+            kept += (Some(file) != readme.as_ref()) as usize;
+            true
+        } else {
+            overflow.push(file.clone());
+            false
+        }
+    });
+    if !overflow.is_empty() {
+        chapter.files.push(page(path));
+        pages.push((page(path), overflow));
+    }
+    for c in &mut chapter.chapter {
+        let sub = match path {
+            "" => c.name.clone(),
+            _ => format!("{}/{}", path, c.name),
+        };
+        split_chapter(c, &sub, max, readme_names, pages);
+    }
+}
+
+/// The overflow pages titled `heading` as `(path, content)`, linking the files with
+/// their resolved `titles`
+pub fn pages(overflows: &[(String, Vec<String>)], heading: &str, titles: &Titles) -> Vec<(String, String)> {
+    overflows
+        .iter()
+        .map(|(page, files)| {
+            let mut content = format!("# {}\n\n", heading);
+            for file in files {
+                let link = target(&links::relative(page, file));
+                content += &format!("- [{}]({})\n", file_title(titles, file), link);
+            }
+            (page.clone(), content)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_test() {
+        let entries = ["a.md", "b.md", "guide/README.md", "guide/x.md", "guide/y.md", "guide/z.md"];
+        let mut book = Chapter::new(
+            "Summary".to_string(),
+            &entries.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+        );
+
+        let overflows = split(&mut book, 2, &["README.md".to_string()]);
+
+        assert_eq!(vec!["a.md", "b.md"], book.files);
+        assert_eq!(vec!["guide/README.md", "guide/x.md", "guide/y.md", "guide/MORE.md"], book.chapter[0].files);
+        assert_eq!(vec![("guide/MORE.md".to_string(), vec!["guide/z.md".to_string()])], overflows);
+        assert_eq!(
+            vec![("guide/MORE.md".to_string(), "# More…\n\n- [Z](z.md)\n".to_string())],
+            pages(&overflows, "More…", &Titles::new())
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use crate::book::Chapter;
+use crate::filter::is_excluded;
+use crate::meta::ChapterMetas;
+use std::path::Path;
+
+/// Locations of the CODEOWNERS file relative to the repository root, as GitHub and
+/// GitLab look for it
+pub const CODEOWNERS_FILES: &[&str] =
+    &["CODEOWNERS", ".github/CODEOWNERS", ".gitlab/CODEOWNERS", "docs/CODEOWNERS"];
+
+/// The rules of a CODEOWNERS file with the path of the notes dir in the repository
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeOwners {
+    /// `(pattern, owners)`, the last matching rule wins
+    pub rules: Vec<(String, Vec<String>)>,
+    /// Path of the notes dir relative to the repository root, empty at the root
+    pub prefix: String,
+}
+
+impl CodeOwners {
+    /// Parse the rules of a CODEOWNERS file, comments and empty lines are ignored
+    pub fn parse(content: &str, prefix: &str) -> Self {
+        let rules = content
+            .lines()
+            .map(|line| line.split(" #").next().unwrap_or(line).trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('['))
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let pattern = parts.next()?.to_string();
+                Some((pattern, parts.map(|o| o.to_string()).collect()))
+            })
+            .collect();
+        CodeOwners {
+            rules,
+            prefix: prefix.trim_matches('/').to_string(),
+        }
+    }
+
+    /// The CODEOWNERS of the repository `dir` is part of, looked up in `dir` and its parents
+    pub fn find(dir: &Path) -> Option<Self> {
+        let dir = dir.canonicalize().ok()?;
+        for root in dir.ancestors() {
+            for file in CODEOWNERS_FILES {
+                if let Ok(content) = std::fs::read_to_string(root.join(file)) {
+                    let prefix = dir.strip_prefix(root).ok()?.to_str()?.replace('\\', "/");
+                    return Some(CodeOwners::parse(&content, &prefix));
+                }
+            }
+            if root.join(".git").exists() {
+                break;
+            }
+        }
+        None
+    }
+
+    /// Owners of the file or dir at `path` relative to the notes dir, empty if none
+    /// or the matching rule removes them
+    pub fn owners(&self, path: &str) -> &[String] {
+        let path = match self.prefix.as_str() {
+            "" => path.to_string(),
+            prefix => format!("{}/{}", prefix, path),
+        };
+        self.rules
+            .iter()
+            .rev()
+            .find(|(pattern, _)| matches(pattern, &path))
+            .map(|(_, owners)| owners.as_slice())
+            .unwrap_or(&[])
+    }
+}
+
+// Like gitignore: patterns with an inner or leading slash are relative to the root,
+// others match at any depth
+fn matches(pattern: &str, path: &str) -> bool {
+    if pattern == "*" {
+        return true;
+    }
+    let anchored = pattern.trim_end_matches('/').contains('/');
+    let pattern = pattern.trim_start_matches('/');
+    if anchored {
+        return is_excluded(path, pattern);
+    }
+    std::iter::once(0)
+        .chain(path.match_indices('/').map(|(i, _)| i + 1))
+        .any(|i| is_excluded(&path[i..], pattern))
+}
+
+/// Set the owners of each chapter of `book` in `metas`: the owners of its dir, or
+/// else those of its README
+pub fn annotate(
+    book: &Chapter,
+    codeowners: &CodeOwners,
+    readme_names: &[String],
+    metas: &mut ChapterMetas,
+) {
+    annotate_chapter(book, "", codeowners, readme_names, metas);
+}
+
+fn annotate_chapter(
+    chapter: &Chapter,
+    path: &str,
+    codeowners: &CodeOwners,
+    readme_names: &[String],
+    metas: &mut ChapterMetas,
+) {
+    for c in &chapter.chapter {
+        let sub = match path {
+            "" => c.name.clone(),
+            _ => format!("{}/{}", path, c.name),
+        };
+        let mut owners = codeowners.owners(&sub);
+        if owners.is_empty() {
+            if let Some(readme) = c.readme(readme_names) {
+                owners = codeowners.owners(readme);
+            }
+        }
+        if !owners.is_empty() {
+            metas.entry(sub.clone()).or_default().owners = owners.to_vec();
+        }
+        annotate_chapter(c, &sub, codeowners, readme_names, metas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn owners_test() {
+        let content = "# Docs\n* @org/docs\n/docs/guide/ @org/guide @ada\nsetup.md @org/setup # setup\n\
+                       /docs/guide/internal/\n";
+        let codeowners = CodeOwners::parse(content, "docs/");
+
+        assert_eq!(["@org/docs"], codeowners.owners("about.md"));
+        assert_eq!(["@org/guide", "@ada"], codeowners.owners("guide"));
+        assert_eq!(["@org/guide", "@ada"], codeowners.owners("guide/cli/build.md"));
+        assert_eq!(["@org/setup"], codeowners.owners("guide/cli/setup.md"));
+        assert!(codeowners.owners("guide/internal/secret.md").is_empty());
+    }
+}
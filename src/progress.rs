@@ -0,0 +1,69 @@
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+/// Entries handled before a progress bar is shown, smaller runs finish fast enough
+pub(crate) const THRESHOLD: usize = 2000;
+
+const WIDTH: usize = 30;
+const REDRAW: Duration = Duration::from_millis(100);
+
+/// Progress of a long phase on stderr, shown only if it is a terminal and once
+/// more than [`THRESHOLD`] entries were handled
+pub(crate) struct Progress {
+    label: &'static str,
+    total: Option<usize>,
+    count: usize,
+    enabled: bool,
+    shown: bool,
+    drawn: Instant,
+}
+
+impl Progress {
+    /// Progress of `label`, with a bar if the `total` is known up front
+    pub(crate) fn new(label: &'static str, total: Option<usize>, enabled: bool) -> Self {
+        Progress {
+            label,
+            total,
+            count: 0,
+            enabled: enabled && std::io::stderr().is_terminal(),
+            shown: false,
+            drawn: Instant::now(),
+        }
+    }
+
+    /// A progress which is never shown
+    pub(crate) fn hidden() -> Self {
+        Self::new("", None, false)
+    }
+
+    pub(crate) fn inc(&mut self) {
+        self.count += 1;
+        if !self.enabled || self.count <= THRESHOLD || (self.shown && self.drawn.elapsed() < REDRAW) {
+            return;
+        }
+        self.shown = true;
+        self.drawn = Instant::now();
+        let line = match self.total {
+            Some(total) if total > 0 => {
+                let done = (self.count.min(total) * WIDTH) / total;
+                let bar = format!("{}{}", "#".repeat(done), "-".repeat(WIDTH - done));
+                format!("{} [{}] {}/{}", self.label, bar, self.count, total)
+            }
+            _ => format!("{} {}", self.label, self.count),
+        };
+        let mut stderr = std::io::stderr();
+        let _ = write!(stderr, "\r\x1b[2K{}", line);
+        let _ = stderr.flush();
+    }
+}
+
+// the line of the progress is cleared once done
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if self.shown {
+            let mut stderr = std::io::stderr();
+            let _ = write!(stderr, "\r\x1b[2K");
+            let _ = stderr.flush();
+        }
+    }
+}
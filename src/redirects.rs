@@ -0,0 +1,96 @@
+use crate::book::Chapter;
+use crate::frontmatter::{read_head, FrontMatter};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+/// Redirects as `(old url, new url)`
+pub type Redirects = Vec<(String, String)>;
+
+/// The redirects of the markdown files of `book` from the urls in their `redirect_from`
+/// front matter key to their `permalink`, or else to the url of the page. The first
+/// file claiming an old url wins.
+pub fn collect(book: &Chapter, root: &Path, max_bytes: Option<u64>) -> Redirects {
+    let mut redirects: Redirects = vec![];
+    for file in book.all_files().into_iter().filter(|f| f.to_lowercase().ends_with(".md")) {
+        let front_matter = read_head(&root.join(file), max_bytes).ok().and_then(|c| FrontMatter::parse(&c));
+        let front_matter = match front_matter {
+            Some(front_matter) => front_matter,
+            None => continue,
+        };
+        let target = match front_matter.get_str("permalink").filter(|p| !p.is_empty()) {
+            Some(permalink) => absolute(permalink),
+            None => url(file),
+        };
+        for from in front_matter.get_list("redirect_from").unwrap_or_default() {
+            let from = absolute(from.trim());
+            if from != target && !redirects.iter().any(|(f, _)| *f == from) {
+                redirects.push((from, target.clone()));
+            }
+        }
+    }
+    redirects
+}
+
+/// Url of the page of the markdown `file` as mdBook builds it, `guide/README.md` is
+/// `/guide/index.html`
+pub fn url(file: &str) -> String {
+    let page = match file.rsplit_once('/') {
+        Some((dir, name)) if name.eq_ignore_ascii_case("README.md") => format!("{}/index.md", dir),
+        None if file.eq_ignore_ascii_case("README.md") => "index.md".to_string(),
+        _ => file.to_string(),
+    };
+    format!("/{}.html", &page[..page.len() - 3])
+}
+
+fn absolute(url: &str) -> String {
+    match url.starts_with('/') || url.contains("://") {
+        true => url.to_string(),
+        false => format!("/{}", url),
+    }
+}
+
+/// The redirect map for the `file` it is written to: the `_redirects` of Netlify and
+/// Cloudflare Pages, or else a JSON object
+pub fn render(redirects: &Redirects, file: &str) -> String {
+    if Path::new(file).file_name() == Some("_redirects".as_ref()) {
+        return redirects.iter().map(|(from, to)| format!("{} {} 301\n", from, to)).collect();
+    }
+    let map = redirects
+        .iter()
+        .map(|(from, to)| (from.clone(), Value::String(to.clone())))
+        .collect::<Map<_, _>>();
+    format!("{}\n", serde_json::to_string_pretty(&map).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::fixture;
+
+    #[test]
+    fn redirects_test() {
+        let dir = fixture(&[("guide/README.md", "---\nredirect_from: /docs/\n---\n")]);
+        std::fs::write(
+            dir.join("guide/setup.md"),
+            "---\nredirect_from:\n  - /install.html\n  - docs/setup.html\n---\n# Setup\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("about.md"), "---\nredirect_from: [/docs/]\npermalink: /about/\n---\n").unwrap();
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &["about.md".to_string(), "guide/README.md".to_string(), "guide/setup.md".to_string()],
+        );
+
+        let redirects = collect(&book, &dir, None);
+
+        assert_eq!(
+            "/docs/ /about/ 301\n/install.html /guide/setup.html 301\n/docs/setup.html /guide/setup.html 301\n",
+            render(&redirects, "_redirects")
+        );
+        assert_eq!(
+            "{\n  \"/docs/\": \"/about/\",\n  \"/docs/setup.html\": \"/guide/setup.html\",\n  \
+             \"/install.html\": \"/guide/setup.html\"\n}\n",
+            render(&redirects, "redirects.json")
+        );
+    }
+}
@@ -0,0 +1,1746 @@
+use crate::book::{make_title_case, Chapter, Item};
+use crate::i18n::Labels;
+use crate::links;
+use crate::meta::{self, ChapterMetas};
+use crate::slug::Slugger;
+use crate::suggest::did_you_mean;
+use crate::title::Titles;
+use crate::SummaryError;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+/// Everything besides the chapter tree a renderer needs
+pub struct RenderContext<'a> {
+    /// Chapters which should come first, in the given order
+    pub sort: &'a Option<Vec<String>>,
+    /// Resolved titles keyed by the entry path
+    pub titles: &'a Titles,
+    /// File listed first as introduction, not part of the chapter tree
+    pub introduction: Option<&'a str>,
+    /// Generated glossary listed after the chapters, not part of the chapter tree
+    pub glossary: Option<&'a str>,
+    /// Generated A–Z index listed after the glossary, not part of the chapter tree
+    pub index: Option<&'a str>,
+    /// Names of generated entries
+    pub labels: &'a Labels,
+    /// Identifiers for formats with ids or anchors
+    pub slugger: &'a Slugger,
+    /// File names of chapter READMEs, the first one found wins
+    pub readme_names: &'a [String],
+    /// Metadata of chapters keyed by their path
+    pub meta: &'a ChapterMetas,
+    /// Titles longer than this many chars are cut off with an ellipsis
+    pub max_title_len: Option<usize>,
+    /// Last updated dates keyed by the entry path, appended to the entries by
+    /// formats which allow trailing text
+    pub updated: &'a HashMap<String, String>,
+    /// How the list formats render chapters without README, their own way if unset
+    pub no_readme: Option<NoReadmeStyle>,
+    /// Pages the list formats render as drafts, `[Title]()`
+    pub drafts: &'a HashSet<String>,
+}
+
+/// Rendering of a chapter without README (or with a draft README) in the list formats
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoReadmeStyle {
+    /// `[Chapter]()`, a draft chapter of mdBook
+    Draft,
+    /// `Chapter` as plain text
+    Text,
+    /// `[Chapter](chapter/first.md)`, linking the first page of the chapter in the
+    /// summary, which is then not listed again. Without pages it is the format's own way.
+    LinkFirstChild,
+    /// No entry for the chapter, its pages and sub chapters take its place
+    Skip,
+}
+
+impl FromStr for NoReadmeStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "draft" => Ok(NoReadmeStyle::Draft),
+            "text" => Ok(NoReadmeStyle::Text),
+            "link-first-child" => Ok(NoReadmeStyle::LinkFirstChild),
+            "skip" => Ok(NoReadmeStyle::Skip),
+            _ => Err(format!(
+                "Invalid no README style {}, expected one of: draft, text, link-first-child, skip",
+                s
+            )),
+        }
+    }
+}
+
+impl<'a> RenderContext<'a> {
+    /// Title of the entry at `path`, falling back to the title cased `name`
+    pub fn title(&self, path: &str, name: &str) -> String {
+        let title = match self.titles.get(path) {
+            Some(title) => title.clone(),
+            None => make_title_case(name),
+        };
+        self.truncate(title)
+    }
+
+    /// Title of the chapter at `path`, from its metadata if given and with its icon
+    pub fn chapter_title(&self, path: &str, name: &str) -> String {
+        let meta = self.meta.get(path);
+        let title = match meta.and_then(|m| m.title.clone()) {
+            Some(title) => self.truncate(title),
+            None => self.title(path, name),
+        };
+        match meta.and_then(|m| m.icon.as_ref()) {
+            Some(icon) => format!("{} {}", icon, title),
+            None => title,
+        }
+    }
+
+    // Cut `title` off after `max_title_len` chars, the ellipsis included
+    fn truncate(&self, title: String) -> String {
+        match self.max_title_len {
+            Some(max) if title.chars().count() > max => {
+                let kept = title.chars().take(max.saturating_sub(1)).collect::<String>();
+                format!("{}…", kept.trim_end())
+            }
+            _ => title,
+        }
+    }
+
+    /// README of the chapter at `path`, unless it is a draft
+    pub fn chapter_readme<'c>(&self, chapter: &'c Chapter, path: &str) -> Option<&'c String> {
+        match self.meta.get(path) {
+            Some(meta) if meta.draft => None,
+            _ => chapter.readme(self.readme_names),
+        }
+    }
+
+    /// Sub chapters of the chapter at `path` in the order of the summary: the
+    /// prefered chapters (only at the root) first, then by weight
+    pub fn chapters<'c>(&self, chapter: &'c Chapter, path: &str) -> Vec<&'c Chapter> {
+        let mut chapters = chapter.chapter.iter().collect::<Vec<_>>();
+        meta::sort_by_weight(&mut chapters, path, self.meta);
+        if let (true, Some(names)) = (path.is_empty(), self.sort) {
+            chapters.sort_by_key(|c| {
+                names
+                    .iter()
+                    .position(|n| n.to_lowercase() == c.name.to_lowercase())
+                    .unwrap_or(usize::MAX)
+            });
+        }
+        chapters
+    }
+}
+
+/// Output target of the summary.
+///
+/// Implementations are registered by name with [`register`] and can then be
+/// selected with `--format <name>` or `"<name>".parse::<Format>()`.
+pub trait Renderer: Send + Sync {
+    /// Name the renderer is registered with
+    fn name(&self) -> &str;
+
+    /// Render the whole summary file
+    fn render(&self, book: &Chapter, context: &RenderContext) -> String;
+
+    /// Book config files (relative to the notes dir) to read options from
+    fn config_files(&self) -> &[&str] {
+        &[]
+    }
+
+    /// File the summary is written to unless another output file is given
+    fn default_output(&self) -> &str {
+        "SUMMARY.md"
+    }
+
+    /// Whether non markdown files (PDFs, HTML pages, ...) can be linked
+    fn links_assets(&self) -> bool {
+        true
+    }
+
+    /// Whether the summary is a markdown list, whose blank lines can be adjusted
+    fn markdown_list(&self) -> bool {
+        false
+    }
+}
+
+/// Style of the link targets of the markdown list formats
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LinkStyle {
+    /// `chapter/file.md`, relative to the summary in the notes dir
+    Relative,
+    /// `/chapter/file.md`, from the root of the notes dir
+    Absolute,
+}
+
+impl FromStr for LinkStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "relative" => Ok(LinkStyle::Relative),
+            "absolute" => Ok(LinkStyle::Absolute),
+            _ => Err(format!("Invalid link style {}, expected one of: relative, absolute", s)),
+        }
+    }
+}
+
+/// The markdown `summary` with its links to files of the notes dir in `style`
+pub fn style_links(summary: &str, style: LinkStyle) -> String {
+    match style {
+        LinkStyle::Relative => summary.to_string(),
+        LinkStyle::Absolute => links::rewrite_targets(summary, |target| {
+            let external = target.contains("://") || target.starts_with("mailto:");
+            let linked = !target.is_empty() && !target.starts_with(['#', '/']) && !external;
+            linked.then(|| format!("/{}", target))
+        }),
+    }
+}
+
+/// The markdown `summary` with `suffix` in place of the `.md` extension of its links,
+/// e.g. `.html` or `/` for pretty URLs
+pub fn replace_extension(summary: &str, suffix: &str) -> String {
+    links::rewrite_targets(summary, |target| {
+        let stem = target.len().checked_sub(3).filter(|&i| target[i..].eq_ignore_ascii_case(".md"))?;
+        Some(format!("{}{}", &target[..stem], suffix))
+    })
+}
+
+/// A registered renderer, used to select the output of the summary
+#[derive(Clone)]
+pub struct Format(Arc<dyn Renderer>);
+
+impl Format {
+    pub fn new<R: Renderer + 'static>(renderer: R) -> Self {
+        Format(Arc::new(renderer))
+    }
+
+    /// mdBook `SUMMARY.md` with `-` as list char
+    pub fn mdbook() -> Self {
+        Format::new(MdBook('-'))
+    }
+
+    /// GitBook `SUMMARY.md` with `*` as list char
+    pub fn gitbook() -> Self {
+        Format::new(GitBook('*'))
+    }
+
+    pub fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    pub fn renderer(&self) -> &dyn Renderer {
+        self.0.as_ref()
+    }
+}
+
+impl fmt::Debug for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Format({})", self.name())
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl PartialEq for Format {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name()
+    }
+}
+
+impl FromStr for Format {
+    type Err = SummaryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let registry = registry().read().unwrap();
+        match registry.get(s) {
+            Some(renderer) => Ok(Format(renderer.clone())),
+            None => {
+                let mut names = registry.keys().cloned().collect::<Vec<_>>();
+                names.sort();
+                // suggest the name of the renderer, not of one of its aliases
+                let suggestion = did_you_mean(s, names.iter().map(|n| n.as_str()))
+                    .map(|n| registry[n].name().to_string());
+                Err(SummaryError::UnknownFormat {
+                    name: s.to_string(),
+                    suggestion,
+                    valid: names,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Format {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Format {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+type Registry = RwLock<HashMap<String, Arc<dyn Renderer>>>;
+
+fn registry() -> &'static Registry {
+    static REGISTRY: std::sync::OnceLock<Registry> = std::sync::OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let mut renderers: HashMap<String, Arc<dyn Renderer>> = HashMap::new();
+        let builtin: Vec<(&str, Arc<dyn Renderer>)> = vec![
+            ("md", Arc::new(MdBook('-'))),
+            ("mdbook", Arc::new(MdBook('-'))),
+            ("git", Arc::new(GitBook('*'))),
+            ("gitbook", Arc::new(GitBook('*'))),
+            ("docsify", Arc::new(Docsify)),
+            ("json", Arc::new(Json)),
+            ("quartz", Arc::new(Quartz)),
+            ("filelist", Arc::new(FileList)),
+            ("epub-nav", Arc::new(EpubNav("xhtml".to_string()))),
+            ("ncx", Arc::new(Ncx("xhtml".to_string()))),
+        ];
+        for (name, renderer) in builtin {
+            renderers.insert(name.to_string(), renderer);
+        }
+        RwLock::new(renderers)
+    })
+}
+
+/// Register a renderer under its name, replacing a registered one with the same name
+pub fn register<R: Renderer + 'static>(renderer: R) {
+    registry()
+        .write()
+        .unwrap()
+        .insert(renderer.name().to_string(), Arc::new(renderer));
+}
+
+/// Format of the book tool whose files are found in `dir`: md for a `book.toml`, git for
+/// a `book.json` or `book.js`, docsify for an `index.html` loading docsify and quartz for
+/// a `quartz.config.ts`
+pub fn detect(dir: &Path) -> Option<Format> {
+    for format in [Format::mdbook(), Format::gitbook()] {
+        if format.renderer().config_files().iter().any(|f| dir.join(f).is_file()) {
+            return Some(format);
+        }
+    }
+    let index = std::fs::read_to_string(dir.join("index.html")).unwrap_or_default();
+    if index.contains("docsify") {
+        return Some(Format::new(Docsify));
+    }
+    if dir.join("quartz.config.ts").is_file() {
+        return Some(Format::new(Quartz));
+    }
+    None
+}
+
+/// mdbook format:
+///
+/// ```text
+/// # Summary
+///
+/// - [mdBook](README.md)
+/// - [Command Line Tool](cli/README.md)
+///     - [init](cli/init.md)
+///     - [build](cli/build.md)
+/// ```
+pub struct MdBook(pub char);
+
+impl Renderer for MdBook {
+    fn name(&self) -> &str {
+        "md"
+    }
+
+    fn render(&self, book: &Chapter, context: &RenderContext) -> String {
+        // mdbook needs a link for every entry, so chapters without README are drafts
+        render_list(book, context, self.0, 4, |title| format!("[{}](#)", title), false, true)
+    }
+
+    fn config_files(&self) -> &[&str] {
+        &["book.toml"]
+    }
+
+    // mdbook renders every entry as a markdown chapter
+    fn links_assets(&self) -> bool {
+        false
+    }
+
+    fn markdown_list(&self) -> bool {
+        true
+    }
+}
+
+/// gitbook format:
+///
+/// ```text
+/// # Summary
+///
+/// * [First page's title](page1/README.md)
+///     * [Some child page](page1/page1-1.md)
+///     * [Some other child page](part1/page1-2.md)
+/// * [Second page's title](page2/README.md)
+/// ```
+pub struct GitBook(pub char);
+
+impl Renderer for GitBook {
+    fn name(&self) -> &str {
+        "git"
+    }
+
+    fn render(&self, book: &Chapter, context: &RenderContext) -> String {
+        render_list(book, context, self.0, 4, |title| title.to_string(), true, true)
+    }
+
+    fn config_files(&self) -> &[&str] {
+        &["book.json", "book.js"]
+    }
+
+    fn markdown_list(&self) -> bool {
+        true
+    }
+}
+
+/// docsify `_sidebar.md` format, nested lists with two spaces
+pub struct Docsify;
+
+impl Renderer for Docsify {
+    fn name(&self) -> &str {
+        "docsify"
+    }
+
+    fn default_output(&self) -> &str {
+        "_sidebar.md"
+    }
+
+    fn render(&self, book: &Chapter, context: &RenderContext) -> String {
+        let summary = render_list(book, context, '-', 2, |title| title.to_string(), true, false);
+        // a heading would be an item of the sidebar
+        summary.split_once("\n\n").map(|(_, list)| list.to_string()).unwrap_or_default()
+    }
+
+    fn markdown_list(&self) -> bool {
+        true
+    }
+}
+
+/// A nested list format described in the config, for wikis with slightly unusual syntaxes:
+///
+/// ```toml
+/// [formats.wiki]
+/// list = "*"
+/// indent = 2
+/// readme = "page"
+/// heading = false
+/// strip-extension = true
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListFormat {
+    pub name: String,
+    pub list_char: char,
+    pub indent_width: usize,
+    /// List the README of a chapter as its first page instead of linking the chapter to it
+    pub readme_as_page: bool,
+    /// Start with the `# Title` heading
+    pub heading: bool,
+    /// Link `guide/setup` instead of `guide/setup.md`
+    pub strip_extension: bool,
+}
+
+impl ListFormat {
+    /// The format `name` of a `[formats.<name>]` table, unset keys as in the md format
+    pub fn from_toml(name: &str, table: &toml::Value) -> Result<Self, String> {
+        let mut format = ListFormat {
+            name: name.to_string(),
+            list_char: '-',
+            indent_width: 4,
+            readme_as_page: false,
+            heading: true,
+            strip_extension: false,
+        };
+        let table = table.as_table().ok_or(format!("Format {} must be a table", name))?;
+        for (key, value) in table {
+            let invalid = || format!("Invalid `{}` of format {}: {}", key, name, value);
+            let flag = || value.as_bool().ok_or_else(invalid);
+            match key.as_str() {
+                "list" => {
+                    format.list_char = match value.as_str().map(|l| l.chars().collect::<Vec<_>>()) {
+                        Some(chars) if chars.len() == 1 => chars[0],
+                        _ => return Err(invalid()),
+                    }
+                }
+                "indent" => {
+                    let indent = value.as_integer().filter(|i| (0..=16).contains(i));
+                    format.indent_width = indent.ok_or_else(invalid)? as usize
+                }
+                "readme" => {
+                    format.readme_as_page = match value.as_str() {
+                        Some("link") => false,
+                        Some("page") => true,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "heading" => format.heading = flag()?,
+                "strip-extension" => format.strip_extension = flag()?,
+                _ => return Err(format!("Unknown key `{}` of format {}", key, name)),
+            }
+        }
+        Ok(format)
+    }
+}
+
+impl Renderer for ListFormat {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn markdown_list(&self) -> bool {
+        true
+    }
+
+    fn render(&self, book: &Chapter, context: &RenderContext) -> String {
+        // without README names the chapters are plain titles and their READMEs pages
+        let context = RenderContext {
+            readme_names: if self.readme_as_page { &[] } else { context.readme_names },
+            ..*context
+        };
+        let (list, indent) = (self.list_char, self.indent_width);
+        let mut summary = render_list(book, &context, list, indent, |t| t.to_string(), true, true);
+        if !self.heading {
+            summary = summary.split_once("\n\n").map(|(_, list)| list.to_string()).unwrap_or_default();
+        }
+        if self.strip_extension {
+            let strip = |target: &str| target.strip_suffix(".md").map(str::to_string);
+            summary = links::rewrite_targets(&summary, strip);
+        }
+        summary
+    }
+}
+
+/// Version of the JSON output, bumped on incompatible changes of [`JSON_SCHEMA`]
+pub const JSON_SCHEMA_VERSION: u32 = 2;
+
+/// JSON Schema of the output of the `json` format
+pub const JSON_SCHEMA: &str = include_str!("../data/summary.schema.json");
+
+/// The chapter tree with resolved titles as JSON, for other tools to consume
+pub struct Json;
+
+impl Renderer for Json {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn default_output(&self) -> &str {
+        "summary.json"
+    }
+
+    fn render(&self, book: &Chapter, context: &RenderContext) -> String {
+        let chapters = context
+            .chapters(book, "")
+            .into_iter()
+            .map(|c| json_chapter(c, context, &c.name))
+            .collect::<Vec<_>>();
+        let value = serde_json::json!({
+            "schema_version": JSON_SCHEMA_VERSION,
+            "title": book.name,
+            "introduction": context.introduction,
+            "glossary": context.glossary,
+            "index": context.index,
+            "pages": json_pages(book, context),
+            "chapters": chapters,
+        });
+        format!("{}\n", serde_json::to_string_pretty(&value).unwrap())
+    }
+}
+
+fn json_chapter(chapter: &Chapter, context: &RenderContext, path: &str) -> serde_json::Value {
+    let chapters = context
+        .chapters(chapter, path)
+        .into_iter()
+        .map(|c| json_chapter(c, context, &join(path, &c.name)))
+        .collect::<Vec<_>>();
+
+    let meta = context.meta.get(path);
+    let title = match meta.and_then(|m| m.title.clone()) {
+        Some(title) => title,
+        None => context.title(path, &chapter.name),
+    };
+    serde_json::json!({
+        "id": context.slugger.slug(&title),
+        "uid": uid(path),
+        "title": title,
+        "icon": meta.and_then(|m| m.icon.clone()),
+        "owners": meta.map(|m| m.owners.clone()).unwrap_or_default(),
+        "path": context.chapter_readme(chapter, path),
+        "pages": json_pages(chapter, context),
+        "chapters": chapters,
+    })
+}
+
+fn json_pages(chapter: &Chapter, context: &RenderContext) -> Vec<serde_json::Value> {
+    chapter
+        .pages(context.readme_names)
+        .map(|f| {
+            let title = page_title(context, f);
+            let id = context.slugger.slug(&title);
+            serde_json::json!({ "id": id, "uid": uid(f), "title": title, "path": f })
+        })
+        .collect()
+}
+
+/// Identifier of the entry at `path` which stays the same across runs and versions,
+/// unlike its title slug: the FNV-1a hash of the path as 16 hex digits
+pub fn uid(path: &str) -> String {
+    let hash = path.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// Tree of nested nodes as used by the explorers of digital gardens (Quartz,
+/// Obsidian Publish), paths are slugs without the `.md` extension:
+///
+/// ```text
+/// { "name": "Summary", "path": "", "children": [
+///     { "name": "Guide", "path": "guide", "children": [
+///         { "name": "Setup", "path": "guide/setup", "children": [] } ] } ] }
+/// ```
+pub struct Quartz;
+
+impl Renderer for Quartz {
+    fn name(&self) -> &str {
+        "quartz"
+    }
+
+    fn default_output(&self) -> &str {
+        "explorer.json"
+    }
+
+    fn render(&self, book: &Chapter, context: &RenderContext) -> String {
+        let mut children = vec![];
+        if let Some(introduction) = context.introduction {
+            children.push(quartz_file(&context.labels.introduction, introduction));
+        }
+        children.extend(quartz_children(book, context, ""));
+        if let Some(index) = context.index {
+            children.push(quartz_file(&context.labels.index, index));
+        }
+        let value = serde_json::json!({ "name": book.name, "path": "", "children": children });
+        format!("{}\n", serde_json::to_string_pretty(&value).unwrap())
+    }
+}
+
+fn quartz_children(chapter: &Chapter, context: &RenderContext, path: &str) -> Vec<serde_json::Value> {
+    let mut children = chapter
+        .pages(context.readme_names)
+        .map(|f| quartz_file(&page_title(context, f), f))
+        .collect::<Vec<_>>();
+    for c in context.chapters(chapter, path) {
+        let sub = join(path, &c.name);
+        children.push(serde_json::json!({
+            "name": context.chapter_title(&sub, &c.name),
+            "path": sub,
+            "children": quartz_children(c, context, &sub),
+        }));
+    }
+    children
+}
+
+fn quartz_file(name: &str, file: &str) -> serde_json::Value {
+    let slug = file.strip_suffix(".md").unwrap_or(file);
+    serde_json::json!({ "name": name, "path": slug, "children": [] })
+}
+
+/// The files of the book in reading order, one per line, for PDF pipelines like
+/// `pandoc $(book-summary --emit filelist) -o book.pdf`
+pub struct FileList;
+
+impl Renderer for FileList {
+    fn name(&self) -> &str {
+        "filelist"
+    }
+
+    fn default_output(&self) -> &str {
+        "filelist.txt"
+    }
+
+    fn render(&self, book: &Chapter, context: &RenderContext) -> String {
+        let mut files = context.introduction.into_iter().collect::<Vec<_>>();
+        file_list(book, context, "", &mut files);
+        files.extend(context.glossary);
+        files.extend(context.index);
+        files.iter().map(|f| format!("{}\n", f)).collect()
+    }
+}
+
+fn file_list<'a>(chapter: &'a Chapter, context: &RenderContext, path: &str, files: &mut Vec<&'a str>) {
+    if !path.is_empty() {
+        files.extend(context.chapter_readme(chapter, path).map(|f| f.as_str()));
+    }
+    files.extend(chapter.pages(context.readme_names).map(|f| f.as_str()));
+    for c in context.chapters(chapter, path) {
+        file_list(c, context, &join(path, &c.name), files);
+    }
+}
+
+/// EPUB 3 navigation document (`nav.xhtml`), the markdown files linked as their
+/// EPUB content documents with the given extension (`xhtml`)
+pub struct EpubNav(pub String);
+
+impl Renderer for EpubNav {
+    fn name(&self) -> &str {
+        "epub-nav"
+    }
+
+    fn default_output(&self) -> &str {
+        "nav.xhtml"
+    }
+
+    fn render(&self, book: &Chapter, context: &RenderContext) -> String {
+        let title = xml_escape(&book.name);
+        let mut nav = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE html>\n");
+        nav += "<html xmlns=\"http://www.w3.org/1999/xhtml\" \
+                xmlns:epub=\"http://www.idpf.org/2007/ops\">\n";
+        nav += &format!("<head>\n  <title>{}</title>\n</head>\n<body>\n", title);
+        nav += &format!("  <nav epub:type=\"toc\" id=\"toc\">\n    <h1>{}</h1>\n", title);
+        nav += &self.list(&nav_items(book, context), 2);
+        nav += "  </nav>\n</body>\n</html>\n";
+        nav
+    }
+
+    fn links_assets(&self) -> bool {
+        false
+    }
+}
+
+impl EpubNav {
+    fn list(&self, items: &[NavItem], depth: usize) -> String {
+        let indent = "  ".repeat(depth);
+        let mut list = format!("{}<ol>\n", indent);
+        for item in items {
+            let title = xml_escape(&item.title);
+            let label = match &item.file {
+                Some(file) => format!("<a href=\"{}\">{}</a>", content_href(file, &self.0), title),
+                None => format!("<span>{}</span>", title),
+            };
+            match item.children.is_empty() {
+                true => list += &format!("{}  <li>{}</li>\n", indent, label),
+                false => {
+                    list += &format!("{}  <li>{}\n", indent, label);
+                    list += &self.list(&item.children, depth + 2);
+                    list += &format!("{}  </li>\n", indent);
+                }
+            }
+        }
+        list + &format!("{}</ol>\n", indent)
+    }
+}
+
+/// EPUB 2 navigation control file (`toc.ncx`) for older readers, the markdown files
+/// linked as their EPUB content documents with the given extension (`xhtml`).
+/// Chapters without README point to their first file.
+pub struct Ncx(pub String);
+
+impl Renderer for Ncx {
+    fn name(&self) -> &str {
+        "ncx"
+    }
+
+    fn default_output(&self) -> &str {
+        "toc.ncx"
+    }
+
+    fn render(&self, book: &Chapter, context: &RenderContext) -> String {
+        let items = nav_items(book, context);
+        let depth = items.iter().map(NavItem::depth).max().unwrap_or(1);
+        let mut ncx = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        ncx += "<ncx xmlns=\"http://www.daisy.org/z3986/2005/ncx/\" version=\"2005-1\">\n  <head>\n";
+        ncx += &format!("    <meta name=\"dtb:uid\" content=\"{}\"/>\n", uid(&book.name));
+        ncx += &format!("    <meta name=\"dtb:depth\" content=\"{}\"/>\n", depth);
+        ncx += "    <meta name=\"dtb:totalPageCount\" content=\"0\"/>\n";
+        ncx += "    <meta name=\"dtb:maxPageNumber\" content=\"0\"/>\n  </head>\n";
+        ncx += &format!("  <docTitle><text>{}</text></docTitle>\n  <navMap>\n", xml_escape(&book.name));
+        let mut order = 0;
+        for item in &items {
+            ncx += &self.nav_point(item, 2, &mut order);
+        }
+        ncx += "  </navMap>\n</ncx>\n";
+        ncx
+    }
+
+    fn links_assets(&self) -> bool {
+        false
+    }
+}
+
+impl Ncx {
+    fn nav_point(&self, item: &NavItem, depth: usize, order: &mut usize) -> String {
+        let file = match item.first_file() {
+            Some(file) => file,
+            None => return String::new(),
+        };
+        *order += 1;
+        let indent = "  ".repeat(depth);
+        let mut point = format!("{}<navPoint id=\"navpoint-{1}\" playOrder=\"{1}\">\n", indent, order);
+        point += &format!("{}  <navLabel><text>{}</text></navLabel>\n", indent, xml_escape(&item.title));
+        point += &format!("{}  <content src=\"{}\"/>\n", indent, content_href(file, &self.0));
+        for child in &item.children {
+            point += &self.nav_point(child, depth + 1, order);
+        }
+        point + &format!("{}</navPoint>\n", indent)
+    }
+}
+
+// An entry of the navigation of an EPUB, `file` is `None` for chapters without README
+struct NavItem<'a> {
+    title: String,
+    file: Option<&'a str>,
+    children: Vec<NavItem<'a>>,
+}
+
+impl NavItem<'_> {
+    fn depth(&self) -> usize {
+        1 + self.children.iter().map(NavItem::depth).max().unwrap_or(0)
+    }
+
+    fn first_file(&self) -> Option<&str> {
+        self.file.or_else(|| self.children.iter().find_map(NavItem::first_file))
+    }
+}
+
+fn nav_items<'a>(book: &'a Chapter, context: &'a RenderContext) -> Vec<NavItem<'a>> {
+    let mut items = vec![];
+    if let Some(introduction) = context.introduction {
+        let title = context.labels.introduction.clone();
+        items.push(NavItem { title, file: Some(introduction), children: vec![] });
+    }
+    items.extend(nav_children(book, context, ""));
+    if let Some(glossary) = context.glossary {
+        let title = context.labels.glossary.clone();
+        items.push(NavItem { title, file: Some(glossary), children: vec![] });
+    }
+    if let Some(index) = context.index {
+        let title = context.labels.index.clone();
+        items.push(NavItem { title, file: Some(index), children: vec![] });
+    }
+    items
+}
+
+fn nav_children<'a>(chapter: &'a Chapter, context: &RenderContext, path: &str) -> Vec<NavItem<'a>> {
+    let mut items = chapter
+        .pages(context.readme_names)
+        .map(|f| NavItem { title: page_title(context, f), file: Some(f.as_str()), children: vec![] })
+        .collect::<Vec<_>>();
+    for c in context.chapters(chapter, path) {
+        let sub = join(path, &c.name);
+        items.push(NavItem {
+            title: context.chapter_title(&sub, &c.name),
+            file: context.chapter_readme(c, &sub).map(|f| f.as_str()),
+            children: nav_children(c, context, &sub),
+        });
+    }
+    items
+}
+
+// Link to the EPUB content document of the markdown `file`
+fn content_href(file: &str, extension: &str) -> String {
+    let href = match file.len().checked_sub(3).filter(|&i| file[i..].eq_ignore_ascii_case(".md")) {
+        Some(i) => format!("{}.{}", &file[..i], extension),
+        None => file.to_string(),
+    };
+    xml_escape(&target(&href))
+}
+
+/// Link target of `file`, with `%` escaped so the target decodes back to the name, `#`
+/// so it isn't taken for an anchor and spaces and parentheses so they don't end the link
+pub(crate) fn target(file: &str) -> String {
+    // `%` first, not to escape the escapes of the others
+    file.replace('%', "%25")
+        .replace('#', "%23")
+        .replace(' ', "%20")
+        .replace('(', "%28")
+        .replace(')', "%29")
+}
+
+pub(crate) fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Markdown list of `book`, chapters without README are rendered with `draft` unless the
+// context has a style for them. The introduction is either the first list item or an
+// unindented prefix chapter (mdbook), the glossary and the index the last list items or
+// suffix chapters.
+// With `dated` the last updated dates follow the links.
+fn render_list(
+    book: &Chapter,
+    context: &RenderContext,
+    list_char: char,
+    indent_width: usize,
+    draft: fn(&str) -> String,
+    intro_as_item: bool,
+    dated: bool,
+) -> String {
+    let list = List {
+        context,
+        list_char,
+        indent_width,
+        draft,
+        dated,
+    };
+    let mut summary = format!("# {}\n\n", book.name);
+    if let Some(introduction) = context.introduction {
+        let introduction = target(introduction);
+        match intro_as_item {
+            true => summary += &format!("{} [{}]({})\n", list_char, context.labels.introduction, introduction),
+            false => summary += &format!("[{}]({})\n\n", context.labels.introduction, introduction),
+        }
+    }
+    summary += &list.children(book, 0, "", None);
+    if let Some(glossary) = context.glossary {
+        match intro_as_item {
+            true => summary += &format!("{} [{}]({})\n", list_char, context.labels.glossary, glossary),
+            false => summary += &format!("\n[{}]({})\n", context.labels.glossary, glossary),
+        }
+    }
+    if let Some(index) = context.index {
+        match intro_as_item {
+            true => summary += &format!("{} [{}]({})\n", list_char, context.labels.index, index),
+            false if context.glossary.is_some() => {
+                summary += &format!("[{}]({})\n", context.labels.index, index)
+            }
+            false => summary += &format!("\n[{}]({})\n", context.labels.index, index),
+        }
+    }
+    summary
+}
+
+// Entry of a chapter without page in the list formats, `draft` being the format's own way
+fn pageless(title: &str, style: Option<NoReadmeStyle>, draft: fn(&str) -> String) -> String {
+    match style {
+        Some(NoReadmeStyle::Draft) => format!("[{}]()", title),
+        Some(NoReadmeStyle::Text) => title.to_string(),
+        _ => draft(title),
+    }
+}
+
+struct List<'a> {
+    context: &'a RenderContext<'a>,
+    list_char: char,
+    indent_width: usize,
+    draft: fn(&str) -> String,
+    dated: bool,
+}
+
+impl<'a> List<'a> {
+    fn link(&self, title: &str, file: &str) -> String {
+        match self.context.updated.get(file) {
+            Some(date) if self.dated => format!("[{}]({}) ({})", title, target(file), date),
+            _ => format!("[{}]({})", title, target(file)),
+        }
+    }
+
+    fn chapter(&self, chapter: &Chapter, indent: usize, root: &str) -> String {
+        let path = join(root, &chapter.name);
+        let title = self.context.chapter_title(&path, &chapter.name);
+        let mut linked = None;
+        let entry = match (self.context.chapter_readme(chapter, &path), self.context.no_readme) {
+            (Some(readme), _) => self.link(&title, readme),
+            (None, Some(NoReadmeStyle::LinkFirstChild)) => match chapter.pages(self.context.readme_names).next() {
+                Some(first) => {
+                    linked = Some(first.as_str());
+                    self.link(&title, first)
+                }
+                None => (self.draft)(&title),
+            },
+            (None, Some(NoReadmeStyle::Skip)) => return self.children(chapter, indent, &path, None),
+            (None, style) => pageless(&title, style, self.draft),
+        };
+
+        let mut summary = format!(
+            "{}{} {}\n",
+            " ".repeat(self.indent_width * indent),
+            self.list_char,
+            entry
+        );
+        summary += &self.children(chapter, indent + 1, &path, linked);
+        summary
+    }
+
+    // The pages besides `linked` and the sub chapters of the chapter at `path`, mixed
+    // like the kept order of the chapter if it has one
+    fn children(&self, chapter: &Chapter, indent: usize, path: &str, linked: Option<&str>) -> String {
+        let pages = chapter
+            .pages(self.context.readme_names)
+            .filter(|f| Some(f.as_str()) != linked)
+            .map(|f| (Item::File(f.clone()), self.page(f, indent)));
+        let chapters = self
+            .context
+            .chapters(chapter, path)
+            .into_iter()
+            .map(|c| (Item::Chapter(c.name.clone()), self.chapter(c, indent, path)));
+        let mut entries = pages.chain(chapters).collect::<Vec<_>>();
+        if !chapter.order.is_empty() {
+            entries.sort_by_key(|(item, _)| chapter.position(item));
+        }
+        entries.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    fn page(&self, file: &str, indent: usize) -> String {
+        let title = page_title(self.context, file);
+        let entry = match self.context.drafts.contains(file) {
+            true => format!("[{}]()", title),
+            false => self.link(&title, file),
+        };
+        format!("{}{} {}\n", " ".repeat(self.indent_width * indent), self.list_char, entry)
+    }
+}
+
+/// Number of linked entries of a rendered summary, `[Title](path)` or `"path": "..."` (json)
+pub fn count_entries(summary: &str) -> usize {
+    summary
+        .lines()
+        .filter(|l| {
+            let l = l.trim();
+            (l.contains("](") && !l.contains("](#)") && !l.contains("]()")) || (l.starts_with("\"path\": \"") && l.len() > 9)
+        })
+        .count()
+}
+
+/// Linked entries of a rendered markdown summary as `(title, path)`, with paths
+/// relative to the notes dir
+pub fn entries(summary: &str) -> Vec<(String, String)> {
+    let mut entries = vec![];
+    for line in summary.lines() {
+        let line = line.trim();
+        let line = line.trim_start_matches(['-', '*', '+']).trim_start();
+        let (title, rest) = match line.strip_prefix('[').and_then(|l| l.split_once("](")) {
+            Some(link) => link,
+            None => continue,
+        };
+        if let Some((path, _)) = rest.split_once(')') {
+            if path != "#" && !path.is_empty() {
+                // absolute links are from the notes dir as well
+                let path = links::percent_decode(path.trim_start_matches('/'));
+                entries.push((title.to_string(), path));
+            }
+        }
+    }
+    entries
+}
+
+/// The section numbers mdBook gives the linked chapters of the `summary`, as
+/// `(path, number)`. Drafts are numbered as well, while prefix and suffix chapters,
+/// part titles and separators are not. The numbers go on across parts.
+pub fn mdbook_numbers(summary: &str) -> Vec<(String, String)> {
+    let mut numbers = vec![];
+    // the number and indent of the items above and at the current item
+    let mut levels: Vec<(usize, usize)> = vec![];
+    for line in summary.lines() {
+        let item = line.trim_start();
+        if !matches!(item.get(..2), Some("- " | "* " | "+ ")) {
+            continue;
+        }
+        let indent = line.len() - item.len();
+        while levels.last().is_some_and(|(_, i)| *i > indent) {
+            levels.pop();
+        }
+        match levels.last_mut() {
+            Some((number, i)) if *i == indent => *number += 1,
+            _ => levels.push((1, indent)),
+        }
+        if let Some((_, path)) = entries(item).pop() {
+            let number = levels.iter().map(|(n, _)| n.to_string()).collect::<Vec<_>>();
+            numbers.push((path, number.join(".")));
+        }
+    }
+    numbers
+}
+
+/// The mdBook `summary` split into parts of at most `max` list items, separated by `---`.
+/// The chapters a part break cuts through are repeated at the start of the next part,
+/// titled `<title> (<continued>)` and rendered like chapters without README in `no_readme`
+/// style, as mdBook drafts unless it is `text`.
+pub fn split_parts(summary: &str, max: usize, continued: &str, no_readme: Option<NoReadmeStyle>) -> String {
+    let mut lines: Vec<String> = vec![];
+    // the chapters above the current item, as `(indent, marker, title)`
+    let mut parents: Vec<(usize, &str, &str)> = vec![];
+    let mut count = 0;
+    for line in summary.lines() {
+        let item = line.trim_start();
+        let indent = line.len() - item.len();
+        let marker = match item.get(..2) {
+            Some(marker @ ("- " | "* " | "+ ")) => marker,
+            _ => {
+                lines.push(line.to_string());
+                continue;
+            }
+        };
+        parents.retain(|(i, ..)| *i < indent);
+        if max > 0 && count >= max {
+            lines.extend(["".to_string(), "---".to_string(), "".to_string()]);
+            for (i, marker, title) in &parents {
+                // the continued chapter has no page of its own, `(#)` would be a missing one
+                let entry = pageless(&format!("{} ({})", title, continued), no_readme, |t| format!("[{}]()", t));
+                lines.push(format!("{}{}{}", " ".repeat(*i), marker, entry));
+            }
+            count = 0;
+        }
+        lines.push(line.to_string());
+        count += 1;
+        let title = item[2..].strip_prefix('[').and_then(|t| t.split_once("](")).map(|(t, _)| t);
+        parents.push((indent, marker, title.unwrap_or("")));
+    }
+    let mut split = lines.join("\n");
+    if summary.ends_with('\n') {
+        split.push('\n');
+    }
+    split
+}
+
+/// The markdown list `summary` without any blank lines when `compact`, then with
+/// `chapter_spacing` blank lines between its top-level items
+pub fn space_lines(summary: &str, compact: bool, chapter_spacing: Option<usize>) -> String {
+    let mut lines: Vec<&str> = vec![];
+    for line in summary.lines() {
+        if line.trim().is_empty() && compact {
+            continue;
+        }
+        let top_level = !line.starts_with(char::is_whitespace) && list_item(line);
+        // the previous item may be nested, but the list goes on
+        let in_list = lines.iter().rev().find(|l| !l.trim().is_empty()).is_some_and(|p| list_item(p.trim_start()));
+        if let (Some(spacing), true, true) = (chapter_spacing, top_level, in_list) {
+            while lines.last().is_some_and(|l| l.trim().is_empty()) {
+                lines.pop();
+            }
+            lines.extend(std::iter::repeat_n("", spacing));
+        }
+        lines.push(line);
+    }
+    let mut spaced = lines.join("\n");
+    if summary.ends_with('\n') {
+        spaced.push('\n');
+    }
+    spaced
+}
+
+// Whether `line` starts with a list char like `-` or `*`, followed by a space
+fn list_item(line: &str) -> bool {
+    let mut chars = line.chars();
+    let marker = chars.next().is_some_and(|c| !c.is_alphanumeric() && !c.is_whitespace() && !['#', '['].contains(&c));
+    marker && chars.next() == Some(' ')
+}
+
+fn page_title(context: &RenderContext, file: &str) -> String {
+    context.title(file, Path::new(file).file_stem().unwrap().to_str().unwrap())
+}
+
+fn join(root: &str, name: &str) -> String {
+    match root {
+        "" => name.to_string(),
+        _ => format!("{}/{}", root, name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::fixture;
+    use crate::book::default_readme_names;
+
+    #[test]
+    fn file_print_test() {
+        let expected = r#"- [WritingIsGood](part1/WritingIsGood.md)
+- [GitbookIsNice](part1/GitbookIsNice.md)
+"#;
+        let chapter = Chapter {
+            name: "part1".to_string(),
+            files: vec![
+                "part1/README.md".to_string(),
+                "part1/WritingIsGood.md".to_string(),
+                "part1/GitbookIsNice.md".to_string(),
+            ],
+            chapter: vec![],
+            order: vec![],
+        };
+        let titles = Titles::new();
+        let context = RenderContext {
+            sort: &None,
+            titles: &titles,
+            introduction: None,
+            glossary: None,
+            index: None,
+            labels: &Labels::default(),
+            slugger: &Slugger::default(),
+            readme_names: &default_readme_names(),
+            meta: &ChapterMetas::new(),
+            max_title_len: None,
+            updated: &HashMap::new(),
+            no_readme: None,
+            drafts: &HashSet::new(),
+        };
+        let list = List {
+            context: &context,
+            list_char: '-',
+            indent_width: 4,
+            draft: |t| t.to_string(),
+            dated: false,
+        };
+        assert_eq!(expected, list.children(&chapter, 0, "", None));
+    }
+
+    #[test]
+    fn docsify_test() {
+        let expected = r#"- [About](about.md)
+- Guide
+  - [Intro](guide/intro.md)
+"#;
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &["about.md".to_string(), "guide/intro.md".to_string()],
+        );
+        let format: Format = "docsify".parse().unwrap();
+        assert_eq!(expected, book.get_summary_file(&format, &None));
+    }
+
+    #[test]
+    fn introduction_test() {
+        let book = Chapter::new("Summary".to_string(), &["about.md".to_string()]);
+        let titles = Titles::new();
+        let context = RenderContext {
+            sort: &None,
+            titles: &titles,
+            introduction: Some("README.md"),
+            glossary: Some("GLOSSARY.md"),
+            index: None,
+            labels: &Labels::default(),
+            slugger: &Slugger::default(),
+            readme_names: &default_readme_names(),
+            meta: &ChapterMetas::new(),
+            max_title_len: None,
+            updated: &HashMap::new(),
+            no_readme: None,
+            drafts: &HashSet::new(),
+        };
+        assert_eq!(
+            "# Summary\n\n[Introduction](README.md)\n\n- [About](about.md)\n\n[Glossary](GLOSSARY.md)\n",
+            MdBook('-').render(&book, &context)
+        );
+        assert_eq!(
+            "# Summary\n\n* [Introduction](README.md)\n* [About](about.md)\n* [Glossary](GLOSSARY.md)\n",
+            GitBook('*').render(&book, &context)
+        );
+    }
+
+    #[test]
+    fn chapter_meta_test() {
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &[
+                "a/README.md".to_string(),
+                "b/README.md".to_string(),
+                "c/file.md".to_string(),
+            ],
+        );
+        let titles = Titles::new();
+        let mut metas = ChapterMetas::new();
+        metas.insert(
+            "b".to_string(),
+            meta::ChapterMeta {
+                title: Some("Start".to_string()),
+                weight: Some(1),
+                icon: Some("🚀".to_string()),
+                draft: false,
+                owners: vec![],
+            },
+        );
+        metas.insert(
+            "a".to_string(),
+            meta::ChapterMeta {
+                draft: true,
+                ..Default::default()
+            },
+        );
+        let context = RenderContext {
+            sort: &Some(vec!["c".to_string()]),
+            titles: &titles,
+            introduction: None,
+            glossary: None,
+            index: None,
+            labels: &Labels::default(),
+            slugger: &Slugger::default(),
+            readme_names: &default_readme_names(),
+            meta: &metas,
+            max_title_len: None,
+            updated: &HashMap::new(),
+            no_readme: None,
+            drafts: &HashSet::new(),
+        };
+        assert_eq!(
+            "- C\n  - [File](c/file.md)\n- [🚀 Start](b/README.md)\n- A\n",
+            Docsify.render(&book, &context)
+        );
+    }
+
+    #[test]
+    fn max_title_len_test() {
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &["a_very_long_chapter/an-exported-note-with-a-long-name.md".to_string()],
+        );
+        let titles = Titles::new();
+        let context = RenderContext {
+            sort: &None,
+            titles: &titles,
+            introduction: None,
+            glossary: None,
+            index: None,
+            labels: &Labels::default(),
+            slugger: &Slugger::default(),
+            readme_names: &default_readme_names(),
+            meta: &ChapterMetas::new(),
+            max_title_len: Some(12),
+            updated: &HashMap::new(),
+            no_readme: None,
+            drafts: &HashSet::new(),
+        };
+        assert_eq!(
+            "- A Very Long…\n  - [An Exported…](a_very_long_chapter/an-exported-note-with-a-long-name.md)\n",
+            Docsify.render(&book, &context)
+        );
+    }
+
+    #[test]
+    fn hash_target_test() {
+        let book = Chapter::new("Summary".to_string(), &["C#/F# notes.md".to_string()]);
+        let titles = Titles::new();
+        let context = RenderContext {
+            sort: &None,
+            titles: &titles,
+            introduction: None,
+            glossary: None,
+            index: None,
+            labels: &Labels::default(),
+            slugger: &Slugger::default(),
+            readme_names: &default_readme_names(),
+            meta: &ChapterMetas::new(),
+            max_title_len: None,
+            updated: &HashMap::new(),
+            no_readme: None,
+            drafts: &HashSet::new(),
+        };
+        let summary = MdBook('-').render(&book, &context);
+
+        assert_eq!("# Summary\n\n- [C#](#)\n    - [F# Notes](C%23/F%23%20notes.md)\n", summary);
+        assert_eq!(vec![("F# Notes".to_string(), "C#/F# notes.md".to_string())], entries(&summary));
+    }
+
+    #[test]
+    fn target_test() {
+        assert_eq!("guide/setup.md", target("guide/setup.md"));
+        assert_eq!("my%20notes/C%23%20%28draft%29.md", target("my notes/C# (draft).md"));
+
+        let summary = "- [Draft](my%20notes/C%23%20%28draft%29.md)\n";
+        assert_eq!(vec![("Draft".to_string(), "my notes/C# (draft).md".to_string())], entries(summary));
+
+        let summary = format!("- [Encoded]({})\n", target("a%20b.md"));
+        assert_eq!("- [Encoded](a%2520b.md)\n", summary);
+        assert_eq!(vec![("Encoded".to_string(), "a%20b.md".to_string())], entries(&summary));
+    }
+
+    #[test]
+    fn no_readme_test() {
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &["guide/setup.md".to_string(), "guide/cli/build.md".to_string()],
+        );
+        let titles = Titles::new();
+        let mut context = RenderContext {
+            sort: &None,
+            titles: &titles,
+            introduction: None,
+            glossary: None,
+            index: None,
+            labels: &Labels::default(),
+            slugger: &Slugger::default(),
+            readme_names: &default_readme_names(),
+            meta: &ChapterMetas::new(),
+            max_title_len: None,
+            updated: &HashMap::new(),
+            no_readme: None,
+            drafts: &HashSet::new(),
+        };
+        assert!(MdBook('-').render(&book, &context).contains("- [Guide](#)\n"));
+        context.no_readme = Some(NoReadmeStyle::Draft);
+        assert!(MdBook('-').render(&book, &context).contains("- [Guide]()\n"));
+        context.no_readme = Some(NoReadmeStyle::Text);
+        assert!(MdBook('-').render(&book, &context).contains("- Guide\n"));
+        context.no_readme = Some(NoReadmeStyle::LinkFirstChild);
+        assert_eq!(
+            "# Summary\n\n- [Guide](guide/setup.md)\n    - [Cli](guide/cli/build.md)\n",
+            MdBook('-').render(&book, &context)
+        );
+        context.no_readme = Some(NoReadmeStyle::Skip);
+        assert_eq!(
+            "# Summary\n\n- [Setup](guide/setup.md)\n- [Build](guide/cli/build.md)\n",
+            MdBook('-').render(&book, &context)
+        );
+    }
+
+    #[test]
+    fn updated_test() {
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &["about.md".to_string(), "guide/README.md".to_string()],
+        );
+        let titles = Titles::new();
+        let mut updated = HashMap::new();
+        updated.insert("about.md".to_string(), "2021-03-04".to_string());
+        updated.insert("guide/README.md".to_string(), "2020-01-02".to_string());
+        let context = RenderContext {
+            sort: &None,
+            titles: &titles,
+            introduction: None,
+            glossary: None,
+            index: None,
+            labels: &Labels::default(),
+            slugger: &Slugger::default(),
+            readme_names: &default_readme_names(),
+            meta: &ChapterMetas::new(),
+            max_title_len: None,
+            updated: &updated,
+            no_readme: None,
+            drafts: &HashSet::new(),
+        };
+        assert_eq!(
+            "# Summary\n\n- [About](about.md) (2021-03-04)\n- [Guide](guide/README.md) (2020-01-02)\n",
+            MdBook('-').render(&book, &context)
+        );
+        assert_eq!(
+            "# Summary\n\n* [About](about.md) (2021-03-04)\n* [Guide](guide/README.md) (2020-01-02)\n",
+            GitBook('*').render(&book, &context)
+        );
+        assert_eq!(
+            "- [About](about.md)\n- [Guide](guide/README.md)\n",
+            Docsify.render(&book, &context)
+        );
+    }
+
+    #[test]
+    fn list_format_test() {
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &["about.md".to_string(), "guide/README.md".to_string(), "guide/setup.md".to_string()],
+        );
+        let table = "list = \"*\"\nindent = 2\nreadme = \"page\"\nheading = false\nstrip-extension = true"
+            .parse::<toml::Value>()
+            .unwrap();
+        let format = Format::new(ListFormat::from_toml("wiki", &table).unwrap());
+
+        assert_eq!(
+            "* [About](about)\n* Guide\n  * [Readme](guide/README)\n  * [Setup](guide/setup)\n",
+            book.get_summary_file(&format, &None)
+        );
+        let table = "list = \"--\"".parse::<toml::Value>().unwrap();
+        assert!(ListFormat::from_toml("wiki", &table).is_err());
+        let table = "color = true".parse::<toml::Value>().unwrap();
+        assert!(ListFormat::from_toml("wiki", &table).is_err());
+    }
+
+    #[test]
+    fn json_test() {
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &["about.md".to_string(), "guide/README.md".to_string()],
+        );
+        let format: Format = "json".parse().unwrap();
+        let value: serde_json::Value =
+            serde_json::from_str(&book.get_summary_file(&format, &None)).unwrap();
+
+        assert_eq!("Summary", value["title"]);
+        assert_eq!("about.md", value["pages"][0]["path"]);
+        assert_eq!("Guide", value["chapters"][0]["title"]);
+        assert_eq!("guide", value["chapters"][0]["id"]);
+        assert_eq!("guide/README.md", value["chapters"][0]["path"]);
+        assert_eq!(uid("guide"), value["chapters"][0]["uid"]);
+        assert_eq!(uid("about.md"), value["pages"][0]["uid"]);
+        assert_eq!("af63dc4c8601ec8c", uid("a"));
+
+        // the id of a chapter is the slug of the title it is listed with
+        let mut meta = ChapterMetas::new();
+        meta.entry("guide".to_string()).or_default().title = Some("Getting Started".to_string());
+        let titles = Titles::new();
+        let context = RenderContext {
+            sort: &None,
+            titles: &titles,
+            introduction: None,
+            glossary: None,
+            index: None,
+            labels: &Labels::default(),
+            slugger: &Slugger::default(),
+            readme_names: &default_readme_names(),
+            meta: &meta,
+            max_title_len: None,
+            updated: &HashMap::new(),
+            no_readme: None,
+            drafts: &HashSet::new(),
+        };
+        let value: serde_json::Value =
+            serde_json::from_str(&format.renderer().render(&book, &context)).unwrap();
+        assert_eq!("Getting Started", value["chapters"][0]["title"]);
+        assert_eq!("getting-started", value["chapters"][0]["id"]);
+    }
+
+    #[test]
+    fn detect_test() {
+        let dir = fixture(&[]);
+        let none = detect(&dir);
+        std::fs::write(dir.join("index.html"), "<script src=\"//cdn.jsdelivr.net/npm/docsify@4\"></script>").unwrap();
+        let docsify = detect(&dir);
+        std::fs::write(dir.join("book.json"), "{}").unwrap();
+        let git = detect(&dir);
+        std::fs::write(dir.join("book.toml"), "").unwrap();
+        let md = detect(&dir);
+
+        assert_eq!(None, none);
+        assert_eq!(Some("docsify"), docsify.as_ref().map(|f| f.name()));
+        assert_eq!(Some(Format::gitbook()), git);
+        assert_eq!(Some(Format::mdbook()), md);
+    }
+
+    #[test]
+    fn style_links_test() {
+        let summary = "# Summary\n\n- [Guide](#)\n    - [Setup](guide/setup.md)\n- [Draft]()\n- [Web](https://x.org)\n";
+
+        assert_eq!(
+            "# Summary\n\n- [Guide](#)\n    - [Setup](/guide/setup.md)\n- [Draft]()\n- [Web](https://x.org)\n",
+            style_links(summary, LinkStyle::Absolute)
+        );
+        assert_eq!(summary, style_links(summary, LinkStyle::Relative));
+    }
+
+    #[test]
+    fn replace_extension_test() {
+        let summary = "# Summary\n\n- [Guide](guide/README.md)\n    - [Setup](guide/setup.MD)\n- [Spec](spec.pdf)\n";
+
+        assert_eq!(
+            "# Summary\n\n- [Guide](guide/README)\n    - [Setup](guide/setup)\n- [Spec](spec.pdf)\n",
+            replace_extension(summary, "")
+        );
+        assert!(replace_extension(summary, ".html").contains("- [Guide](guide/README.html)\n"));
+    }
+
+    #[test]
+    fn space_lines_test() {
+        let summary = "# Summary\n\n[Introduction](README.md)\n\n- [Guide](guide/README.md)\n    - [Setup](guide/setup.md)\n\
+                       - [Über](uber.md)\n\n---\n\n- [Glossary](GLOSSARY.md)\n";
+
+        assert_eq!(
+            "# Summary\n[Introduction](README.md)\n- [Guide](guide/README.md)\n    - [Setup](guide/setup.md)\n\
+             - [Über](uber.md)\n---\n- [Glossary](GLOSSARY.md)\n",
+            space_lines(summary, true, None)
+        );
+        assert_eq!(
+            "# Summary\n\n[Introduction](README.md)\n\n- [Guide](guide/README.md)\n    - [Setup](guide/setup.md)\n\n\
+             - [Über](uber.md)\n\n---\n\n- [Glossary](GLOSSARY.md)\n",
+            space_lines(summary, false, Some(1))
+        );
+        assert_eq!(
+            "# Summary\n[Introduction](README.md)\n- [Guide](guide/README.md)\n    - [Setup](guide/setup.md)\n\n\n\
+             - [Über](uber.md)\n---\n- [Glossary](GLOSSARY.md)\n",
+            space_lines(summary, true, Some(2))
+        );
+    }
+
+    #[test]
+    fn mdbook_numbers_test() {
+        let summary = "# Summary\n\n[Intro](intro.md)\n\n- [Guide](guide/README.md)\n    - [Draft]()\n    \
+                       - [Setup](guide/setup.md)\n\n# Reference\n\n- [API](api.md)\n\n[License](license.md)\n";
+
+        let expected = vec![
+            ("guide/README.md".to_string(), "1".to_string()),
+            ("guide/setup.md".to_string(), "1.2".to_string()),
+            ("api.md".to_string(), "2".to_string()),
+        ];
+        assert_eq!(expected, mdbook_numbers(summary));
+    }
+
+    #[test]
+    fn split_parts_test() {
+        let summary = "# Summary\n\n[Introduction](README.md)\n\n- [A](a/README.md)\n    - [B](a/b.md)\n\
+                       \x20   - [C](a/c/README.md)\n        - [D](a/c/d.md)\n- [E](e.md)\n";
+        assert_eq!(
+            "# Summary\n\n[Introduction](README.md)\n\n- [A](a/README.md)\n    - [B](a/b.md)\n\
+             \x20   - [C](a/c/README.md)\n\n---\n\n- [A (continued)]()\n    - [C (continued)]()\n\
+             \x20       - [D](a/c/d.md)\n- [E](e.md)\n",
+            split_parts(summary, 3, "continued", None)
+        );
+        assert!(split_parts(summary, 3, "continued", Some(NoReadmeStyle::Text)).contains("\n- A (continued)\n"));
+        assert_eq!(summary, split_parts(summary, 5, "continued", None));
+    }
+
+    #[test]
+    fn quartz_test() {
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &["about.md".to_string(), "guide/README.md".to_string(), "guide/setup.md".to_string()],
+        );
+        let format: Format = "quartz".parse().unwrap();
+        let value: serde_json::Value =
+            serde_json::from_str(&book.get_summary_file(&format, &None)).unwrap();
+
+        assert_eq!(
+            serde_json::json!({ "name": "Summary", "path": "", "children": [
+                { "name": "About", "path": "about", "children": [] },
+                { "name": "Guide", "path": "guide", "children": [
+                    { "name": "Setup", "path": "guide/setup", "children": [] },
+                ] },
+            ] }),
+            value
+        );
+    }
+
+    #[test]
+    fn filelist_test() {
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &[
+                "README.md".to_string(),
+                "about.md".to_string(),
+                "guide/setup.md".to_string(),
+                "guide/README.md".to_string(),
+                "guide/more/x.md".to_string(),
+            ],
+        );
+        let format: Format = "filelist".parse().unwrap();
+
+        assert_eq!(
+            "README.md\nabout.md\nguide/README.md\nguide/setup.md\nguide/more/x.md\n",
+            book.get_summary_file(&format, &None)
+        );
+    }
+
+    #[test]
+    fn epub_test() {
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &["a & b.md".to_string(), "guide/setup.md".to_string(), "guide/x/y.md".to_string()],
+        );
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+<head>
+  <title>Summary</title>
+</head>
+<body>
+  <nav epub:type="toc" id="toc">
+    <h1>Summary</h1>
+    <ol>
+      <li><a href="a%20&amp;%20b.xhtml">A &amp; B</a></li>
+      <li><span>Guide</span>
+        <ol>
+          <li><a href="guide/setup.xhtml">Setup</a></li>
+          <li><span>X</span>
+            <ol>
+              <li><a href="guide/x/y.xhtml">Y</a></li>
+            </ol>
+          </li>
+        </ol>
+      </li>
+    </ol>
+  </nav>
+</body>
+</html>
+"#;
+        assert_eq!(expected, book.get_summary_file(&"epub-nav".parse().unwrap(), &None));
+        let nav = book.get_summary_file(&Format::new(EpubNav("html".to_string())), &None);
+        assert!(nav.contains("<a href=\"guide/setup.html\">"));
+
+        let ncx = book.get_summary_file(&"ncx".parse().unwrap(), &None);
+        assert!(ncx.contains("<meta name=\"dtb:depth\" content=\"3\"/>"));
+        // chapters without README point to their first file
+        let expected = r#"    <navPoint id="navpoint-2" playOrder="2">
+      <navLabel><text>Guide</text></navLabel>
+      <content src="guide/setup.xhtml"/>
+      <navPoint id="navpoint-3" playOrder="3">
+        <navLabel><text>Setup</text></navLabel>
+        <content src="guide/setup.xhtml"/>
+      </navPoint>
+      <navPoint id="navpoint-4" playOrder="4">
+"#;
+        assert!(ncx.contains(expected));
+    }
+
+    #[test]
+    fn json_schema_test() {
+        let schema: serde_json::Value = serde_json::from_str(JSON_SCHEMA).unwrap();
+        assert_eq!(JSON_SCHEMA_VERSION, schema["properties"]["schema_version"]["const"]);
+
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &["about.md".to_string(), "guide/README.md".to_string()],
+        );
+        let format: Format = "json".parse().unwrap();
+        let value: serde_json::Value =
+            serde_json::from_str(&book.get_summary_file(&format, &None)).unwrap();
+
+        // every key of the output is described
+        let keys = |v: &serde_json::Value| v.as_object().unwrap().keys().cloned().collect::<Vec<_>>();
+        let definitions = &schema["definitions"];
+        assert_eq!(keys(&schema["properties"]), keys(&value));
+        assert_eq!(keys(&definitions["page"]["properties"]), keys(&value["pages"][0]));
+        assert_eq!(keys(&definitions["chapter"]["properties"]), keys(&value["chapters"][0]));
+    }
+
+    #[test]
+    fn register_test() {
+        struct Plain;
+
+        impl Renderer for Plain {
+            fn name(&self) -> &str {
+                "plain"
+            }
+
+            fn render(&self, book: &Chapter, _context: &RenderContext) -> String {
+                book.name.clone()
+            }
+        }
+
+        assert!("plain".parse::<Format>().is_err());
+        register(Plain);
+
+        let format: Format = "plain".parse().unwrap();
+        let book = Chapter::new("Book".to_string(), &[]);
+        assert_eq!("Book", book.get_summary_file(&format, &None));
+    }
+
+    #[test]
+    fn count_entries_test() {
+        assert_eq!(3, count_entries("# Summary\n\n[Intro](README.md)\n\n- [A](a.md)\n- [B](#)\n    - [C](b/c.md)\n"));
+        assert_eq!(1, count_entries("{\n  \"path\": \"a.md\",\n  \"path\": null\n}"));
+    }
+
+    #[test]
+    fn invalid_format_test() {
+        let err = "latex".parse::<Format>().unwrap_err();
+        assert!(err.to_string().contains("docsify"));
+        assert!(!err.to_string().contains("did you mean"));
+        assert_eq!(
+            "Invalid format mdbok, did you mean 'md'? \
+             (one of docsify, epub-nav, filelist, git, gitbook, json, md, mdbook, ncx, quartz)",
+            "mdbok".parse::<Format>().unwrap_err().to_string()
+        );
+        assert_eq!(Format::gitbook(), "gitbook".parse().unwrap());
+    }
+}
@@ -0,0 +1,157 @@
+use crate::render::xml_escape;
+use crate::warning::Warning;
+use serde_json::json;
+use std::str::FromStr;
+
+/// Machine readable report of the warnings of a run, for CI systems
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    /// JUnit XML with a failed testcase per warning
+    Junit,
+    /// SARIF with a result per warning, for GitHub code scanning
+    Sarif,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "junit" => Ok(ReportFormat::Junit),
+            "sarif" => Ok(ReportFormat::Sarif),
+            _ => Err(format!("Invalid report format {}, expected one of: junit, sarif", s)),
+        }
+    }
+}
+
+impl ReportFormat {
+    /// File the report is written to unless another one is given
+    pub fn default_file(&self) -> &str {
+        match self {
+            ReportFormat::Junit => "book-summary-junit.xml",
+            ReportFormat::Sarif => "book-summary.sarif",
+        }
+    }
+
+    /// The report of `warnings`, whose files are relative to the notes dir at `base` (a
+    /// path relative to the repository or the current dir, empty for it)
+    pub fn render(&self, warnings: &[Warning], base: &str) -> String {
+        let locations = warnings
+            .iter()
+            .map(|w| {
+                let (file, line) = w.location();
+                match base.trim_matches('/') {
+                    "" | "." => (file, line),
+                    base => (format!("{}/{}", base, file), line),
+                }
+            })
+            .collect::<Vec<_>>();
+        match self {
+            ReportFormat::Junit => junit(warnings, &locations),
+            ReportFormat::Sarif => sarif(warnings, &locations),
+        }
+    }
+}
+
+// A testcase per warning named after its entry and classified by its kind, or a single
+// passed testcase without warnings
+fn junit(warnings: &[Warning], locations: &[(String, usize)]) -> String {
+    let tests = warnings.len().max(1);
+    let mut xml = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".to_string();
+    xml += &format!("<testsuites name=\"book-summary\" tests=\"{}\" failures=\"{}\">\n", tests, warnings.len());
+    xml += &format!("  <testsuite name=\"book-summary\" tests=\"{}\" failures=\"{}\">\n", tests, warnings.len());
+    if warnings.is_empty() {
+        xml += "    <testcase classname=\"book-summary\" name=\"summary\"/>\n";
+    }
+    for (warning, (file, line)) in warnings.iter().zip(locations) {
+        let name = match line {
+            0 => file.clone(),
+            line => format!("{}:{}", file, line),
+        };
+        let message = xml_escape(&warning.to_string());
+        xml += &format!(
+            "    <testcase classname=\"book-summary.{}\" name=\"{}\">\n",
+            warning.kind(),
+            xml_escape(&name)
+        );
+        xml += &format!("      <failure message=\"{}\" type=\"{}\">{}</failure>\n", message, warning.kind(), message);
+        xml += "    </testcase>\n";
+    }
+    xml += "  </testsuite>\n</testsuites>\n";
+    xml
+}
+
+// A result per warning with the kinds of warnings as rules
+fn sarif(warnings: &[Warning], locations: &[(String, usize)]) -> String {
+    let mut rules: Vec<&str> = warnings.iter().map(|w| w.kind()).collect();
+    rules.sort_unstable();
+    rules.dedup();
+    let results = warnings
+        .iter()
+        .zip(locations)
+        .map(|(warning, (file, line))| {
+            let mut location = json!({ "artifactLocation": { "uri": file } });
+            if *line > 0 {
+                location["region"] = json!({ "startLine": line });
+            }
+            json!({
+                "ruleId": warning.kind(),
+                "level": "warning",
+                "message": { "text": warning.to_string() },
+                "locations": [{ "physicalLocation": location }],
+            })
+        })
+        .collect::<Vec<_>>();
+    let value = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "book-summary",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules.iter().map(|id| json!({ "id": id })).collect::<Vec<_>>(),
+                }
+            },
+            "results": results,
+        }],
+    });
+    format!("{}\n", serde_json::to_string_pretty(&value).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn junit_test() {
+        let warnings = vec![
+            Warning::EmptyChapter("drafts".to_string()),
+            Warning::Lint("guide/setup.md".to_string(), 6, "forbidden `TODO`".to_string()),
+        ];
+
+        let xml = ReportFormat::Junit.render(&warnings, "");
+
+        assert!(xml.contains("<testsuite name=\"book-summary\" tests=\"2\" failures=\"2\">"));
+        assert!(xml.contains(
+            "    <testcase classname=\"book-summary.lint\" name=\"guide/setup.md:6\">\n      \
+             <failure message=\"guide/setup.md:6: forbidden `TODO`\" type=\"lint\">"
+        ));
+        assert!(ReportFormat::Junit.render(&[], ".").contains("<testcase classname=\"book-summary\" name=\"summary\"/>"));
+    }
+
+    #[test]
+    fn sarif_test() {
+        let warnings = vec![Warning::Lint("guide/setup.md".to_string(), 6, "forbidden `TODO`".to_string())];
+
+        let sarif: serde_json::Value = serde_json::from_str(&ReportFormat::Sarif.render(&warnings, "docs/")).unwrap();
+
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!("lint", result["ruleId"]);
+        assert_eq!("guide/setup.md:6: forbidden `TODO`", result["message"]["text"]);
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!("docs/guide/setup.md", location["artifactLocation"]["uri"]);
+        assert_eq!(6, location["region"]["startLine"]);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::frontmatter;
+use serde_json::json;
+
+/// File name of the generated search metadata, relative to the notes dir
+pub const SEARCH_INDEX_FILE: &str = "search-index.json";
+
+/// Search metadata of a markdown file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub title: String,
+    pub path: String,
+    /// Text of the headings below the title, in order
+    pub headings: Vec<String>,
+    /// The first paragraph as plain text
+    pub excerpt: String,
+}
+
+impl Document {
+    /// Metadata of the file at `path` with the markdown `content`, code blocks are
+    /// left out
+    pub fn new(path: &str, title: &str, content: &str) -> Self {
+        let mut headings = vec![];
+        let mut paragraph: Vec<&str> = vec![];
+        let mut excerpt = None;
+        let mut fenced = false;
+        for line in frontmatter::strip(content).lines() {
+            let trimmed = line.trim();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                fenced = !fenced;
+                continue;
+            }
+            if fenced {
+                continue;
+            }
+            let ends_paragraph = trimmed.is_empty() || trimmed.starts_with('#');
+            if ends_paragraph && excerpt.is_none() && !paragraph.is_empty() {
+                excerpt = Some(plain(&paragraph.join(" ")));
+            }
+            if ends_paragraph {
+                paragraph.clear();
+            }
+            match trimmed.strip_prefix("##") {
+                Some(heading) => headings.push(plain(heading.trim_start_matches('#').trim())),
+                None if ends_paragraph || excerpt.is_some() => {}
+                // html blocks and comments
+                None if trimmed.starts_with('<') => {}
+                None => paragraph.push(trimmed),
+            }
+        }
+        if excerpt.is_none() && !paragraph.is_empty() {
+            excerpt = Some(plain(&paragraph.join(" ")));
+        }
+        Document {
+            title: title.to_string(),
+            path: path.to_string(),
+            headings,
+            excerpt: excerpt.unwrap_or_default(),
+        }
+    }
+}
+
+// `text` without links, images, emphasis and code marks
+fn plain(text: &str) -> String {
+    let mut plain = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('[') {
+        let (before, link) = rest.split_at(start);
+        // the alt text of images as well
+        plain += before.strip_suffix('!').unwrap_or(before);
+        match link.find("](").and_then(|end| Some((end, link[end..].find(')')? + end))) {
+            Some((end, close)) => {
+                plain += &link[1..end];
+                rest = &link[close + 1..];
+            }
+            None => {
+                plain += "[";
+                rest = &link[1..];
+            }
+        }
+    }
+    plain += rest;
+    plain.replace(['*', '`'], "")
+}
+
+/// The documents as JSON array for search libraries like Lunr or Pagefind
+pub fn render(documents: &[Document]) -> String {
+    let value = documents
+        .iter()
+        .map(|d| json!({ "title": d.title, "path": d.path, "headings": d.headings, "excerpt": d.excerpt }))
+        .collect::<Vec<_>>();
+    format!("{}\n", serde_json::to_string_pretty(&value).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn document_test() {
+        let content = "---\ntitle: Setup\n---\n# Setup\n\nInstall the **CLI** with\n[cargo](https://doc.rust-lang.org/cargo).\n\n\
+                       ```sh\n## not a heading\n```\n\n## Usage\n\nRun `book-summary`.\n\n### With `--format`\n";
+        let document = Document::new("guide/setup.md", "Setup", content);
+
+        assert_eq!(
+            Document {
+                title: "Setup".to_string(),
+                path: "guide/setup.md".to_string(),
+                headings: vec!["Usage".to_string(), "With --format".to_string()],
+                excerpt: "Install the CLI with cargo.".to_string(),
+            },
+            document
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use crate::collate;
+
+/// Creates URL safe identifiers from titles, transliterating non ASCII letters
+/// (`Über Straße` -> `uber-strasse`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slugger {
+    pub separator: char,
+}
+
+impl Default for Slugger {
+    fn default() -> Self {
+        Slugger { separator: '-' }
+    }
+}
+
+impl Slugger {
+    pub fn new(separator: char) -> Self {
+        Slugger { separator }
+    }
+
+    pub fn slug(&self, title: &str) -> String {
+        let mut slug = String::new();
+        let mut words = vec![];
+        for c in title.chars().flat_map(|c| c.to_lowercase()) {
+            match transliterate(c) {
+                Some(ascii) => slug.push_str(&ascii),
+                // anything else separates words
+                None if !slug.is_empty() => words.push(std::mem::take(&mut slug)),
+                None => {}
+            }
+        }
+        if !slug.is_empty() {
+            words.push(slug);
+        }
+        words.join(&self.separator.to_string())
+    }
+}
+
+// ASCII letters and digits of `c`, or `None` for word separators
+fn transliterate(c: char) -> Option<String> {
+    if c.is_ascii_alphanumeric() {
+        return Some(c.to_string());
+    }
+    if let Some(base) = collate::fold(c) {
+        return Some(base.to_string());
+    }
+    if let Some((_, latin)) = CYRILLIC.iter().chain(GREEK).find(|(l, _)| *l == c) {
+        return Some(latin.to_string());
+    }
+    if c.is_alphanumeric() {
+        // no transliteration known, but stable and URL safe
+        return Some(format!("u{:x}", c as u32));
+    }
+    None
+}
+
+const CYRILLIC: &[(char, &str)] = &[
+    ('а', "a"), ('б', "b"), ('в', "v"), ('г', "g"), ('д', "d"), ('е', "e"), ('ё', "e"),
+    ('ж', "zh"), ('з', "z"), ('и', "i"), ('й', "i"), ('к', "k"), ('л', "l"), ('м', "m"),
+    ('н', "n"), ('о', "o"), ('п', "p"), ('р', "r"), ('с', "s"), ('т', "t"), ('у', "u"),
+    ('ф', "f"), ('х', "kh"), ('ц', "ts"), ('ч', "ch"), ('ш', "sh"), ('щ', "shch"), ('ъ', ""),
+    ('ы', "y"), ('ь', ""), ('э', "e"), ('ю', "iu"), ('я', "ia"), ('і', "i"), ('ї', "i"),
+    ('є', "ie"), ('ґ', "g"),
+];
+
+const GREEK: &[(char, &str)] = &[
+    ('α', "a"), ('ά', "a"), ('β', "v"), ('γ', "g"), ('δ', "d"), ('ε', "e"), ('έ', "e"),
+    ('ζ', "z"), ('η', "i"), ('ή', "i"), ('θ', "th"), ('ι', "i"), ('ί', "i"), ('ϊ', "i"),
+    ('κ', "k"), ('λ', "l"), ('μ', "m"), ('ν', "n"), ('ξ', "x"), ('ο', "o"), ('ό', "o"),
+    ('π', "p"), ('ρ', "r"), ('σ', "s"), ('ς', "s"), ('τ', "t"), ('υ', "y"), ('ύ', "y"),
+    ('φ', "f"), ('χ', "ch"), ('ψ', "ps"), ('ω', "o"), ('ώ', "o"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slug_test() {
+        let slugger = Slugger::default();
+        assert_eq!("uber-strasse-2", slugger.slug("Über Straße 2"));
+        assert_eq!("privet-mir", slugger.slug("Привет, мир!"));
+        assert_eq!("ellada", slugger.slug("Ελλάδα"));
+        assert_eq!("getting-started", slugger.slug("  Getting started... "));
+        assert_eq!("u4e2du6587", slugger.slug("中文"));
+        assert_eq!("a_b", Slugger::new('_').slug("a b"));
+    }
+}
@@ -0,0 +1,96 @@
+use crate::slug::Slugger;
+
+// Level and title of a heading with its lines, and whether they are in a code block
+type Section<'a> = (usize, &'a str, Vec<(&'a str, bool)>);
+
+/// The files of the markdown `content` split at its H1 and H2 headings, as
+/// `(path, content)`. Each H1 becomes a chapter dir with a README of the text up to
+/// its first H2, each H2 a page of its chapter (its headings one level up). Numbered
+/// names keep the order of the document, the text before the first heading is the
+/// README of the notes dir.
+pub fn split(content: &str, slugger: &Slugger) -> Vec<(String, String)> {
+    let mut sections: Vec<Section> = vec![(0, "", vec![])];
+    let mut fenced = false;
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+        }
+        match heading(line).filter(|(level, _)| !fenced && *level <= 2) {
+            Some((level, title)) => sections.push((level, title, vec![(line, false)])),
+            None => sections.last_mut().unwrap().2.push((line, fenced)),
+        }
+    }
+
+    let mut files = vec![];
+    let (mut chapter, mut chapters, mut pages) = (String::new(), 0, 0);
+    for (level, title, lines) in sections {
+        let path = match level {
+            0 if lines.iter().all(|(l, _)| l.trim().is_empty()) => continue,
+            0 => "README.md".to_string(),
+            1 => {
+                chapters += 1;
+                pages = 0;
+                chapter = format!("{:02}-{}", chapters, name(slugger, title));
+                format!("{}/README.md", chapter)
+            }
+            _ => {
+                pages += 1;
+                match chapter.as_str() {
+                    "" => format!("{:02}-{}.md", pages, name(slugger, title)),
+                    chapter => format!("{}/{:02}-{}.md", chapter, pages, name(slugger, title)),
+                }
+            }
+        };
+        let lines = lines.into_iter().map(|(line, fenced)| match heading(line) {
+            Some((heading, _)) if level == 2 && heading > 1 && !fenced => &line[1..],
+            _ => line,
+        });
+        files.push((path, format!("{}\n", lines.collect::<Vec<_>>().join("\n").trim_end())));
+    }
+    files
+}
+
+// Level and text of the ATX heading `line`
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let text = line[level..].strip_prefix(' ')?;
+    match level {
+        1..=6 => Some((level, text.trim().trim_end_matches('#').trim())),
+        _ => None,
+    }
+}
+
+fn name(slugger: &Slugger, title: &str) -> String {
+    match slugger.slug(title) {
+        slug if slug.is_empty() => "section".to_string(),
+        slug => slug,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_test() {
+        let content = "Exported from Docs\n\n# Getting Started\n\nWelcome.\n\n## Install\n\n```sh\n## not a heading\n```\n\n\
+                       ### From source\n\n# Usage\n\n## The CLI\n";
+
+        let files = split(content, &Slugger::default());
+
+        assert_eq!(
+            vec![
+                ("README.md".to_string(), "Exported from Docs\n".to_string()),
+                ("01-getting-started/README.md".to_string(), "# Getting Started\n\nWelcome.\n".to_string()),
+                (
+                    "01-getting-started/01-install.md".to_string(),
+                    "# Install\n\n```sh\n## not a heading\n```\n\n## From source\n".to_string()
+                ),
+                ("02-usage/README.md".to_string(), "# Usage\n".to_string()),
+                ("02-usage/01-the-cli.md".to_string(), "# The CLI\n".to_string()),
+            ],
+            files
+        );
+    }
+}
@@ -0,0 +1,46 @@
+/// The candidate closest to the mistyped `input`, if any is close enough to be
+/// what was meant
+pub(crate) fn did_you_mean<'a>(input: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let input = input.to_lowercase();
+    let max_distance = std::cmp::max(2, input.chars().count() / 3);
+    candidates
+        .into_iter()
+        .map(|c| (distance(&input, &c.to_lowercase()), c))
+        .filter(|(d, _)| *d <= max_distance)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c)
+}
+
+// Levenshtein distance, counting a swap of two neighbouring chars as one edit
+fn distance(a: &str, b: &str) -> usize {
+    let (a, b) = (a.chars().collect::<Vec<_>>(), b.chars().collect::<Vec<_>>());
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn did_you_mean_test() {
+        let names = ["md", "mdbook", "git", "gitbook", "json"];
+        assert_eq!(Some("mdbook"), did_you_mean("mdbok", names));
+        assert_eq!(Some("git"), did_you_mean("gti", names));
+        assert_eq!(Some("json"), did_you_mean("JSN", names));
+        assert_eq!(None, did_you_mean("latex", names));
+    }
+}
@@ -0,0 +1,2227 @@
+use crate::authors;
+use crate::backlinks;
+use crate::book::{default_readme_names, file_title, Chapter};
+use crate::collate::Collation;
+use crate::date::{default_date_formats, SortMode};
+use crate::filter::{
+    self, Entry, EntryFilter, Extensions, Globs, Hidden, IgnoreFile, Marker, NavExclude,
+    OutputFile, Private, RootReadme, Status,
+};
+use crate::frontmatter;
+use crate::glossary;
+use crate::i18n::{self, Labels};
+use crate::index;
+use crate::links;
+use crate::lint::{self, Check, Note};
+use crate::meta::{self, ChapterMetas};
+use crate::migrate::{self, Migration};
+use crate::overflow;
+use crate::owners::{self, CodeOwners};
+use crate::redirects;
+use crate::render::{self, count_entries, Format, LinkStyle, NoReadmeStyle, RenderContext};
+use crate::search::{self, Document};
+use crate::slug::Slugger;
+use crate::tags;
+use crate::title::{
+    self, Dots, FilenameTitle, HeaderTitle, NumericNames, NumericTitle, TitleProvider, TitleSource, TitleStyle, Titles,
+};
+use crate::warning::{self, Warning};
+use crate::{Result, SummaryError};
+use crate::normalize::nfc;
+use crate::progress::Progress;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+use walkdir::WalkDir;
+
+/// Section file of Zola and Hugo, see [`SummaryBuilder::section_indexes`]
+const SECTION_INDEX: &str = "_index.md";
+
+/// Outputs of the built-in formats, never an entry even when generating another one
+const KNOWN_OUTPUTS: &[&str] = &["SUMMARY.md", "_sidebar.md"];
+
+/// Titles and chapter metadata of an already built chapter tree, resolved once with
+/// [`SummaryBuilder::resolve`] for all the summaries and pages of a run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Resolved {
+    pub titles: Titles,
+    pub metas: ChapterMetas,
+    /// Warnings of the title sources
+    pub warnings: Vec<Warning>,
+}
+
+/// Builder for generating a summary without going through the command line.
+///
+/// ```no_run
+/// use book_summary::{Format, SummaryBuilder};
+/// use std::path::Path;
+///
+/// let summary = SummaryBuilder::new()
+///     .title("My Book")
+///     .format(Format::gitbook())
+///     .sort(vec!["intro".to_string()])
+///     .generate(Path::new("./book"))
+///     .unwrap();
+/// ```
+pub struct SummaryBuilder {
+    title: Option<String>,
+    max_title_len: Option<usize>,
+    no_readme_style: Option<NoReadmeStyle>,
+    draft_stubs: Option<u64>,
+    compact: bool,
+    link_style: LinkStyle,
+    link_suffix: Option<String>,
+    raw_titles: bool,
+    chapter_spacing: Option<usize>,
+    renumbering: bool,
+    timestamps: Option<String>,
+    tag_index: bool,
+    author_index: bool,
+    az_index: bool,
+    search_index: bool,
+    codeowners: bool,
+    backlinks: bool,
+    labels: Labels,
+    slugger: Slugger,
+    readme_names: Vec<String>,
+    format: Format,
+    sort: Option<Vec<String>>,
+    collation: Collation,
+    sort_mode: SortMode,
+    append_new_only: bool,
+    date_key: String,
+    date_formats: Vec<String>,
+    excludes: Vec<String>,
+    entry_list: Option<Vec<String>>,
+    merges: Vec<(String, String)>,
+    renames: Vec<(String, String)>,
+    max_part_entries: Option<usize>,
+    max_entries_per_chapter: Option<usize>,
+    assets: Vec<String>,
+    outputfile: Option<String>,
+    outputs: Vec<(String, Format)>,
+    root_readme: bool,
+    introduction: Option<String>,
+    glossary: Option<String>,
+    changelog: Option<String>,
+    redirects: Option<String>,
+    migration: Option<Migration>,
+    languages: bool,
+    title_sources: Vec<TitleSource>,
+    dots: Dots,
+    title_style: TitleStyle,
+    numeric_names: NumericNames,
+    max_scan_bytes: Option<u64>,
+    status: Option<Vec<String>>,
+    title_providers: Vec<Box<dyn TitleProvider>>,
+    filters: Vec<Box<dyn EntryFilter>>,
+    checks: Vec<Box<dyn Check>>,
+    verbose: u8,
+    progress: bool,
+    strict: bool,
+    reported: Mutex<Vec<Warning>>,
+}
+
+impl Default for SummaryBuilder {
+    fn default() -> Self {
+        SummaryBuilder {
+            title: None,
+            max_title_len: None,
+            no_readme_style: None,
+            draft_stubs: None,
+            compact: false,
+            link_style: LinkStyle::Relative,
+            link_suffix: None,
+            raw_titles: false,
+            chapter_spacing: None,
+            renumbering: false,
+            timestamps: None,
+            tag_index: false,
+            author_index: false,
+            az_index: false,
+            search_index: false,
+            codeowners: false,
+            backlinks: false,
+            labels: Labels::default(),
+            slugger: Slugger::default(),
+            readme_names: default_readme_names(),
+            format: Format::mdbook(),
+            sort: None,
+            collation: Collation::Codepoint,
+            sort_mode: SortMode::Name,
+            append_new_only: false,
+            date_key: "date".to_string(),
+            date_formats: default_date_formats(),
+            excludes: vec![],
+            entry_list: None,
+            merges: vec![],
+            renames: vec![],
+            max_part_entries: None,
+            max_entries_per_chapter: None,
+            assets: vec![],
+            outputfile: None,
+            outputs: vec![],
+            root_readme: false,
+            introduction: None,
+            glossary: None,
+            changelog: None,
+            redirects: None,
+            migration: None,
+            languages: false,
+            title_sources: vec![TitleSource::Filename],
+            dots: Dots::default(),
+            title_style: TitleStyle::default(),
+            numeric_names: NumericNames::default(),
+            max_scan_bytes: None,
+            status: None,
+            title_providers: vec![],
+            filters: vec![],
+            checks: vec![],
+            verbose: 0,
+            progress: false,
+            strict: false,
+            reported: Mutex::new(vec![]),
+        }
+    }
+}
+
+impl SummaryBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Title for the summary heading, instead of the `# Header` of the root README or the
+    /// one from the labels
+    pub fn title<S: Into<String>>(mut self, title: S) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Cut titles longer than `len` chars off with an ellipsis, the links stay as they are
+    pub fn max_title_len(mut self, len: usize) -> Self {
+        self.max_title_len = Some(len);
+        self
+    }
+
+    /// List chapters without README in the list formats with `style`, instead of the
+    /// format's own way (a `(#)` link for mdBook, plain text for the others)
+    pub fn no_readme_style(mut self, style: NoReadmeStyle) -> Self {
+        self.no_readme_style = Some(style);
+        self
+    }
+
+    /// List stubs, pages smaller than `min_size` bytes or with nothing but headings, as
+    /// drafts in the list formats
+    pub fn draft_stubs(mut self, min_size: u64) -> Self {
+        self.draft_stubs = Some(min_size);
+        self
+    }
+
+    /// Leave out the blank lines of the markdown list formats, e.g. after the heading
+    pub fn compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    /// Keep the HTML entities (`&amp;`) and smart quotes of the titles instead of
+    /// decoding and straightening them
+    pub fn raw_titles(mut self, raw: bool) -> Self {
+        self.raw_titles = raw;
+        self
+    }
+
+    /// Link the files in `style` in the markdown list formats, relative by default
+    pub fn link_style(mut self, style: LinkStyle) -> Self {
+        self.link_style = style;
+        self
+    }
+
+    /// Link the markdown files with `suffix` in place of their `.md` extension in the
+    /// markdown list formats, e.g. nothing, `.html` or `/` for sites with pretty URLs
+    pub fn strip_extension<S: Into<String>>(mut self, suffix: S) -> Self {
+        self.link_suffix = Some(suffix.into());
+        self
+    }
+
+    /// Put `lines` blank lines between the top-level chapters of the markdown list formats
+    pub fn chapter_spacing(mut self, lines: usize) -> Self {
+        self.chapter_spacing = Some(lines);
+        self
+    }
+
+    /// Warn about chapters of the mdBook summary numbered differently than in its
+    /// previous version on disk
+    pub fn renumbering(mut self, renumbering: bool) -> Self {
+        self.renumbering = renumbering;
+        self
+    }
+
+    /// Append the last modified date of each entry in `format` (`%Y-%m-%d`, ...),
+    /// if the output format allows trailing text (md, git)
+    pub fn timestamps<S: Into<String>>(mut self, format: S) -> Self {
+        self.timestamps = Some(format.into());
+        self
+    }
+
+    /// Names of generated entries, like the heading or the introduction
+    pub fn labels(mut self, labels: Labels) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Identifiers of entries for formats with ids or anchors
+    pub fn slugger(mut self, slugger: Slugger) -> Self {
+        self.slugger = slugger;
+        self
+    }
+
+    /// Output format of the summary
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Chapters which should come first, in the given order
+    pub fn sort(mut self, chapters: Vec<String>) -> Self {
+        self.sort = Some(chapters);
+        self
+    }
+
+    /// Order of the remaining files and chapters
+    pub fn collate(mut self, collation: Collation) -> Self {
+        self.collation = collation;
+        self
+    }
+
+    /// Order of the files of a chapter
+    pub fn sort_mode(mut self, mode: SortMode) -> Self {
+        self.sort_mode = mode;
+        self
+    }
+
+    /// Leave out markdown files whose front matter `status` is not one of `allowed`,
+    /// files without a status are kept
+    pub fn status(mut self, allowed: Vec<String>) -> Self {
+        self.status = Some(allowed);
+        self
+    }
+
+    /// Keep the order of the entries of the existing output file and append new ones at
+    /// the end of their chapter, for summaries whose order is edited by hand
+    pub fn append_new_only(mut self, append: bool) -> Self {
+        self.append_new_only = append;
+        self
+    }
+
+    /// Front matter key of the date to sort by, `date` by default
+    pub fn date_key<S: Into<String>>(mut self, key: S) -> Self {
+        self.date_key = key.into();
+        self
+    }
+
+    /// Formats of front matter dates, the first matching one wins
+    pub fn date_formats(mut self, formats: Vec<String>) -> Self {
+        self.date_formats = formats;
+        self
+    }
+
+    /// Files or chapters (relative to the notes dir) or globs (`*`, `**`, `?`) to leave out
+    pub fn exclude<S: Into<String>>(mut self, path: S) -> Self {
+        self.excludes.push(path.into());
+        self
+    }
+
+    /// Build the chapter tree from these paths (relative to the notes dir) instead of
+    /// walking the notes dir, e.g. from a build system knowing the files already. The
+    /// filters still apply to each path and its dirs.
+    pub fn from_list<I: IntoIterator<Item = S>, S: Into<String>>(mut self, paths: I) -> Self {
+        self.entry_list = Some(paths.into_iter().map(|p| p.into()).collect());
+        self
+    }
+
+    /// Split the mdBook summary into parts of at most `max` entries, for huge books
+    pub fn max_part_entries(mut self, max: usize) -> Self {
+        self.max_part_entries = Some(max);
+        self
+    }
+
+    /// List only the first `max` files of each chapter and the rest on a generated
+    /// `MORE.md` page of the chapter, returned by [`SummaryBuilder::more_pages`]
+    pub fn max_entries_per_chapter(mut self, max: usize) -> Self {
+        self.max_entries_per_chapter = Some(max);
+        self
+    }
+
+    /// Show the top-level chapter `name` (case insensitive) as `title`, instead of the title
+    /// from its folder name or metadata
+    pub fn rename<S: Into<String>, T: Into<String>>(mut self, name: S, title: T) -> Self {
+        self.renames.push((name.into(), title.into()));
+        self
+    }
+
+    /// List the entries below the dir `source` in the chapter `target` (a path like
+    /// `notes/rust`, empty for the top level) together with its own entries
+    pub fn merge<S: Into<String>, T: Into<String>>(mut self, source: S, target: T) -> Self {
+        self.merges.push((source.into(), target.into()));
+        self
+    }
+
+    /// Extensions of non markdown files (`pdf`, `html`, ...) to link as well,
+    /// if the format allows it
+    pub fn include_assets(mut self, extensions: Vec<String>) -> Self {
+        self.assets = extensions;
+        self
+    }
+
+    /// Name of the summary file, which is never listed in itself. Defaults to the one of
+    /// the format, e.g. `SUMMARY.md` or `_sidebar.md`.
+    pub fn outputfile<S: Into<String>>(mut self, outputfile: S) -> Self {
+        self.outputfile = Some(outputfile.into());
+        self
+    }
+
+    /// Also render the summary in `format` for `file`, sharing the scan of the notes
+    pub fn output<S: Into<String>>(mut self, file: S, format: Format) -> Self {
+        self.outputs.push((file.into(), format));
+        self
+    }
+
+    /// File names of chapter READMEs (case insensitive). If a chapter contains several,
+    /// the first name in the list wins and the other files are listed as pages.
+    pub fn readme_names(mut self, names: Vec<String>) -> Self {
+        self.readme_names = names;
+        self
+    }
+
+    /// Append a chapter with a page per front matter tag, listing the notes with
+    /// that tag. The pages are returned by [`SummaryBuilder::tag_pages`] and
+    /// belong into the `tags` dir, which is not scanned itself.
+    pub fn tag_index(mut self, tag_index: bool) -> Self {
+        self.tag_index = tag_index;
+        self
+    }
+
+    /// Append an appendix chapter with a page per author, listing the notes they wrote
+    /// most lines of by `git blame`. The pages are returned by
+    /// [`SummaryBuilder::author_pages`] and belong into the `authors` dir, which is not
+    /// scanned itself.
+    pub fn author_index(mut self, author_index: bool) -> Self {
+        self.author_index = author_index;
+        self
+    }
+
+    /// Annotate the chapters with their owners from the CODEOWNERS of the repository the
+    /// notes are part of, in the json format
+    pub fn codeowners(mut self, codeowners: bool) -> Self {
+        self.codeowners = codeowners;
+        self
+    }
+
+    /// List every page alphabetically by title on a generated `INDEX.md`, listed last.
+    /// Its content is returned by [`SummaryBuilder::az_index_file`].
+    pub fn az_index(mut self, az_index: bool) -> Self {
+        self.az_index = az_index;
+        self
+    }
+
+    /// Collect the `redirect_from` urls of the front matter of all notes into a redirect
+    /// map `file` (relative to the notes dir), a `_redirects` file or else JSON. Its content
+    /// is returned by [`SummaryBuilder::redirects_file`].
+    pub fn redirects<S: Into<String>>(mut self, file: S) -> Self {
+        self.redirects = Some(file.into());
+        self
+    }
+
+    /// Export the title, headings and first paragraph of each markdown file to a
+    /// generated `search-index.json`, returned by [`SummaryBuilder::search_index_file`]
+    pub fn search_index(mut self, search_index: bool) -> Self {
+        self.search_index = search_index;
+        self
+    }
+
+    /// Collect the terms of all notes into a glossary (relative to the notes dir) listed
+    /// last. Its content is returned by [`SummaryBuilder::glossary_file`].
+    pub fn glossary<S: Into<String>>(mut self, file: S) -> Self {
+        self.glossary = Some(file.into());
+        self
+    }
+
+    /// Changelog of the entries (relative to the notes dir) written besides the summary,
+    /// which is not listed in it
+    pub fn changelog<S: Into<String>>(mut self, file: S) -> Self {
+        self.changelog = Some(file.into());
+        self
+    }
+
+    /// Write a summary per language folder (`en/`, `de/`, ...) and a `LANGS.md` listing
+    /// them instead of a summary, as multilingual GitBook and HonKit books have them. The
+    /// files are returned by [`SummaryBuilder::language_files`].
+    pub fn languages(mut self, languages: bool) -> Self {
+        self.languages = languages;
+        self
+    }
+
+    /// Migrate the book to another tool besides writing its summary, the changed files
+    /// are returned by [`SummaryBuilder::migration_files`]
+    pub fn migrate(mut self, migration: Migration) -> Self {
+        self.migration = Some(migration);
+        self
+    }
+
+    /// Maintain a backlinks section at the end of each markdown file, listing the
+    /// files linking to it. The updated files are returned by [`SummaryBuilder::backlink_files`].
+    pub fn backlinks(mut self, backlinks: bool) -> Self {
+        self.backlinks = backlinks;
+        self
+    }
+
+    /// List the README (or `index.md`) of the notes dir as introduction at the top of the summary
+    pub fn include_root_readme(mut self, include: bool) -> Self {
+        self.root_readme = include;
+        self
+    }
+
+    /// File (relative to the notes dir) to list as introduction at the top of the summary
+    pub fn introduction<S: Into<String>>(mut self, file: S) -> Self {
+        self.introduction = Some(file.into());
+        self
+    }
+
+    /// Built-in title sources, asked in the given order
+    pub fn title_from(mut self, sources: Vec<TitleSource>) -> Self {
+        self.title_sources = sources;
+        self
+    }
+
+    /// How titles derived from file names handle an inner extension (`setup.sh.md`)
+    pub fn dots(mut self, dots: Dots) -> Self {
+        self.dots = dots;
+        self
+    }
+
+    /// Capitalization of titles derived from file and chapter names
+    pub fn title_style(mut self, style: TitleStyle) -> Self {
+        self.title_style = style;
+        self
+    }
+
+    /// Titles of files and chapters named without any letters (`2024.md`), asked
+    /// before titles from their names
+    pub fn numeric_names(mut self, numeric: NumericNames) -> Self {
+        self.numeric_names = numeric;
+        self
+    }
+
+    /// Read at most `bytes` of each file for titles and front matter
+    pub fn max_scan_bytes(mut self, bytes: u64) -> Self {
+        self.max_scan_bytes = Some(bytes);
+        self
+    }
+
+    /// Custom title provider, asked before the built-in title sources.
+    /// Providers added first are asked first.
+    pub fn title_provider<P: TitleProvider + 'static>(mut self, provider: P) -> Self {
+        self.title_providers.push(Box::new(provider));
+        self
+    }
+
+    /// Additional filter for the entries of the notes dir, asked after the built-in ones
+    /// (hidden entries, output file, non markdown files, excludes and private chapters)
+    pub fn filter<F: EntryFilter + 'static>(mut self, filter: F) -> Self {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    /// Check of the content of the markdown notes, its findings are warnings
+    pub fn check<C: Check + 'static>(mut self, check: C) -> Self {
+        self.checks.push(Box::new(check));
+        self
+    }
+
+    /// Verbose level, skipped entries are reported from level 2 on
+    pub fn verbose(mut self, level: u8) -> Self {
+        self.verbose = level;
+        self
+    }
+
+    /// Show the progress of scanning and reading titles on stderr, if it is a terminal and
+    /// there are many entries
+    pub fn progress(mut self, progress: bool) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Fail on warnings instead of printing them
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Scan `dir` and build the chapter tree, warnings are printed to stderr
+    pub fn build(&self, dir: &Path) -> Result<Chapter> {
+        let (book, warnings) = self.scan(dir)?;
+        self.report(warnings)?;
+        Ok(book)
+    }
+
+    /// Scan `dir` and build the chapter tree, returning the warnings found
+    pub fn scan(&self, dir: &Path) -> Result<(Chapter, Vec<Warning>)> {
+        if !dir.is_dir() {
+            return Err(Box::new(SummaryError::DirNotFound(dir.to_path_buf())));
+        }
+
+        let hidden = Hidden;
+        let no_summary = Marker(filter::NO_SUMMARY.to_string());
+        let outputs = self
+            .output_files()
+            .map(|(file, _)| file)
+            .chain(self.glossary.as_deref())
+            .chain(self.changelog.as_deref())
+            .chain(self.az_index.then_some(index::INDEX_FILE))
+            .chain(self.search_index.then_some(search::SEARCH_INDEX_FILE))
+            .chain(self.languages.then_some(migrate::LANGS))
+            // the GitBook files are no chapters of an mdBook
+            .chain(match self.migration {
+                Some(Migration::GitbookToMdbook) => vec![migrate::GLOSSARY, migrate::LANGS],
+                None => vec![],
+            })
+            .chain(KNOWN_OUTPUTS.iter().copied())
+            .map(|file| {
+                // `./SUMMARY.md` is the entry `SUMMARY.md`
+                OutputFile(entry_path(Path::new(file), Path::new("")).unwrap_or_default())
+            })
+            .collect::<Vec<_>>();
+        let mut extensions = Extensions(vec!["md".to_string()]);
+        if !self.assets.is_empty() {
+            if self.output_files().any(|(_, format)| format.renderer().links_assets()) {
+                extensions.0.extend(self.assets.iter().map(|e| e.trim_start_matches('.').to_string()));
+            } else if self.verbose > 0 {
+                eprintln!("Format {} can't link assets, ignoring {}", self.format, self.assets.join(","));
+            }
+        }
+        let mut excludes = Globs(self.excludes.clone());
+        if self.tag_index {
+            excludes.0.push(tags::TAG_DIR.to_string());
+        }
+        if self.author_index {
+            excludes.0.push(authors::AUTHOR_DIR.to_string());
+        }
+        if self.max_entries_per_chapter.is_some() {
+            excludes.0.push(format!("**/{}", overflow::MORE_FILE));
+        }
+        let private = Private(self.readme_names.clone(), self.max_scan_bytes);
+        let nav_exclude = NavExclude(self.max_scan_bytes);
+        let mut filters: Vec<&dyn EntryFilter> = vec![&hidden, &no_summary];
+        filters.extend(outputs.iter().map(|o| o as &dyn EntryFilter));
+        filters.extend([&extensions as &dyn EntryFilter, &excludes, &private, &nav_exclude]);
+        // the exclusion rules of the other mdBook tooling apply to the summary as well
+        let mdbook_ignore = match dir.join(filter::MDBOOK_IGNORE) {
+            file if file.is_file() => Some(IgnoreFile::from_file(&file)?),
+            _ => None,
+        };
+        if let Some(mdbook_ignore) = &mdbook_ignore {
+            filters.push(mdbook_ignore);
+        }
+        let status = self.status.clone().map(|allowed| Status(allowed, self.max_scan_bytes));
+        if let Some(status) = &status {
+            filters.push(status);
+        }
+        // an explicit introduction is never skipped as root README
+        let root_readme = RootReadme(
+            self.readme_names
+                .iter()
+                .filter(|name| match &self.introduction {
+                    Some(intro) => !intro.eq_ignore_ascii_case(name),
+                    None => true,
+                })
+                .cloned()
+                .collect(),
+        );
+        if !self.root_readme {
+            filters.push(&root_readme);
+        }
+        filters.extend(self.filters.iter().map(|f| f.as_ref()));
+
+        let started = Instant::now();
+        let (entries, mut warnings) = match &self.entry_list {
+            Some(list) => {
+                let (mut entries, warnings) = get_list(dir, list, &filters, self.verbose);
+                entries.sort();
+                entries.dedup();
+                (entries, warnings)
+            }
+            None => get_dir(dir, &filters, &self.collation, self.verbose, self.progress)?,
+        };
+        self.stats(format!("Scanned {} entries", entries.len()), started);
+        warnings.extend(warning::case_conflicts(&entries));
+        warnings.extend(warning::hash_names(&entries));
+        let title = match &self.title {
+            Some(title) => title.clone(),
+            None => self.root_readme_title(dir).unwrap_or_else(|| self.labels.summary.clone()),
+        };
+        let mut book = Chapter::merged(title.clone(), &entries, &self.merges);
+        if !self.merges.is_empty() || self.entry_list.is_some() {
+            // merged chapters list the entries of their dirs mixed, in the order of a scan
+            book.sort_by(&|a, b| self.collation.compare(&nfc(a), &nfc(b)).then_with(|| a.cmp(b)));
+        }
+        if self.sort_mode == SortMode::FrontMatterDate {
+            let started = Instant::now();
+            let (key, formats) = (&self.date_key, &self.date_formats);
+            meta::sort_by_date(&mut book, dir, key, formats, self.max_scan_bytes);
+            self.stats(format!("Sorted {} files by date", entries.len()), started);
+        }
+        if self.append_new_only {
+            if let Ok(previous) = std::fs::read_to_string(dir.join(self.summary_file())) {
+                let order = render::entries(&previous).into_iter().map(|(_, path)| path);
+                book.keep_order(&order.collect::<Vec<_>>());
+            }
+        }
+        Ok((book, warnings))
+    }
+
+    /// Resolve the titles and the chapter metadata of an already built chapter tree of
+    /// the notes in `dir`, for rendering it and generating its pages
+    pub fn resolve(&self, book: &Chapter, dir: &Path) -> Result<Resolved> {
+        let started = Instant::now();
+        let (titles, warnings) = self.resolve_titles(book, dir)?;
+        self.stats(format!("Resolved {} titles", titles.len()), started);
+        let started = Instant::now();
+        let metas = self.chapter_metas(book, dir);
+        self.stats(format!("Read the metadata of {} chapters", metas.len()), started);
+        Ok(Resolved { titles, metas, warnings })
+    }
+
+    /// Render an already built chapter tree of the notes in `dir`, warnings are printed to stderr
+    pub fn render(&self, book: &Chapter, dir: &Path, resolved: &Resolved) -> Result<String> {
+        let (summary, warnings) = self.render_checked(book, dir, resolved)?;
+        self.report(warnings)?;
+        Ok(summary)
+    }
+
+    /// Render an already built chapter tree of the notes in `dir`, returning the warnings found
+    pub fn render_checked(
+        &self,
+        book: &Chapter,
+        dir: &Path,
+        resolved: &Resolved,
+    ) -> Result<(String, Vec<Warning>)> {
+        self.render_format(book, dir, resolved, &self.format)
+    }
+
+    /// Render an already built chapter tree of the notes in `dir` for the output file
+    /// and each additional output, as `(file, summary)`. Warnings are printed to stderr.
+    pub fn render_outputs(
+        &self,
+        book: &Chapter,
+        dir: &Path,
+        resolved: &Resolved,
+    ) -> Result<Vec<(String, String)>> {
+        let mut summaries = vec![];
+        for (i, (file, format)) in self.output_files().enumerate() {
+            let (summary, warnings) = self.render_format(book, dir, resolved, format)?;
+            // the warnings don't depend on the format
+            if i == 0 {
+                self.report(warnings)?;
+            }
+            summaries.push((file.to_string(), summary));
+        }
+        Ok(summaries)
+    }
+
+    // The `# Header` of the README at the root of `dir`
+    fn root_readme_title(&self, dir: &Path) -> Option<String> {
+        let files = std::fs::read_dir(dir)
+            .ok()?
+            .filter_map(|e| e.ok()?.file_name().into_string().ok())
+            .collect::<Vec<_>>();
+        let readme = self
+            .readme_names
+            .iter()
+            .find_map(|name| files.iter().find(|f| f.eq_ignore_ascii_case(name)))?;
+        HeaderTitle(self.max_scan_bytes).title(dir, readme)
+    }
+
+    // The output file, or the default one of the format
+    fn summary_file(&self) -> &str {
+        match &self.outputfile {
+            Some(file) => file,
+            None => self.format.renderer().default_output(),
+        }
+    }
+
+    // The output file and the additional outputs
+    fn output_files(&self) -> impl Iterator<Item = (&str, &Format)> {
+        std::iter::once((self.summary_file(), &self.format))
+            .chain(self.outputs.iter().map(|(file, format)| (file.as_str(), format)))
+    }
+
+    fn resolve_titles(&self, book: &Chapter, dir: &Path) -> Result<(Titles, Vec<Warning>)> {
+        let mut providers: Vec<&dyn TitleProvider> =
+            self.title_providers.iter().map(|p| p.as_ref()).collect();
+        let sources = self
+            .title_sources
+            .iter()
+            .map(|s| s.provider_with(self.dots, self.title_style, self.max_scan_bytes))
+            .collect::<Result<Vec<_>>>()?;
+        let numeric = NumericTitle(self.numeric_names, self.dots, self.title_style, self.max_scan_bytes);
+        let names = self.title_sources.iter().position(|s| *s == TitleSource::Filename);
+        for (i, source) in sources.iter().enumerate() {
+            if Some(i) == names && self.numeric_names != NumericNames::Keep {
+                providers.push(&numeric);
+            }
+            providers.push(source.as_ref());
+        }
+        if names.is_none() && self.numeric_names != NumericNames::Keep {
+            providers.push(&numeric);
+        }
+        // entries without a title from the sources are named with the dots and style as well
+        let fallback = FilenameTitle(self.dots, self.title_style);
+        providers.push(&fallback);
+        let files = book.all_files().len();
+        let mut progress = Progress::new("Reading titles", Some(files), self.progress);
+        let (mut titles, warnings) = title::resolve_progress(book, dir, &providers, &mut progress);
+        if !self.raw_titles {
+            titles.values_mut().for_each(|title| *title = title::plain_title(title));
+        }
+        Ok((titles, warnings))
+    }
+
+    /// The README and tag pages of the tag index of an already built chapter tree of the
+    /// notes in `dir`, as `(file, content)`. Empty unless enabled by [`SummaryBuilder::tag_index`].
+    pub fn tag_pages(
+        &self,
+        book: &Chapter,
+        dir: &Path,
+        resolved: &Resolved,
+    ) -> Result<Vec<(String, String)>> {
+        if !self.tag_index {
+            return Ok(vec![]);
+        }
+        let tags = tags::collect(book, dir, &self.slugger, self.max_scan_bytes);
+        if tags.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(tags::pages(&tags, &self.labels.tags, &resolved.titles))
+    }
+
+    /// The README and author pages of the author index of an already built chapter tree
+    /// of the notes in `dir`, as `(file, content)`. Empty unless enabled by
+    /// [`SummaryBuilder::author_index`].
+    pub fn author_pages(
+        &self,
+        book: &Chapter,
+        dir: &Path,
+        resolved: &Resolved,
+    ) -> Result<Vec<(String, String)>> {
+        if !self.author_index {
+            return Ok(vec![]);
+        }
+        let authors = authors::collect(book, dir, &self.slugger);
+        if authors.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(authors::pages(&authors, &self.labels.authors, &resolved.titles))
+    }
+
+    // Metadata of the chapters of `book`, with the titles of renamed top-level chapters
+    // and the owners of the chapters
+    fn chapter_metas(&self, book: &Chapter, dir: &Path) -> ChapterMetas {
+        let mut metas = meta::resolve(book, dir, &self.readme_names, self.max_scan_bytes);
+        if let Some(codeowners) = self.codeowners.then(|| CodeOwners::find(dir)).flatten() {
+            owners::annotate(book, &codeowners, &self.readme_names, &mut metas);
+        }
+        for (name, title) in &self.renames {
+            if let Some(c) = book.chapter.iter().find(|c| c.name.to_lowercase() == name.to_lowercase()) {
+                metas.entry(c.name.clone()).or_default().title = Some(title.clone());
+            }
+        }
+        metas
+    }
+
+    /// The `MORE.md` pages listing the overflow of the chapters of an already built chapter
+    /// tree of the notes in `dir`, as `(file, content)`. Empty unless enabled by
+    /// [`SummaryBuilder::max_entries_per_chapter`].
+    pub fn more_pages(
+        &self,
+        book: &Chapter,
+        dir: &Path,
+        resolved: &Resolved,
+    ) -> Result<Vec<(String, String)>> {
+        let max = match self.max_entries_per_chapter {
+            Some(max) => max,
+            None => return Ok(vec![]),
+        };
+        let (mut listed, _) = self.listed(book, dir, &self.format)?;
+        let overflows = overflow::split(&mut listed, max, &self.readme_names);
+        Ok(overflow::pages(&overflows, &self.labels.more, &resolved.titles))
+    }
+
+    /// The `_index.md` of each chapter of an already built chapter tree of the notes in
+    /// `dir`, with the `title` and `weight` of its front matter set to the title and the
+    /// position of the chapter in the summary. For Zola and Hugo, whose menus are ordered
+    /// by these instead of a summary. Returns `(file, content)` of the files which change.
+    pub fn section_indexes(
+        &self,
+        book: &Chapter,
+        dir: &Path,
+        resolved: &Resolved,
+    ) -> Result<Vec<(String, String)>> {
+        let context = RenderContext {
+            sort: &self.sort,
+            titles: &resolved.titles,
+            introduction: None,
+            glossary: None,
+            index: None,
+            labels: &self.labels,
+            slugger: &self.slugger,
+            readme_names: &self.readme_names,
+            meta: &resolved.metas,
+            max_title_len: None,
+            updated: &HashMap::new(),
+            no_readme: None,
+            drafts: &HashSet::new(),
+        };
+        let mut indexes = vec![];
+        section_indexes(book, "", &context, dir, &mut indexes)?;
+        Ok(indexes)
+    }
+
+    /// The A–Z index of an already built chapter tree with its content, `None` unless
+    /// enabled by [`SummaryBuilder::az_index`]
+    pub fn az_index_file(&self, book: &Chapter, resolved: &Resolved) -> Option<(String, String)> {
+        if !self.az_index {
+            return None;
+        }
+        // chapters are listed with the title of their metadata
+        let mut titles = resolved.titles.clone();
+        for (path, meta) in &resolved.metas {
+            if let Some(title) = &meta.title {
+                titles.insert(path.clone(), title.clone());
+            }
+        }
+        let entries = index::collect(book, &titles, &self.readme_names, &self.collation);
+        Some((index::INDEX_FILE.to_string(), index::page(&entries, &self.labels.index)))
+    }
+
+    /// The redirect map of an already built chapter tree of the notes in `dir` with its
+    /// content, `None` unless enabled by [`SummaryBuilder::redirects`]
+    pub fn redirects_file(&self, book: &Chapter, dir: &Path) -> Option<(String, String)> {
+        let file = self.redirects.as_ref()?;
+        let redirects = redirects::collect(book, dir, self.max_scan_bytes);
+        Some((file.clone(), redirects::render(&redirects, file)))
+    }
+
+    /// The search metadata of the markdown files of an already built chapter tree of the
+    /// notes in `dir` with its content, `None` unless enabled by [`SummaryBuilder::search_index`]
+    pub fn search_index_file(
+        &self,
+        book: &Chapter,
+        dir: &Path,
+        resolved: &Resolved,
+    ) -> Result<Option<(String, String)>> {
+        if !self.search_index {
+            return Ok(None);
+        }
+        let mut documents = vec![];
+        let mut warnings = vec![];
+        for file in book.all_files().into_iter().filter(|f| f.to_lowercase().ends_with(".md")) {
+            // the summary is already written, so a page is rather left out than the run failed
+            let content = match read_note(dir, file) {
+                Ok(content) => content,
+                Err(warning) => {
+                    warnings.push(warning);
+                    continue;
+                }
+            };
+            documents.push(Document::new(file, &file_title(&resolved.titles, file), &content));
+        }
+        self.report(warnings)?;
+        Ok(Some((search::SEARCH_INDEX_FILE.to_string(), search::render(&documents))))
+    }
+
+    /// The glossary file of an already built chapter tree of the notes in `dir` with its
+    /// content, `None` unless enabled by [`SummaryBuilder::glossary`] or without terms
+    pub fn glossary_file(&self, book: &Chapter, dir: &Path) -> Option<(String, String)> {
+        let file = self.glossary.as_ref()?;
+        let glossary = glossary::collect(book, dir);
+        if glossary.is_empty() {
+            return None;
+        }
+        Some((file.clone(), glossary::render(&glossary, &self.labels.glossary)))
+    }
+
+    /// The `LANGS.md` and the summary of each language folder of an already built chapter
+    /// tree of the notes in `dir`, as `(file, content)`. Empty unless enabled by
+    /// [`SummaryBuilder::languages`] or without language folders.
+    pub fn language_files(&self, book: &Chapter, dir: &Path) -> Result<Vec<(String, String)>> {
+        if !self.languages {
+            return Ok(vec![]);
+        }
+        let mut langs = "# Languages\n\n".to_string();
+        let mut files = vec![];
+        for c in &book.chapter {
+            if let Some(language) = i18n::language_name(&c.name) {
+                langs += &format!("* [{}]({}/)\n", language, c.name);
+                let summary = self.generate(&dir.join(&c.name))?;
+                files.push((format!("{}/{}", c.name, self.summary_file()), summary));
+            }
+        }
+        if !files.is_empty() {
+            files.insert(0, (migrate::LANGS.to_string(), langs));
+        }
+        Ok(files)
+    }
+
+    /// The files of an already built chapter tree of the notes in `dir` which change by
+    /// the migration, as `(file, content)`. Constructs the target doesn't support are
+    /// printed to stderr. Empty unless enabled by [`SummaryBuilder::migrate`].
+    pub fn migration_files(&self, book: &Chapter, dir: &Path) -> Result<Vec<(String, String)>> {
+        let (files, warnings) = match self.migration {
+            Some(Migration::GitbookToMdbook) => migrate::gitbook_to_mdbook(book, dir),
+            None => return Ok(vec![]),
+        };
+        self.report(warnings)?;
+        Ok(files)
+    }
+
+    /// The files of an already built chapter tree of the notes in `dir` whose backlinks
+    /// section changes, as `(file, content)`. Empty unless enabled by
+    /// [`SummaryBuilder::backlinks`].
+    pub fn backlink_files(
+        &self,
+        book: &Chapter,
+        dir: &Path,
+        resolved: &Resolved,
+    ) -> Result<Vec<(String, String)>> {
+        if !self.backlinks {
+            return Ok(vec![]);
+        }
+        let mut contents = HashMap::new();
+        let mut warnings = vec![];
+        for file in book.all_files() {
+            if !file.to_lowercase().ends_with(".md") {
+                continue;
+            }
+            // written back, so a file that isn't UTF-8 is left as it is
+            match std::fs::read_to_string(dir.join(file)) {
+                Ok(content) => {
+                    contents.insert(file.clone(), content);
+                }
+                Err(why) => warnings.push(Warning::Unreadable(dir.join(file), why.to_string())),
+            }
+        }
+        self.report(warnings)?;
+        let backlinks = backlinks::collect(book, &contents);
+
+        let mut files = contents.iter().collect::<Vec<_>>();
+        files.sort();
+        let mut updated = vec![];
+        for (file, content) in files {
+            let links = backlinks
+                .get(file)
+                .into_iter()
+                .flatten()
+                .map(|source| (file_title(&resolved.titles, source), links::relative(file, source)))
+                .collect::<Vec<_>>();
+            let new = backlinks::update(content, &self.labels.backlinks, &links);
+            if &new != content {
+                updated.push((file.clone(), new));
+            }
+        }
+        Ok(updated)
+    }
+
+    // The chapter tree of the entries listed by `format`, without the introduction
+    // listed first, and the introduction
+    fn listed(&self, book: &Chapter, dir: &Path, format: &Format) -> Result<(Chapter, Option<String>)> {
+        let mut book = book.clone();
+        // assets scanned for another output
+        if !format.renderer().links_assets() {
+            book.retain_files(&|f| {
+                let ext = Path::new(f).extension().and_then(|e| e.to_str());
+                ext.is_some_and(|e| e.eq_ignore_ascii_case("md"))
+            });
+        }
+        let introduction = match &self.introduction {
+            Some(file) if book.remove_file(file) => Some(file.clone()),
+            Some(file) => {
+                return Err(Box::new(SummaryError::Io(
+                    dir.join(file),
+                    "introduction is not part of the notes".to_string(),
+                )))
+            }
+            None if self.root_readme => self
+                .readme_names
+                .iter()
+                .map(|name| name.as_str())
+                .chain(Some("index.md"))
+                .find_map(|name| book.files.iter().find(|f| f.eq_ignore_ascii_case(name)))
+                .cloned()
+                .filter(|file| book.remove_file(file)),
+            None => None,
+        };
+        Ok((book, introduction))
+    }
+
+    fn render_format(
+        &self,
+        book: &Chapter,
+        dir: &Path,
+        resolved: &Resolved,
+        format: &Format,
+    ) -> Result<(String, Vec<Warning>)> {
+        let (mut titles, mut warnings) = (resolved.titles.clone(), resolved.warnings.clone());
+        if !self.checks.is_empty() {
+            let started = Instant::now();
+            warnings.extend(self.lint(book, dir, &titles)?);
+            self.stats(format!("Checked {} files", book.all_files().len()), started);
+        }
+
+        let (mut book, introduction) = self.listed(book, dir, format)?;
+        if let Some(max) = self.max_entries_per_chapter {
+            for (page, _) in overflow::split(&mut book, max, &self.readme_names) {
+                titles.insert(page, self.labels.more.clone());
+            }
+        }
+        if self.tag_index {
+            let tags = tags::collect(&book, dir, &self.slugger, self.max_scan_bytes);
+            if !tags.is_empty() {
+                titles.insert(tags::TAG_DIR.to_string(), self.labels.tags.clone());
+                for (slug, tag) in &tags {
+                    titles.insert(tags::page(slug), tag.name.clone());
+                }
+                book.chapter.push(tags::chapter(&tags));
+            }
+        }
+        if self.author_index {
+            let authors = authors::collect(&book, dir, &self.slugger);
+            if !authors.is_empty() {
+                titles.insert(authors::AUTHOR_DIR.to_string(), self.labels.authors.clone());
+                for (slug, author) in &authors {
+                    titles.insert(authors::page(slug), author.name.clone());
+                }
+                book.chapter.push(authors::chapter(&authors));
+            }
+        }
+
+        let updated = match &self.timestamps {
+            Some(format) => meta::last_updated(&book, dir, format),
+            None => HashMap::new(),
+        };
+        let drafts = match self.draft_stubs {
+            Some(min_size) => book
+                .all_files()
+                .into_iter()
+                .filter(|f| f.to_lowercase().ends_with(".md"))
+                .filter(|f| {
+                    let content = frontmatter::read_head(&dir.join(f), self.max_scan_bytes);
+                    content.is_ok_and(|c| lint::is_stub(&c, min_size))
+                })
+                .cloned()
+                .collect(),
+            None => HashSet::new(),
+        };
+        let glossary = self.glossary_file(&book, dir).map(|(file, _)| file).or_else(|| {
+            let migrated = self.migration == Some(Migration::GitbookToMdbook);
+            (migrated && dir.join(migrate::GLOSSARY).is_file()).then(|| migrate::GLOSSARY.to_string())
+        });
+        let context = RenderContext {
+            sort: &self.sort,
+            titles: &titles,
+            introduction: introduction.as_deref(),
+            glossary: glossary.as_deref(),
+            index: self.az_index.then_some(index::INDEX_FILE),
+            labels: &self.labels,
+            slugger: &self.slugger,
+            readme_names: &self.readme_names,
+            meta: &resolved.metas,
+            max_title_len: self.max_title_len,
+            updated: &updated,
+            no_readme: self.no_readme_style,
+            drafts: &drafts,
+        };
+        warnings.extend(warning::duplicate_titles(&book, &context));
+        let started = Instant::now();
+        let mut summary = format.renderer().render(&book, &context);
+        if let Some(max) = self.max_part_entries {
+            if format.name() == Format::mdbook().name() {
+                summary = render::split_parts(&summary, max, &self.labels.continued, self.no_readme_style);
+            } else if self.verbose > 0 {
+                eprintln!("Format {} has no parts, not splitting it", format);
+            }
+        }
+        if format.renderer().markdown_list() {
+            summary = render::style_links(&summary, self.link_style);
+            if let Some(suffix) = &self.link_suffix {
+                summary = render::replace_extension(&summary, suffix);
+            }
+            if self.compact || self.chapter_spacing.is_some() {
+                summary = render::space_lines(&summary, self.compact, self.chapter_spacing);
+            }
+        }
+        if self.renumbering && format.name() == Format::mdbook().name() {
+            let file = self.output_files().find(|(_, f)| f.name() == format.name()).map(|(file, _)| file);
+            if let Some(previous) = file.and_then(|file| std::fs::read_to_string(dir.join(file)).ok()) {
+                warnings.extend(warning::renumbered(&previous, &summary));
+            }
+        }
+        let entries = count_entries(&summary);
+        self.stats(format!("Rendered {} with {} entries", format, entries), started);
+        Ok((summary, warnings))
+    }
+
+    // Findings of the checks in the markdown files of `book`
+    fn lint(&self, book: &Chapter, dir: &Path, titles: &Titles) -> Result<Vec<Warning>> {
+        let mut warnings = vec![];
+        let mut read = vec![];
+        for file in book.all_files().into_iter().filter(|f| f.to_lowercase().ends_with(".md")) {
+            let content = match read_note(dir, file) {
+                Ok(content) => content,
+                Err(warning) => {
+                    warnings.push(warning);
+                    continue;
+                }
+            };
+            read.push((file, file_title(titles, file), content));
+        }
+        let notes = read
+            .iter()
+            .map(|(path, title, content)| Note { path, title, content })
+            .collect::<Vec<_>>();
+        for note in &notes {
+            for check in &self.checks {
+                warnings.extend(check.check(note));
+            }
+        }
+        for check in &self.checks {
+            warnings.extend(check.check_all(&notes));
+        }
+        Ok(warnings)
+    }
+
+    // Counts and duration of a phase, for tuning large notes dirs (-vv)
+    fn stats(&self, done: String, started: Instant) {
+        if self.verbose > 1 {
+            eprintln!("{} in {:.1?}", done, started.elapsed());
+        }
+    }
+
+    // Strict mode fails on any warning, otherwise they are only printed
+    fn report(&self, warnings: Vec<Warning>) -> Result<()> {
+        self.reported.lock().unwrap().extend(warnings.iter().cloned());
+        if self.strict && !warnings.is_empty() {
+            return Err(Box::new(SummaryError::Strict(warnings)));
+        }
+        for warning in warnings {
+            eprintln!("Warning: {}", warning);
+        }
+        Ok(())
+    }
+
+    /// The warnings reported so far, those failing in strict mode included, e.g. for a
+    /// report of the run
+    pub fn reported(&self) -> Vec<Warning> {
+        self.reported.lock().unwrap().clone()
+    }
+
+    /// Scan `dir` and return the content of the summary file
+    pub fn generate(&self, dir: &Path) -> Result<String> {
+        let book = self.build(dir)?;
+        self.render(&book, dir, &self.resolve(&book, dir)?)
+    }
+}
+
+// Content of the note `file` of `dir` with invalid UTF-8 replaced, like a Latin-1
+// export. A file which can't be read is a warning.
+fn read_note(dir: &Path, file: &str) -> std::result::Result<String, Warning> {
+    frontmatter::read_head(&dir.join(file), None)
+        .map_err(|why| Warning::Unreadable(dir.join(file), why.to_string()))
+}
+
+fn section_indexes(
+    chapter: &Chapter,
+    path: &str,
+    context: &RenderContext,
+    dir: &Path,
+    indexes: &mut Vec<(String, String)>,
+) -> Result<()> {
+    for (i, c) in context.chapters(chapter, path).into_iter().enumerate() {
+        let sub = match path {
+            "" => c.name.clone(),
+            _ => format!("{}/{}", path, c.name),
+        };
+        let file = format!("{}/{}", sub, SECTION_INDEX);
+        let existing = match std::fs::read_to_string(dir.join(&file)) {
+            Ok(content) => Some(content),
+            Err(why) if why.kind() == std::io::ErrorKind::NotFound => None,
+            Err(why) => return Err(Box::new(SummaryError::Io(dir.join(&file), why.to_string()))),
+        };
+        let title = match context.meta.get(&sub).and_then(|m| m.title.clone()) {
+            Some(title) => title,
+            None => context.title(&sub, &c.name),
+        };
+        let content = existing.clone().unwrap_or_default();
+        let content = frontmatter::set(&content, "title", &serde_json::to_string(&title).unwrap());
+        let content = frontmatter::set(&content, "weight", &(i + 1).to_string());
+        if existing.as_ref() != Some(&content) {
+            indexes.push((file, content));
+        }
+        section_indexes(c, &sub, context, dir, indexes)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn get_dir(
+    dir: &Path,
+    filters: &[&dyn EntryFilter],
+    collation: &Collation,
+    verbose: u8,
+    progress: bool,
+) -> Result<(Vec<String>, Vec<Warning>)> {
+    let mut progress = Progress::new("Scanning", None, progress);
+    let mut entries: Vec<String> = vec![];
+    let mut warnings = vec![];
+    let mut non_utf8 = vec![];
+    let mut dirs = vec![];
+    let collation = collation.clone();
+    let root = walk_root(dir);
+    let walk = WalkDir::new(&root)
+        .sort_by(move |a, b| {
+            // the same order for names written as NFD (macOS) or NFC
+            let (a, b) = (a.file_name().to_string_lossy(), b.file_name().to_string_lossy());
+            collation.compare(&nfc(&a), &nfc(&b)).then_with(|| a.cmp(&b))
+        })
+        .into_iter()
+        .filter_entry(|e| {
+            let path = match entry_path(e.path(), &root) {
+                Some(path) => path,
+                None => {
+                    non_utf8.push(Warning::NonUtf8Name(display_path(e.path())));
+                    return false;
+                }
+            };
+            let path = path.as_str();
+            // never skip the given root folder
+            if path.is_empty() {
+                return true;
+            }
+            match filter::skip(filters, &Entry { path, direntry: e }) {
+                Some((name, reason)) => {
+                    if verbose > 1 {
+                        eprintln!("Skipped {}: {} ({})", path, reason, name);
+                    }
+                    false
+                }
+                None => true,
+            }
+        });
+    for direntry in walk {
+        progress.inc();
+        let direntry = match direntry {
+            Ok(direntry) => direntry,
+            Err(err) => {
+                let path = display_path(err.path().unwrap_or(dir));
+                warnings.push(Warning::Unreadable(path, err.to_string()));
+                continue;
+            }
+        };
+        let entry = entry_path(direntry.path(), &root).unwrap();
+        // link to the NFC name, if the file system finds the entry by it as well
+        let entry = match nfc(&entry) {
+            Cow::Owned(normalized) if root.join(&normalized).exists() => normalized,
+            _ => entry,
+        };
+        if direntry.file_type().is_dir() {
+            if !entry.is_empty() {
+                dirs.push(entry);
+            }
+        } else {
+            entries.push(entry);
+        }
+    }
+
+    warnings.extend(non_utf8);
+    for d in dirs {
+        let prefix = format!("{}/", d);
+        if !entries.iter().any(|e| e.starts_with(&prefix)) {
+            warnings.push(Warning::EmptyChapter(d));
+        }
+    }
+    Ok((entries, warnings))
+}
+
+// The files of `list` in `dir` which pass the filters, as do their dirs. Missing files
+// and paths outside of `dir` are warned about.
+fn get_list(
+    dir: &Path,
+    list: &[String],
+    filters: &[&dyn EntryFilter],
+    verbose: u8,
+) -> (Vec<String>, Vec<Warning>) {
+    let root = walk_root(dir);
+    let mut entries: Vec<String> = vec![];
+    let mut warnings = vec![];
+    let mut skipped: HashMap<String, bool> = HashMap::new();
+    for path in list.iter().map(|p| p.trim()).filter(|p| !p.is_empty()) {
+        let path = Path::new(path);
+        let outside = path.components().any(|c| c == Component::ParentDir)
+            || (path.is_absolute() && !path.starts_with(&root));
+        let entry = match entry_path(path, &root) {
+            Some(_) if outside => {
+                warnings.push(Warning::Unreadable(path.to_path_buf(), "outside of the notes dir".to_string()));
+                continue;
+            }
+            Some(entry) => entry,
+            None => {
+                warnings.push(Warning::NonUtf8Name(display_path(path)));
+                continue;
+            }
+        };
+        // the dirs of the entry first, then the entry itself
+        let mut skip = false;
+        for (i, _) in entry.match_indices('/').chain(Some((entry.len(), ""))) {
+            let sub = &entry[..i];
+            skip = *skipped.entry(sub.to_string()).or_insert_with(|| {
+                let direntry = match WalkDir::new(root.join(sub)).max_depth(0).into_iter().next() {
+                    Some(Ok(direntry)) => direntry,
+                    Some(Err(err)) => {
+                        warnings.push(Warning::Unreadable(PathBuf::from(sub), err.to_string()));
+                        return true;
+                    }
+                    None => return true,
+                };
+                match filter::skip(filters, &Entry { path: sub, direntry: &direntry }) {
+                    Some((name, reason)) => {
+                        if verbose > 1 {
+                            eprintln!("Skipped {}: {} ({})", sub, reason, name);
+                        }
+                        true
+                    }
+                    None => false,
+                }
+            });
+            if skip {
+                break;
+            }
+        }
+        if !skip && !root.join(&entry).is_dir() {
+            entries.push(entry);
+        }
+    }
+    (entries, warnings)
+}
+
+// The same entries for any spelling of `dir` (`notes`, `./notes/`, absolute). On
+// Windows this is an extended-length path (`\\?\C:\...`), which lifts the limit
+// of 260 chars.
+fn walk_root(dir: &Path) -> PathBuf {
+    dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf())
+}
+
+// Path of `path` relative to `root` as used in links, with `/` separators on any
+// platform. `None` if not valid UTF-8.
+fn entry_path(path: &Path, root: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let names = relative
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => Some(name.to_str()),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(names.join("/"))
+}
+
+// `path` without the extended-length prefix, as users know it: `\\?\C:\notes` is
+// shown as `C:\notes` and `\\?\UNC\server\share` as `\\server\share`
+pub(crate) fn display_path(path: &Path) -> PathBuf {
+    let s = path.to_string_lossy();
+    if let Some(share) = s.strip_prefix(r"\\?\UNC\") {
+        PathBuf::from(format!(r"\\{}", share))
+    } else if let Some(path) = s.strip_prefix(r"\\?\") {
+        PathBuf::from(path)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::fixture;
+    use std::path::PathBuf;
+
+    // # get file list: no hidden files, filepaths from given folder as root
+    #[test]
+    fn get_file_list_test() {
+        let expected = Ok((vec![
+            "about.md".to_string(),
+            "chapter1/FILE.md".to_string(),
+            "chapter1/file1.md".to_string(),
+            "chapter2/FILE1.md".to_string(),
+            "chapter2/README.md".to_string(),
+            "chapter2/file2.md".to_string(),
+            "chapter2/subchap/info.md".to_string(),
+            "chapter3/file1.md".to_string(),
+            "chapter3/file2.md".to_string(),
+            "chapter3/file3.md".to_string(),
+        ], vec![]));
+        assert_eq!(
+            expected,
+            get_dir(
+                &PathBuf::from(r"./examples/gitbook/book"),
+                &[
+                    &Hidden,
+                    &OutputFile("SUMMARY.md".to_string()),
+                    &RootReadme(default_readme_names()),
+                    &Extensions(vec!["md".to_string()])
+                ],
+                &Collation::Codepoint,
+                0,
+                false
+            )
+        );
+    }
+
+    #[test]
+    fn entry_path_test() {
+        let root = Path::new("notes");
+        assert_eq!(Some("a/b.md".to_string()), entry_path(&root.join("a").join("b.md"), root));
+        assert_eq!(Some("".to_string()), entry_path(root, root));
+    }
+
+    #[test]
+    fn display_path_test() {
+        assert_eq!(PathBuf::from(r"C:\notes"), display_path(Path::new(r"\\?\C:\notes")));
+        assert_eq!(
+            PathBuf::from(r"\\server\share\notes"),
+            display_path(Path::new(r"\\?\UNC\server\share\notes"))
+        );
+        assert_eq!(PathBuf::from("notes"), display_path(Path::new("notes")));
+    }
+
+    #[test]
+    fn builder_generate_test() {
+        let expected = r#"# Book
+
+* [About](about.md)
+* Chapter1
+    * [File](chapter1/FILE.md)
+* [Chapter2](chapter2/README.md)
+    * [File1](chapter2/FILE1.md)
+    * [File2](chapter2/file2.md)
+    * Subchap
+        * [Info](chapter2/subchap/info.md)
+"#;
+        let summary = SummaryBuilder::new()
+            .title("Book")
+            .format(Format::gitbook())
+            .exclude("chapter1/file1.md")
+            .exclude("chapter3/")
+            .generate(Path::new("./examples/gitbook/book"));
+
+        assert_eq!(Ok(expected.to_string()), summary);
+    }
+
+    #[test]
+    fn builder_include_assets_test() {
+        let dir = fixture(&[
+            ("intro.md", ""),
+            ("policies/signed.pdf", ""),
+            ("policies/notes.txt", ""),
+        ]);
+
+        let builder = SummaryBuilder::new().include_assets(vec!["pdf".to_string()]);
+        let git = builder.format(Format::gitbook()).generate(&dir);
+        let md = SummaryBuilder::new()
+            .include_assets(vec!["pdf".to_string()])
+            .generate(&dir);
+
+        assert_eq!(
+            Ok("# Summary\n\n* [Intro](intro.md)\n* Policies\n    * [Signed](policies/signed.pdf)\n".to_string()),
+            git
+        );
+        assert_eq!(Ok("# Summary\n\n- [Intro](intro.md)\n".to_string()), md);
+    }
+
+    #[test]
+    fn builder_outputs_test() {
+        let dir = fixture(&[
+            ("intro.md", ""),
+            ("SUMMARY.md", ""),
+            ("_sidebar.md", ""),
+            ("policies/signed.pdf", ""),
+        ]);
+
+        let builder = SummaryBuilder::new()
+            .include_assets(vec!["pdf".to_string()])
+            .output("_sidebar.md", Format::gitbook());
+        let outputs = builder
+            .build(&dir)
+            .and_then(|book| builder.render_outputs(&book, &dir, &builder.resolve(&book, &dir)?));
+
+        assert_eq!(
+            Ok(vec![
+                ("SUMMARY.md".to_string(), "# Summary\n\n- [Intro](intro.md)\n".to_string()),
+                (
+                    "_sidebar.md".to_string(),
+                    "# Summary\n\n* [Intro](intro.md)\n* Policies\n    * [Signed](policies/signed.pdf)\n"
+                        .to_string()
+                ),
+            ]),
+            outputs
+        );
+    }
+
+    #[test]
+    fn builder_default_output_test() {
+        let dir = fixture(&[("intro.md", "")]);
+
+        let builder = SummaryBuilder::new().format("docsify".parse().unwrap());
+        let outputs = builder
+            .build(&dir)
+            .and_then(|book| builder.render_outputs(&book, &dir, &builder.resolve(&book, &dir)?));
+
+        let sidebar = "- [Intro](intro.md)\n".to_string();
+        assert_eq!(Ok(vec![("_sidebar.md".to_string(), sidebar)]), outputs);
+    }
+
+    #[test]
+    fn builder_include_root_readme_test() {
+        let summary = SummaryBuilder::new()
+            .include_root_readme(true)
+            .exclude("chapter1")
+            .exclude("chapter2")
+            .exclude("chapter3")
+            .generate(Path::new("./examples/gitbook/book"));
+
+        assert_eq!(
+            Ok("# Summary\n\n[Introduction](README.md)\n\n- [About](about.md)\n".to_string()),
+            summary
+        );
+    }
+
+    #[test]
+    fn builder_introduction_test() {
+        let summary = SummaryBuilder::new()
+            .introduction("chapter2/file2.md")
+            .exclude("chapter1")
+            .exclude("chapter3")
+            .generate(Path::new("./examples/gitbook/book"));
+
+        assert_eq!(
+            Ok(r#"# Summary
+
+[Introduction](chapter2/file2.md)
+
+- [About](about.md)
+- [Chapter2](chapter2/README.md)
+    - [File1](chapter2/FILE1.md)
+    - [Subchap](#)
+        - [Info](chapter2/subchap/info.md)
+"#
+            .to_string()),
+            summary
+        );
+
+        assert!(SummaryBuilder::new()
+            .introduction("missing.md")
+            .generate(Path::new("./examples/gitbook/book"))
+            .is_err());
+    }
+
+    #[test]
+    fn builder_labels_test() {
+        let summary = SummaryBuilder::new()
+            .labels("de".parse().unwrap())
+            .include_root_readme(true)
+            .exclude("chapter1")
+            .exclude("chapter2")
+            .exclude("chapter3")
+            .generate(Path::new("./examples/gitbook/book"));
+
+        assert_eq!(
+            Ok("# Inhalt\n\n[Einführung](README.md)\n\n- [About](about.md)\n".to_string()),
+            summary
+        );
+    }
+
+    #[test]
+    fn builder_draft_stubs_test() {
+        let dir = fixture(&[
+            ("guide/setup.md", "# Setup\n\nRun `cargo install book-summary`.\n"),
+            ("guide/later.md", "---\nowner: docs\n---\n# Later\n"),
+            ("guide/todo.md", "# Todo\n\nTBD\n"),
+        ]);
+        std::fs::write(dir.join("guide/latin.md"), b"# Caf\xe9\n").unwrap();
+
+        let summary = SummaryBuilder::new().draft_stubs(20).generate(&dir);
+
+        assert_eq!(
+            "# Summary\n\n- [Guide](#)\n    - [Later]()\n    - [Latin]()\n    - [Setup](guide/setup.md)\n    \
+             - [Todo]()\n",
+            summary.unwrap()
+        );
+    }
+
+    #[test]
+    fn builder_strict_test() {
+        let dir = fixture(&[("chapter/my-file.md", ""), ("chapter/my_file.md", "")]);
+        std::fs::create_dir_all(dir.join("empty")).unwrap();
+
+        let lenient = SummaryBuilder::new().generate(&dir);
+        let strict = SummaryBuilder::new().strict(true).generate(&dir);
+
+        assert!(lenient.is_ok());
+        assert_eq!(
+            Err(Box::new(SummaryError::Strict(vec![Warning::EmptyChapter(
+                "empty".to_string()
+            )]))),
+            strict
+        );
+
+        let (book, _) = SummaryBuilder::new()
+            .scan(Path::new("./examples/gitbook/book"))
+            .unwrap();
+        let book = Chapter {
+            files: vec!["a/my-file.md".to_string(), "a/my_file.md".to_string()],
+            ..book
+        };
+        let (builder, dir) = (SummaryBuilder::new(), Path::new("./examples/gitbook/book"));
+        let resolved = builder.resolve(&book, dir).unwrap();
+        let (_, warnings) = builder.render_checked(&book, dir, &resolved).unwrap();
+        assert_eq!(
+            vec![Warning::DuplicateTitle(
+                "My File".to_string(),
+                vec!["a/my-file.md".to_string(), "a/my_file.md".to_string()]
+            )],
+            warnings
+        );
+    }
+
+    #[test]
+    fn builder_from_list_test() {
+        let dir = fixture(&[]);
+        std::fs::create_dir_all(dir.join("guide")).unwrap();
+        std::fs::create_dir_all(dir.join("drafts")).unwrap();
+        for file in &["about.md", "guide/setup.md", "guide/build.md", "drafts/idea.md", "SUMMARY.md"] {
+            std::fs::write(dir.join(file), "").unwrap();
+        }
+        let list = ["guide/setup.md", "./about.md", "drafts/idea.md", "SUMMARY.md", "guide/setup.md", "missing.md"];
+
+        let builder = SummaryBuilder::new().exclude("drafts").from_list(list.iter().copied());
+        let (book, warnings) = builder.scan(&dir).unwrap();
+
+        assert_eq!(
+            "# Summary\n\n- [About](about.md)\n- [Guide](#)\n    - [Setup](guide/setup.md)\n",
+            book.get_summary_file(&Format::mdbook(), &None)
+        );
+        assert!(matches!(&warnings[..], [Warning::Unreadable(path, _)] if path == Path::new("missing.md")));
+    }
+
+    #[test]
+    fn builder_private_test() {
+        let dir = fixture(&[]);
+        for chapter in &["public", "private", "unpublished", "marked"] {
+            std::fs::create_dir_all(dir.join(chapter)).unwrap();
+            std::fs::write(dir.join(chapter).join("file.md"), "").unwrap();
+        }
+        std::fs::write(dir.join("private/README.md"), "---\nprivate: true\n---\n").unwrap();
+        std::fs::write(dir.join("unpublished/readme.md"), "---\npublish: false\n---\n").unwrap();
+        std::fs::write(dir.join("marked/.private"), "").unwrap();
+        std::fs::create_dir_all(dir.join("public/archive")).unwrap();
+        std::fs::write(dir.join("public/archive/old.md"), "").unwrap();
+        std::fs::write(dir.join("public/archive/.nosummary"), "").unwrap();
+
+        let summary = SummaryBuilder::new().generate(&dir);
+
+        assert_eq!(
+            Ok("# Summary\n\n- [Public](#)\n    - [File](public/file.md)\n".to_string()),
+            summary
+        );
+    }
+
+    #[test]
+    fn builder_unicode_normalization_test() {
+        let dir = fixture(&[("Cafe\u{301}/a.md", ""), ("Café/b.md", "")]);
+
+        let summary = SummaryBuilder::new().generate(&dir);
+
+        // both are one chapter, the links go to the names found on disk
+        assert_eq!(
+            Ok("# Summary\n\n- [Café](#)\n    - [A](Cafe\u{301}/a.md)\n    - [B](Café/b.md)\n".to_string()),
+            summary
+        );
+    }
+
+    #[test]
+    fn builder_notesdir_spelling_test() {
+        let dir = fixture(&[("chapter/file.md", ""), ("SUMMARY.md", "")]);
+
+        let builder = SummaryBuilder::new().outputfile("./SUMMARY.md");
+        let summaries = [
+            builder.generate(&dir),
+            builder.generate(&dir.join("")),
+            builder.generate(&dir.join(".")),
+            builder.generate(&dir.join("chapter").join("..")),
+        ];
+
+        for summary in summaries {
+            assert_eq!(
+                Ok("# Summary\n\n- [Chapter](#)\n    - [File](chapter/file.md)\n".to_string()),
+                summary
+            );
+        }
+    }
+
+    #[test]
+    fn builder_outputfile_test() {
+        let dir = fixture(&[
+            ("chapter/file.md", ""),
+            ("chapter/toc.md", ""),
+            ("Toc.md", ""),
+            ("summary.md", ""),
+            ("_sidebar.md", ""),
+        ]);
+
+        let summary = SummaryBuilder::new().outputfile("TOC.md").generate(&dir);
+
+        assert_eq!(
+            Ok("# Summary\n\n- [Chapter](#)\n    - [File](chapter/file.md)\n".to_string()),
+            summary
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn builder_unreadable_test() {
+        let dir = fixture(&[("file.md", "# File")]);
+        std::os::unix::fs::symlink(dir.join("missing.md"), dir.join("broken.md")).unwrap();
+
+        let builder = SummaryBuilder::new().title_from(vec![TitleSource::Header, TitleSource::Filename]);
+        let rendered = builder
+            .scan(&dir)
+            .and_then(|(book, _)| builder.render_checked(&book, &dir, &builder.resolve(&book, &dir)?));
+
+        let (summary, warnings) = rendered.unwrap();
+        assert_eq!("# Summary\n\n- [Broken](broken.md)\n- [File](file.md)\n", summary);
+        assert!(matches!(&warnings[..], [Warning::Unreadable(path, _)] if path.ends_with("broken.md")));
+    }
+
+    #[test]
+    fn builder_nav_exclude_test() {
+        let dir = fixture(&[
+            ("a.md", "---\nnav_exclude: true\n---\n"),
+            ("b.md", "---\nsummary: false\n---\n"),
+            ("c.md", "---\nsummary: true\n---\n"),
+        ]);
+
+        let summary = SummaryBuilder::new().generate(&dir);
+
+        assert_eq!(Ok("# Summary\n\n- [C](c.md)\n".to_string()), summary);
+    }
+
+    #[test]
+    fn builder_root_readme_title_test() {
+        let dir = fixture(&[("readme.md", "---\ntitle: Other\n---\n# My Notes\n"), ("a.md", "")]);
+
+        let summary = SummaryBuilder::new().generate(&dir);
+        let titled = SummaryBuilder::new().title("Book").generate(&dir);
+
+        assert_eq!(Ok("# My Notes\n\n- [A](a.md)\n".to_string()), summary);
+        assert_eq!(Ok("# Book\n\n- [A](a.md)\n".to_string()), titled);
+    }
+
+    #[test]
+    fn builder_title_style_test() {
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &["getting_started/README.md".to_string(), "getting_started/first-steps.md".to_string()],
+        );
+        let builder = SummaryBuilder::new()
+            .title_from(vec![TitleSource::Header])
+            .title_style(TitleStyle::Sentence);
+        let resolved = builder.resolve(&book, Path::new(".")).unwrap();
+        let summary = builder.render(&book, Path::new("."), &resolved);
+
+        assert_eq!(
+            Ok("# Summary\n\n- [Getting started](getting_started/README.md)\n    \
+                - [First steps](getting_started/first-steps.md)\n"
+                .to_string()),
+            summary
+        );
+    }
+
+    #[test]
+    fn builder_rename_test() {
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &["part1/README.md".to_string(), "part1/a.md".to_string(), "part2/b.md".to_string()],
+        );
+        let builder = SummaryBuilder::new()
+            .rename("Part1", "Getting started")
+            .rename("part3", "Missing");
+        let resolved = builder.resolve(&book, Path::new(".")).unwrap();
+        let summary = builder.render(&book, Path::new("."), &resolved);
+
+        assert_eq!(
+            Ok("# Summary\n\n- [Getting started](part1/README.md)\n    - [A](part1/a.md)\n\
+                - [Part2](#)\n    - [B](part2/b.md)\n"
+                .to_string()),
+            summary
+        );
+    }
+
+    #[test]
+    fn builder_merge_test() {
+        let dir = fixture(&[("notes/rust/a.md", ""), ("archive/rust-old/b.md", "")]);
+
+        let summary = SummaryBuilder::new().merge("archive/rust-old", "notes/rust").generate(&dir);
+
+        assert_eq!(
+            Ok("# Summary\n\n- [Notes](#)\n    - [Rust](#)\n        - [A](notes/rust/a.md)\n        \
+                - [B](archive/rust-old/b.md)\n"
+                .to_string()),
+            summary
+        );
+    }
+
+    #[test]
+    fn builder_status_test() {
+        let dir = fixture(&[
+            ("a.md", "---\nstatus: draft\n---\n"),
+            ("b.md", "---\nstatus: Published\n---\n"),
+            ("c.md", "# No status\n"),
+        ]);
+
+        let summary = SummaryBuilder::new()
+            .status(vec!["published".to_string(), "review".to_string()])
+            .generate(&dir);
+
+        assert_eq!(Ok("# Summary\n\n- [B](b.md)\n- [C](c.md)\n".to_string()), summary);
+    }
+
+    #[test]
+    fn builder_max_entries_per_chapter_test() {
+        let dir = fixture(&[
+            ("guide/README.md", ""),
+            ("guide/a.md", ""),
+            ("guide/b.md", ""),
+            ("guide/c.md", ""),
+            ("guide/MORE.md", ""),
+        ]);
+
+        let builder = SummaryBuilder::new().max_entries_per_chapter(2);
+        let book = builder.build(&dir).unwrap();
+        let resolved = builder.resolve(&book, &dir).unwrap();
+        let summary = builder.render(&book, &dir, &resolved);
+        let pages = builder.more_pages(&book, &dir, &resolved);
+
+        assert_eq!(
+            Ok("# Summary\n\n- [Guide](guide/README.md)\n    - [A](guide/a.md)\n    - [B](guide/b.md)\n    \
+                - [More…](guide/MORE.md)\n"
+                .to_string()),
+            summary
+        );
+        assert_eq!(Ok(vec![("guide/MORE.md".to_string(), "# More…\n\n- [C](c.md)\n".to_string())]), pages);
+    }
+
+    #[test]
+    fn builder_sort_mode_test() {
+        let dir = fixture(&[
+            ("blog/a.md", "---\npublished: 2020-01-02\n---\n"),
+            ("blog/b.md", "---\npublished: 2021-05-01 10:00\n---\n"),
+            ("blog/c.md", "---\npublished: 01.01.2019\n---\n"),
+        ]);
+
+        let summary = SummaryBuilder::new()
+            .sort_mode(SortMode::FrontMatterDate)
+            .date_key("published")
+            .generate(&dir);
+
+        // c.md has no date in a known format, its modification time is the newest
+        assert_eq!(
+            Ok("# Summary\n\n- [Blog](#)\n    - [C](blog/c.md)\n    - [B](blog/b.md)\n    - [A](blog/a.md)\n"
+                .to_string()),
+            summary
+        );
+    }
+
+    #[test]
+    fn builder_tag_index_test() {
+        let dir = fixture(&[
+            ("about.md", "---\ntags: [rust]\n---\n"),
+            ("setup.sh.md", "---\ntags: [rust]\n---\n"),
+            ("tags/old.md", "# Old tag page\n"),
+        ]);
+
+        // titles of notes without a title from the sources are named with the dots as well
+        let builder =
+            SummaryBuilder::new().tag_index(true).title_from(vec![TitleSource::Header]).dots(Dots::Strip);
+        let book = builder.build(&dir).unwrap();
+        let resolved = builder.resolve(&book, &dir).unwrap();
+        let summary = builder.render(&book, &dir, &resolved);
+        let pages = builder.tag_pages(&book, &dir, &resolved).unwrap();
+
+        assert_eq!(
+            Ok("# Summary\n\n- [About](about.md)\n- [Setup](setup.sh.md)\n- [Tags](tags/README.md)\n    \
+                - [rust](tags/rust.md)\n"
+                .to_string()),
+            summary
+        );
+        assert_eq!(
+            vec!["tags/README.md", "tags/rust.md"],
+            pages.iter().map(|(file, _)| file.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!("# rust\n\n- [About](../about.md)\n- [Setup](../setup.sh.md)\n", pages[1].1);
+    }
+
+    #[test]
+    fn builder_backlinks_test() {
+        let dir = fixture(&[
+            ("about.md", "# About\n\nSee [setup](guide/setup.md).\n"),
+            ("guide/setup.md", "# Setup\n"),
+        ]);
+
+        let builder = SummaryBuilder::new().backlinks(true);
+        let book = builder.build(&dir).unwrap();
+        let resolved = builder.resolve(&book, &dir).unwrap();
+        let files = builder.backlink_files(&book, &dir, &resolved).unwrap();
+
+        assert_eq!(
+            vec![(
+                "guide/setup.md".to_string(),
+                "# Setup\n\n<!-- backlinks -->\n## Backlinks\n\n- [About](../about.md)\n<!-- /backlinks -->\n"
+                    .to_string()
+            )],
+            files
+        );
+    }
+
+    #[test]
+    fn builder_languages_test() {
+        let dir = fixture(&[]);
+        for lang in ["en", "pt-br", "api"] {
+            std::fs::create_dir_all(dir.join(lang)).unwrap();
+            std::fs::write(dir.join(lang).join("intro.md"), "").unwrap();
+        }
+
+        let builder = SummaryBuilder::new().format(Format::gitbook()).languages(true);
+        let files = builder.build(&dir).and_then(|book| builder.language_files(&book, &dir));
+
+        assert_eq!(
+            vec![
+                (
+                    "LANGS.md".to_string(),
+                    "# Languages\n\n* [English](en/)\n* [Português (BR)](pt-br/)\n".to_string()
+                ),
+                ("en/SUMMARY.md".to_string(), "# Summary\n\n* [Intro](intro.md)\n".to_string()),
+                ("pt-br/SUMMARY.md".to_string(), "# Summary\n\n* [Intro](intro.md)\n".to_string()),
+            ],
+            files.unwrap()
+        );
+    }
+
+    #[test]
+    fn builder_migrate_test() {
+        let dir = fixture(&[
+            ("GLOSSARY.md", "## API\n\nApplication programming interface\n"),
+            ("LANGS.md", "* [English](en/)\n"),
+            ("guide/README.md", "# Guide\n"),
+            ("a.md", "See [the guide](guide/).\n{% hint %}\n"),
+        ]);
+
+        let builder = SummaryBuilder::new().migrate(Migration::GitbookToMdbook);
+        let summary = builder.generate(&dir);
+        let files = builder.build(&dir).and_then(|book| builder.migration_files(&book, &dir));
+        let builder = builder.strict(true);
+        let strict = builder.build(&dir).and_then(|book| builder.migration_files(&book, &dir));
+
+        assert_eq!(
+            Ok("# Summary\n\n- [A](a.md)\n- [Guide](guide/README.md)\n\n[Glossary](GLOSSARY.md)\n"
+                .to_string()),
+            summary
+        );
+        assert_eq!(
+            vec![("a.md".to_string(), "See [the guide](guide/README.md).\n{% hint %}\n".to_string())],
+            files.unwrap()
+        );
+        match *strict.unwrap_err() {
+            SummaryError::Strict(warnings) => assert_eq!(
+                vec![
+                    Warning::Unsupported("LANGS.md".to_string(), "languages".to_string()),
+                    Warning::Unsupported("a.md".to_string(), "{% hint %}".to_string()),
+                ],
+                warnings
+            ),
+            err => panic!("unexpected {}", err),
+        }
+    }
+
+    #[test]
+    fn builder_glossary_test() {
+        let dir = fixture(&[
+            ("about.md", "API\n: Application programming interface\n"),
+            ("GLOSSARY.md", "# Glossary\n"),
+        ]);
+
+        let builder = SummaryBuilder::new().format(Format::gitbook()).glossary("GLOSSARY.md");
+        let book = builder.build(&dir).unwrap();
+        let resolved = builder.resolve(&book, &dir).unwrap();
+        let summary = builder.render(&book, &dir, &resolved);
+        let glossary = builder.glossary_file(&book, &dir);
+
+        assert_eq!(
+            Ok("# Summary\n\n* [About](about.md)\n* [Glossary](GLOSSARY.md)\n".to_string()),
+            summary
+        );
+        assert_eq!(
+            Some((
+                "GLOSSARY.md".to_string(),
+                "# Glossary\n\n## API\n\nApplication programming interface\n".to_string()
+            )),
+            glossary
+        );
+    }
+
+    #[test]
+    fn builder_section_indexes_test() {
+        let dir = fixture(&[
+            ("a_intro/start.md", "# Start\n"),
+            ("b_guide/_index.md", "+++\ntitle = \"Guide\"\n+++\nText\n"),
+        ]);
+
+        let builder = SummaryBuilder::new()
+            .readme_names(vec!["_index.md".to_string()])
+            .sort(vec!["b_guide".to_string()]);
+        let book = builder.build(&dir).unwrap();
+        let resolved = builder.resolve(&book, &dir).unwrap();
+        let indexes = builder.section_indexes(&book, &dir, &resolved).unwrap();
+
+        assert_eq!(
+            vec![
+                (
+                    "b_guide/_index.md".to_string(),
+                    "+++\ntitle = \"Guide\"\nweight = 1\n+++\nText\n".to_string()
+                ),
+                (
+                    "a_intro/_index.md".to_string(),
+                    "+++\ntitle = \"A Intro\"\nweight = 2\n+++\n".to_string()
+                ),
+            ],
+            indexes
+        );
+    }
+
+    #[test]
+    fn builder_append_new_only_test() {
+        let dir = fixture(&[
+            ("about.md", ""),
+            ("guide/a.md", ""),
+            ("guide/b.md", ""),
+            ("guide/c.md", ""),
+        ]);
+        let previous =
+            "# Summary\n\n- [Guide](#)\n    - [C](guide/c.md)\n    - [A](guide/a.md)\n- [About](about.md)\n";
+        std::fs::write(dir.join("SUMMARY.md"), previous).unwrap();
+
+        let summary = SummaryBuilder::new().append_new_only(true).generate(&dir);
+
+        let expected = r#"# Summary
+
+- [Guide](#)
+    - [C](guide/c.md)
+    - [A](guide/a.md)
+    - [B](guide/b.md)
+- [About](about.md)
+"#;
+        assert_eq!(Ok(expected.to_string()), summary);
+    }
+
+    #[test]
+    fn builder_non_utf8_test() {
+        let dir = fixture(&[("about.md", "# About\n\nSee [Caf\u{e9}](cafe.md). TODO\n")]);
+        // Latin-1 export
+        std::fs::write(dir.join("cafe.md"), b"# Caf\xe9\n\nTODO: menu\n").unwrap();
+
+        let builder = SummaryBuilder::new()
+            .check(crate::lint::Forbidden(vec!["TODO".to_string()]))
+            .search_index(true)
+            .backlinks(true);
+        let results = builder.scan(&dir).and_then(|(book, _)| {
+            let resolved = builder.resolve(&book, &dir)?;
+            let (_, warnings) = builder.render_checked(&book, &dir, &resolved)?;
+            let search = builder.search_index_file(&book, &dir, &resolved)?;
+            Ok((warnings, search, builder.backlink_files(&book, &dir, &resolved)?))
+        });
+
+        let (warnings, search, backlinks) = results.unwrap();
+        let findings = warnings.iter().filter(|w| matches!(w, Warning::Lint(..))).count();
+        assert_eq!(2, findings);
+        assert!(search.unwrap().1.contains("cafe.md"));
+        assert!(backlinks.is_empty());
+        let reported = builder.reported();
+        assert!(matches!(&reported[..], [Warning::Unreadable(path, _)] if path.ends_with("cafe.md")));
+    }
+
+    #[test]
+    fn builder_mdbook_ignore_test() {
+        let dir = fixture(&[
+            ("about.md", ""),
+            ("drafts/idea.md", ""),
+            ("notes.tmp.md", ""),
+            (".mdbookignore", "# not published\ndrafts/\n*.tmp.md\n"),
+        ]);
+
+        let summary = SummaryBuilder::new().generate(&dir);
+
+        assert_eq!(Ok("# Summary\n\n- [About](about.md)\n".to_string()), summary);
+    }
+
+    #[test]
+    fn builder_renumbering_test() {
+        let dir = fixture(&[
+            ("a.md", ""),
+            ("b.md", ""),
+            ("c.md", ""),
+            ("SUMMARY.md", "# Summary\n\n- [A](a.md)\n- [C](c.md)\n"),
+        ]);
+
+        let builder = SummaryBuilder::new().renumbering(true);
+        let rendered = builder
+            .scan(&dir)
+            .and_then(|(book, _)| builder.render_checked(&book, &dir, &builder.resolve(&book, &dir)?));
+
+        let (_, warnings) = rendered.unwrap();
+        assert_eq!(vec![Warning::Renumbered("c.md".to_string(), "2".to_string(), "3".to_string())], warnings);
+    }
+
+    #[test]
+    fn builder_changelog_test() {
+        let dir = fixture(&[("about.md", "")]);
+
+        let builder = SummaryBuilder::new().changelog("CHANGELOG_NAV.md");
+        let first = builder.generate(&dir).unwrap();
+        std::fs::write(dir.join("guide.md"), "").unwrap();
+        let second = builder.generate(&dir).unwrap();
+        let changes = crate::changelog::diff(&first, &second, &[]);
+        let changelog = crate::changelog::prepend(None, "2024-03-01", &changes);
+        std::fs::write(dir.join("CHANGELOG_NAV.md"), changelog).unwrap();
+        let third = builder.generate(&dir);
+
+        assert_eq!(vec![("Guide".to_string(), "guide.md".to_string())], changes.added);
+        assert_eq!(Ok(second), third);
+    }
+
+    #[test]
+    fn builder_dir_not_found_test() {
+        assert_eq!(
+            Err(Box::new(SummaryError::DirNotFound(PathBuf::from(
+                "./not-existing"
+            )))),
+            SummaryBuilder::new().generate(Path::new("./not-existing"))
+        );
+    }
+}
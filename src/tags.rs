@@ -0,0 +1,159 @@
+use crate::book::{file_title, Chapter};
+use crate::frontmatter::{read_head, FrontMatter};
+use crate::render::target;
+use crate::slug::Slugger;
+use crate::title::Titles;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Directory of the generated tag pages, relative to the notes dir
+pub const TAG_DIR: &str = "tags";
+
+/// A tag of the notes with the files bearing it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tag {
+    /// The tag as first written in a front matter
+    pub name: String,
+    pub files: Vec<String>,
+}
+
+/// Tags keyed by their slug, so `Rust` and `rust` are the same tag
+pub type Tags = BTreeMap<String, Tag>;
+
+/// Collect the `tags` of the front matter of all markdown files of `book`,
+/// reading at most `max_bytes` of each file
+pub fn collect(book: &Chapter, root: &Path, slugger: &Slugger, max_bytes: Option<u64>) -> Tags {
+    let mut tags = Tags::new();
+    collect_chapter(book, root, slugger, max_bytes, &mut tags);
+    tags
+}
+
+fn collect_chapter(
+    chapter: &Chapter,
+    root: &Path,
+    slugger: &Slugger,
+    max_bytes: Option<u64>,
+    tags: &mut Tags,
+) {
+    for file in chapter.files.iter().filter(|f| f.to_lowercase().ends_with(".md")) {
+        let names = read_head(&root.join(file), max_bytes)
+            .ok()
+            .and_then(|content| FrontMatter::parse(&content))
+            .and_then(|front_matter| front_matter.get_list("tags"))
+            .unwrap_or_default();
+        for name in names.iter().map(|n| n.trim()).filter(|n| !n.is_empty()) {
+            let slug = slugger.slug(name);
+            if slug.is_empty() {
+                continue;
+            }
+            let tag = tags.entry(slug).or_insert_with(|| Tag {
+                name: name.to_string(),
+                files: vec![],
+            });
+            if !tag.files.contains(file) {
+                tag.files.push(file.clone());
+            }
+        }
+    }
+    for c in &chapter.chapter {
+        collect_chapter(c, root, slugger, max_bytes, tags);
+    }
+}
+
+/// Path of the page of the tag with `slug`
+pub fn page(slug: &str) -> String {
+    index_page(TAG_DIR, slug)
+}
+
+/// The tag index chapter: its README lists all tags, followed by a page per tag
+pub fn chapter(tags: &Tags) -> Chapter {
+    index_chapter(TAG_DIR, tags.keys())
+}
+
+/// The README titled `heading` and the page of each tag as `(path, content)`,
+/// the pages link the notes with their resolved `titles`
+pub fn pages(tags: &Tags, heading: &str, titles: &Titles) -> Vec<(String, String)> {
+    let entries = tags.iter().map(|(slug, tag)| (slug.as_str(), tag.name.as_str(), tag.files.as_slice()));
+    index_pages(TAG_DIR, heading, entries, titles)
+}
+
+// Path of the page with `slug` of the generated index in `dir`
+pub(crate) fn index_page(dir: &str, slug: &str) -> String {
+    format!("{}/{}.md", dir, slug)
+}
+
+// Chapter of the generated index in `dir`: its README, followed by the page of each slug
+pub(crate) fn index_chapter<'a>(dir: &str, slugs: impl Iterator<Item = &'a String>) -> Chapter {
+    let mut files = vec![format!("{}/README.md", dir)];
+    files.extend(slugs.map(|slug| index_page(dir, slug)));
+    Chapter {
+        name: dir.to_string(),
+        files,
+        chapter: vec![],
+        order: vec![],
+    }
+}
+
+// The README titled `heading` of the generated index in `dir` and the page of each
+// entry `(slug, name, files)`, listing the files with their resolved `titles`
+pub(crate) fn index_pages<'a>(
+    dir: &str,
+    heading: &str,
+    entries: impl Iterator<Item = (&'a str, &'a str, &'a [String])>,
+    titles: &Titles,
+) -> Vec<(String, String)> {
+    let mut readme = format!("# {}\n\n", heading);
+    let mut pages = vec![];
+    for (slug, name, files) in entries {
+        readme += &format!("- [{}]({}.md) ({})\n", name, slug, files.len());
+        let mut content = format!("# {}\n\n", name);
+        for file in files {
+            content += &format!("- [{}](../{})\n", file_title(titles, file), target(file));
+        }
+        pages.push((index_page(dir, slug), content));
+    }
+    pages.insert(0, (format!("{}/README.md", dir), readme));
+    pages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::fixture;
+
+    #[test]
+    fn tags_test() {
+        let dir = fixture(&[
+            ("about.md", "---\ntags: [Rust, cli]\n---\n"),
+            ("guide/setup.md", "---\ntags:\n  - rust\n---\n"),
+        ]);
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &["about.md".to_string(), "guide/setup.md".to_string()],
+        );
+
+        let tags = collect(&book, &dir, &Slugger::default(), None);
+
+        assert_eq!(vec!["cli", "rust"], tags.keys().collect::<Vec<_>>());
+        assert_eq!(vec!["about.md", "guide/setup.md"], tags["rust"].files);
+        assert_eq!(
+            vec!["tags/README.md", "tags/cli.md", "tags/rust.md"],
+            chapter(&tags).files
+        );
+
+        let mut titles = Titles::new();
+        titles.insert("guide/setup.md".to_string(), "Getting started".to_string());
+        let pages = pages(&tags, "Tags", &titles);
+        assert_eq!(
+            ("tags/README.md".to_string(), "# Tags\n\n- [cli](cli.md) (1)\n- [Rust](rust.md) (2)\n".to_string()),
+            pages[0]
+        );
+        assert_eq!(
+            (
+                "tags/rust.md".to_string(),
+                "# Rust\n\n- [About](../about.md)\n- [Getting started](../guide/setup.md)\n".to_string()
+            ),
+            pages[2]
+        );
+    }
+}
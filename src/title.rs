@@ -0,0 +1,693 @@
+use crate::book::{title_words, Chapter};
+use titlecase::titlecase;
+use crate::frontmatter::{self, FrontMatter};
+use crate::normalize::nfc;
+use crate::progress::Progress;
+use crate::warning::Warning;
+use crate::{Result, SummaryError};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Resolved titles keyed by the entry path (relative to the notes dir)
+pub type Titles = HashMap<String, String>;
+
+/// Source of titles for files and chapters.
+///
+/// `entry` is the path of a file or chapter directory relative to `root`.
+/// Providers return `None` when they have no title for an entry, in which
+/// case the next provider in the chain is asked.
+pub trait TitleProvider {
+    fn title(&self, root: &Path, entry: &str) -> Option<String>;
+
+    /// Same as `title`, but tells why an entry couldn't be read. The next
+    /// provider is asked then as well.
+    fn try_title(&self, root: &Path, entry: &str) -> std::result::Result<Option<String>, Warning> {
+        Ok(self.title(root, entry))
+    }
+}
+
+/// Title from the file or chapter name (`my_file.md` -> `My File`)
+pub struct FilenameTitle(pub Dots, pub TitleStyle);
+
+impl TitleProvider for FilenameTitle {
+    fn title(&self, _root: &Path, entry: &str) -> Option<String> {
+        let path = Path::new(entry);
+        let name = match path.extension() {
+            Some(_) => path.file_stem(),
+            None => path.file_name(),
+        }?;
+        Some(self.0.styled_title(name.to_str()?, self.1))
+    }
+}
+
+/// Capitalization of titles derived from file and chapter names
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum TitleStyle {
+    /// `Getting Started With Rust`
+    #[default]
+    Title,
+    /// `Getting started with rust`, as many style guides want navigation labels
+    Sentence,
+    /// `getting started with Rust`, as written in the name
+    AsIs,
+}
+
+impl FromStr for TitleStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "title" => Ok(TitleStyle::Title),
+            "sentence" => Ok(TitleStyle::Sentence),
+            "asis" | "as-is" => Ok(TitleStyle::AsIs),
+            _ => Err(format!("Invalid title style {}, expected one of: title, sentence, asis", s)),
+        }
+    }
+}
+
+impl TitleStyle {
+    /// Title of the file or chapter `name` (`01_getting-started`) in this style
+    pub fn apply(&self, name: &str) -> String {
+        let words = title_words(name);
+        match self {
+            TitleStyle::Title => titlecase(&words),
+            TitleStyle::Sentence => {
+                let mut chars = words.trim().chars();
+                let first = chars.next().into_iter().flat_map(char::to_uppercase);
+                first.chain(chars.flat_map(char::to_lowercase)).collect()
+            }
+            TitleStyle::AsIs => words.trim().to_string(),
+        }
+    }
+}
+
+/// What to do with an inner extension of a file name (`setup.sh.md`) when
+/// deriving the title from it. Dots between digits (`1.2`) are always kept.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Dots {
+    /// Part of the title
+    #[default]
+    Keep,
+    /// `Setup`
+    Strip,
+    /// `Setup: Sh`
+    Subtitle,
+}
+
+impl FromStr for Dots {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "keep" => Ok(Dots::Keep),
+            "strip" => Ok(Dots::Strip),
+            "subtitle" => Ok(Dots::Subtitle),
+            _ => Err(format!("Invalid dots {}, expected one of: keep, strip, subtitle", s)),
+        }
+    }
+}
+
+impl Dots {
+    /// Title cased `name`, its inner extension handled as configured
+    pub fn title(&self, name: &str) -> String {
+        self.styled_title(name, TitleStyle::Title)
+    }
+
+    /// Title of `name` in `style`, its inner extension handled as configured
+    pub fn styled_title(&self, name: &str, style: TitleStyle) -> String {
+        let chars = name.char_indices().collect::<Vec<_>>();
+        let dot = (1..chars.len().saturating_sub(1)).rev().find(|&i| {
+            chars[i].1 == '.' && !(chars[i - 1].1.is_ascii_digit() && chars[i + 1].1.is_ascii_digit())
+        });
+        match (self, dot.map(|i| chars[i].0)) {
+            (Dots::Strip, Some(dot)) => style.apply(&name[..dot]),
+            (Dots::Subtitle, Some(dot)) => {
+                format!("{}: {}", style.apply(&name[..dot]), style.apply(&name[dot + 1..]))
+            }
+            _ => style.apply(name),
+        }
+    }
+}
+
+/// Titles of files and chapters named without any letters (`2024.md`, `0001.md`)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum NumericNames {
+    /// `2024`, the name as it is
+    #[default]
+    Keep,
+    /// `Journal 2024`, the title of the parent chapter and the number
+    Parent,
+    /// The header, bold line or first sentence of the file, the name as fallback
+    Content,
+}
+
+impl FromStr for NumericNames {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "keep" => Ok(NumericNames::Keep),
+            "parent" => Ok(NumericNames::Parent),
+            "content" => Ok(NumericNames::Content),
+            _ => Err(format!("Invalid numeric names {}, expected one of: keep, parent, content", s)),
+        }
+    }
+}
+
+/// Title of entries named without any letters as configured by [`NumericNames`],
+/// `None` for all other entries. Files are read for at most the given number of bytes.
+pub struct NumericTitle(pub NumericNames, pub Dots, pub TitleStyle, pub Option<u64>);
+
+impl TitleProvider for NumericTitle {
+    fn title(&self, root: &Path, entry: &str) -> Option<String> {
+        self.try_title(root, entry).ok().flatten()
+    }
+
+    fn try_title(&self, root: &Path, entry: &str) -> std::result::Result<Option<String>, Warning> {
+        let path = Path::new(entry);
+        let name = match path.extension() {
+            Some(_) => path.file_stem(),
+            None => path.file_name(),
+        }
+        .and_then(|n| n.to_str())
+        .filter(|n| !n.chars().any(char::is_alphabetic));
+        let name = match name {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+        match self.0 {
+            NumericNames::Keep => Ok(None),
+            NumericNames::Parent => {
+                let number = match name.trim_start_matches('0') {
+                    "" => "0",
+                    number if number.starts_with(|c: char| c.is_ascii_digit()) => number,
+                    _ => name,
+                };
+                let parent = path.parent().and_then(|p| p.file_name()).and_then(|p| p.to_str());
+                Ok(parent.map(|parent| {
+                    format!("{} {}", self.1.styled_title(parent, self.2), number)
+                }))
+            }
+            NumericNames::Content => {
+                let content = read_entry(root, entry, self.3)?;
+                Ok(content.and_then(|content| {
+                    let content = frontmatter::strip(&content);
+                    get_header(content)
+                        .or_else(|| get_bold(content))
+                        .or_else(|| get_sentence(content))
+                }))
+            }
+        }
+    }
+}
+
+/// Title from the first `# Header` of a markdown file, reading at most the
+/// given number of bytes
+pub struct HeaderTitle(pub Option<u64>);
+
+impl TitleProvider for HeaderTitle {
+    fn title(&self, root: &Path, entry: &str) -> Option<String> {
+        self.try_title(root, entry).ok().flatten()
+    }
+
+    fn try_title(&self, root: &Path, entry: &str) -> std::result::Result<Option<String>, Warning> {
+        let content = read_entry(root, entry, self.0)?;
+        Ok(content.and_then(|content| get_header(frontmatter::strip(&content))))
+    }
+}
+
+/// Title from the `title` key of the front matter of a markdown file, reading
+/// at most the given number of bytes
+pub struct FrontMatterTitle(pub Option<u64>);
+
+impl TitleProvider for FrontMatterTitle {
+    fn title(&self, root: &Path, entry: &str) -> Option<String> {
+        self.try_title(root, entry).ok().flatten()
+    }
+
+    fn try_title(&self, root: &Path, entry: &str) -> std::result::Result<Option<String>, Warning> {
+        Ok(read_entry(root, entry, self.0)?.and_then(|content| {
+            FrontMatter::parse(&content)?
+                .get_str("title")
+                .filter(|t| !t.is_empty())
+                .map(|t| t.to_string())
+        }))
+    }
+}
+
+/// Title from the first line of a markdown file that is bold as a whole
+/// (`**Meeting notes**`), reading at most the given number of bytes
+pub struct BoldTitle(pub Option<u64>);
+
+impl TitleProvider for BoldTitle {
+    fn title(&self, root: &Path, entry: &str) -> Option<String> {
+        self.try_title(root, entry).ok().flatten()
+    }
+
+    fn try_title(&self, root: &Path, entry: &str) -> std::result::Result<Option<String>, Warning> {
+        let content = read_entry(root, entry, self.0)?;
+        Ok(content.and_then(|content| get_bold(frontmatter::strip(&content))))
+    }
+}
+
+/// Title from the first sentence of the text of a markdown file, cut at a word
+/// after [`SENTENCE_CHARS`], reading at most the given number of bytes
+pub struct SentenceTitle(pub Option<u64>);
+
+impl TitleProvider for SentenceTitle {
+    fn title(&self, root: &Path, entry: &str) -> Option<String> {
+        self.try_title(root, entry).ok().flatten()
+    }
+
+    fn try_title(&self, root: &Path, entry: &str) -> std::result::Result<Option<String>, Warning> {
+        let content = read_entry(root, entry, self.0)?;
+        Ok(content.and_then(|content| get_sentence(frontmatter::strip(&content))))
+    }
+}
+
+/// Titles from a TOML mapping file (`"chapter1/file.md" = "My Title"`)
+pub struct MappingTitle {
+    titles: Titles,
+}
+
+impl MappingTitle {
+    pub fn new(titles: Titles) -> Self {
+        MappingTitle { titles }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| SummaryError::Io(path.to_path_buf(), e.to_string()))?;
+        let values = content
+            .parse::<toml::Value>()
+            .map_err(|e| SummaryError::Config(path.to_path_buf(), e.to_string()))?;
+
+        let mut titles = Titles::new();
+        if let Some(table) = values.as_table() {
+            for (entry, title) in table {
+                match title.as_str() {
+                    Some(title) => {
+                        titles.insert(entry.trim_end_matches('/').to_string(), title.to_string())
+                    }
+                    None => {
+                        return Err(Box::new(SummaryError::Config(
+                            path.to_path_buf(),
+                            format!("title for `{}` is not a string", entry),
+                        )))
+                    }
+                };
+            }
+        }
+        Ok(MappingTitle { titles })
+    }
+}
+
+impl TitleProvider for MappingTitle {
+    fn title(&self, _root: &Path, entry: &str) -> Option<String> {
+        self.titles.get(entry).cloned()
+    }
+}
+
+/// Built-in title providers selectable from the command line
+#[derive(Debug, Clone, PartialEq)]
+pub enum TitleSource {
+    Filename,
+    Header,
+    FrontMatter,
+    Bold,
+    Sentence,
+    Map(PathBuf),
+}
+
+impl FromStr for TitleSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "filename" => Ok(TitleSource::Filename),
+            "h1" | "header" => Ok(TitleSource::Header),
+            "frontmatter" => Ok(TitleSource::FrontMatter),
+            "bold" => Ok(TitleSource::Bold),
+            "sentence" => Ok(TitleSource::Sentence),
+            _ => match s.strip_prefix("map:") {
+                Some(path) => Ok(TitleSource::Map(PathBuf::from(path))),
+                None => Err(format!(
+                    "Invalid title source {}, expected one of: filename, h1, frontmatter, bold, sentence, \
+                     map:<file>",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+impl TitleSource {
+    pub fn provider(&self) -> Result<Box<dyn TitleProvider>> {
+        self.provider_with(Dots::default(), TitleStyle::default(), None)
+    }
+
+    /// Provider handling inner extensions of file names with `dots`, writing titles
+    /// from names in `style` and reading at most `max_scan_bytes` of each file
+    pub fn provider_with(
+        &self,
+        dots: Dots,
+        style: TitleStyle,
+        max_scan_bytes: Option<u64>,
+    ) -> Result<Box<dyn TitleProvider>> {
+        Ok(match self {
+            TitleSource::Filename => Box::new(FilenameTitle(dots, style)),
+            TitleSource::Header => Box::new(HeaderTitle(max_scan_bytes)),
+            TitleSource::FrontMatter => Box::new(FrontMatterTitle(max_scan_bytes)),
+            TitleSource::Bold => Box::new(BoldTitle(max_scan_bytes)),
+            TitleSource::Sentence => Box::new(SentenceTitle(max_scan_bytes)),
+            TitleSource::Map(path) => Box::new(MappingTitle::from_file(path)?),
+        })
+    }
+}
+
+// Content of the file `entry`, `None` for directories
+fn read_entry(
+    root: &Path,
+    entry: &str,
+    max_bytes: Option<u64>,
+) -> std::result::Result<Option<String>, Warning> {
+    let path = root.join(entry);
+    if path.is_dir() {
+        return Ok(None);
+    }
+    match frontmatter::read_head(&path, max_bytes) {
+        Ok(content) => Ok(Some(content)),
+        Err(err) => Err(Warning::Unreadable(path, err.to_string())),
+    }
+}
+
+fn get_header(content: &str) -> Option<String> {
+    content
+        .lines()
+        .map(|l| l.trim())
+        .find(|l| l.starts_with("# ") || *l == "#")
+        .map(|l| {
+            l.trim_start_matches('#')
+                .trim_end_matches('#')
+                .trim()
+                .to_string()
+        })
+        .filter(|l| !l.is_empty())
+}
+
+// Lines of text outside code blocks, trimmed and without blank lines
+fn text_lines(content: &str) -> impl Iterator<Item = &str> {
+    let mut fenced = false;
+    content.lines().map(|l| l.trim()).filter(move |l| {
+        if l.starts_with("```") || l.starts_with("~~~") {
+            fenced = !fenced;
+            return false;
+        }
+        !fenced && !l.is_empty()
+    })
+}
+
+fn get_bold(content: &str) -> Option<String> {
+    text_lines(content)
+        .find_map(|l| {
+            ["**", "__"].iter().find_map(|mark| {
+                let inner = l.strip_prefix(mark)?.strip_suffix(mark)?;
+                Some(inner.trim_end_matches(':').trim().to_string())
+            })
+        })
+        .filter(|l| !l.is_empty() && !l.contains("**"))
+}
+
+/// Characters of a title from the first sentence before it is cut at a word
+pub const SENTENCE_CHARS: usize = 60;
+
+fn get_sentence(content: &str) -> Option<String> {
+    let line = text_lines(content).find(|l| {
+        !l.starts_with(['#', '<', '|', '>', '!', '[', '-', '*', '+'])
+            && !l.starts_with(|c: char| c.is_ascii_digit())
+    })?;
+    let text = plain_text(line);
+    let end = text
+        .match_indices(['.', '?', '!'])
+        .map(|(i, _)| i)
+        .find(|i| text[i + 1..].is_empty() || text[i + 1..].starts_with(' '))
+        .unwrap_or(text.len());
+    let sentence = text[..end].trim();
+    if sentence.chars().count() <= SENTENCE_CHARS {
+        return Some(sentence.to_string()).filter(|s| !s.is_empty());
+    }
+    let cut = sentence.char_indices().nth(SENTENCE_CHARS).map_or(sentence.len(), |(i, _)| i);
+    let cut = sentence[..cut].rfind(' ').unwrap_or(cut);
+    Some(format!("{}…", sentence[..cut].trim_end_matches(|c: char| !c.is_alphanumeric())))
+}
+
+// `line` without emphasis, code marks and link targets
+fn plain_text(line: &str) -> String {
+    let mut text = String::new();
+    let mut rest = line;
+    while let Some(start) = rest.find("](") {
+        match rest[start..].find(')') {
+            Some(end) => {
+                text.push_str(&rest[..start]);
+                rest = &rest[start + end + 1..];
+            }
+            None => break,
+        }
+    }
+    text.push_str(rest);
+    text.chars().filter(|c| !matches!(c, '*' | '_' | '`' | '[')).collect()
+}
+
+// Named HTML entities common in exported headers
+const ENTITIES: &[(&str, &str)] = &[
+    ("amp", "&"), ("lt", "<"), ("gt", ">"), ("quot", "\""), ("apos", "'"), ("nbsp", " "),
+    ("ndash", "–"), ("mdash", "—"), ("hellip", "…"), ("copy", "©"), ("reg", "®"),
+    ("trade", "™"), ("laquo", "«"), ("raquo", "»"), ("lsquo", "‘"), ("rsquo", "’"),
+    ("ldquo", "“"), ("rdquo", "”"),
+];
+
+/// `title` with its HTML entities (`&amp;`, `&#39;`) decoded and smart quotes replaced
+/// by straight ones, which some renderers break on. Unknown entities are kept.
+pub fn plain_title(title: &str) -> String {
+    let mut decoded = String::with_capacity(title.len());
+    let mut rest = title;
+    while let Some(start) = rest.find('&') {
+        decoded += &rest[..start];
+        rest = &rest[start..];
+        let entity = rest[1..].find(';').map(|end| &rest[1..end + 1]).filter(|e| e.len() <= 8);
+        let char = entity.and_then(|entity| match entity.strip_prefix('#') {
+            Some(code) => match code.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => code.parse().ok(),
+            }
+            .and_then(char::from_u32)
+            .map(String::from),
+            None => ENTITIES.iter().find(|(name, _)| *name == entity).map(|(_, c)| c.to_string()),
+        });
+        match (entity, char) {
+            (Some(entity), Some(char)) => {
+                decoded += &char;
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded += rest;
+    decoded.replace(['‘', '’', '‚', '‛'], "'").replace(['“', '”', '„', '‟'], "\"")
+}
+
+/// Resolve the titles of all files and chapters of `book`, asking the
+/// `providers` in order. Entries without any title are left out.
+pub fn resolve(book: &Chapter, root: &Path, providers: &[&dyn TitleProvider]) -> Titles {
+    resolve_checked(book, root, providers).0
+}
+
+/// Same as `resolve`, also returning the files which couldn't be read
+pub fn resolve_checked(
+    book: &Chapter,
+    root: &Path,
+    providers: &[&dyn TitleProvider],
+) -> (Titles, Vec<Warning>) {
+    resolve_progress(book, root, providers, &mut Progress::hidden())
+}
+
+// Same as `resolve_checked`, counting each resolved file in `progress`
+pub(crate) fn resolve_progress(
+    book: &Chapter,
+    root: &Path,
+    providers: &[&dyn TitleProvider],
+    progress: &mut Progress,
+) -> (Titles, Vec<Warning>) {
+    let mut titles = Titles::new();
+    let mut warnings = vec![];
+    resolve_chapter(book, "", root, providers, &mut titles, &mut warnings, progress);
+    (titles, warnings)
+}
+
+fn resolve_chapter(
+    chapter: &Chapter,
+    path: &str,
+    root: &Path,
+    providers: &[&dyn TitleProvider],
+    titles: &mut Titles,
+    warnings: &mut Vec<Warning>,
+    progress: &mut Progress,
+) {
+    let mut find = |entry: &str| {
+        providers
+            .iter()
+            .find_map(|p| match p.try_title(root, entry) {
+                Ok(title) => title,
+                Err(warning) => {
+                    if !warnings.contains(&warning) {
+                        warnings.push(warning);
+                    }
+                    None
+                }
+            })
+            .map(|title| nfc(&title).into_owned())
+    };
+
+    if !path.is_empty() {
+        if let Some(title) = find(path) {
+            titles.insert(path.to_string(), title);
+        }
+    }
+
+    for file in &chapter.files {
+        if let Some(title) = find(file) {
+            titles.insert(file.clone(), title);
+        }
+        progress.inc();
+    }
+
+    for c in &chapter.chapter {
+        let sub = match path {
+            "" => c.name.clone(),
+            _ => format!("{}/{}", path, c.name),
+        };
+        resolve_chapter(c, &sub, root, providers, titles, warnings, progress);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::fixture;
+
+    #[test]
+    fn header_test() {
+        assert_eq!(
+            Some("Title".to_string()),
+            get_header("text\n# Title #\n## Sub")
+        );
+        assert_eq!(None, get_header("## Sub\n#hashtag"));
+    }
+
+    #[test]
+    fn bold_sentence_test() {
+        let note = "---\ntags: [x]\n---\n\n**Weekly sync:**\n\nWe talked about [the roadmap](road.md). Then lunch.";
+        assert_eq!(Some("Weekly sync".to_string()), get_bold(frontmatter::strip(note)));
+        assert_eq!(
+            Some("We talked about the roadmap".to_string()),
+            get_sentence(frontmatter::strip(note))
+        );
+        assert_eq!(None, get_bold("Some **bold** word"));
+        assert_eq!(None, get_sentence("```\ncode.\n```\n- a list"));
+        assert_eq!(
+            Some("A very long first line without any end in sight that keeps…".to_string()),
+            get_sentence("A very long first line without any end in sight that keeps on going and going")
+        );
+        assert_eq!(Some("Version 1.2 is out".to_string()), get_sentence("Version 1.2 is out! Get it."));
+    }
+
+    #[test]
+    fn numeric_title_test() {
+        let dir = fixture(&[("journal/0001.md", "Kickoff with the team. Notes follow.")]);
+
+        let title = |numeric| NumericTitle(numeric, Dots::Keep, TitleStyle::Title, None);
+        let parent = title(NumericNames::Parent).title(&dir, "journal/0001.md");
+        let content = title(NumericNames::Content).title(&dir, "journal/0001.md");
+        let named = title(NumericNames::Parent).title(&dir, "journal/kickoff.md");
+        let top = title(NumericNames::Parent).title(&dir, "2024.md");
+
+        assert_eq!(Some("Journal 1".to_string()), parent);
+        assert_eq!(Some("Kickoff with the team".to_string()), content);
+        assert_eq!(None, named);
+        assert_eq!(None, top);
+        assert_eq!(None, title(NumericNames::Keep).title(&dir, "2024.md"));
+        assert_eq!(Ok(NumericNames::Content), "content".parse());
+    }
+
+    #[test]
+    fn plain_title_test() {
+        assert_eq!("Tom & Jerry's \"Best\"", plain_title("Tom &amp; Jerry&#39;s “Best”"));
+        assert_eq!("Don't <panic> — A&B", plain_title("Don&rsquo;t &lt;panic&gt; &mdash; A&B"));
+        assert_eq!("&unknown; & A", plain_title("&unknown; & &#x41;"));
+    }
+
+    #[test]
+    fn provider_chain_test() {
+        let book = Chapter::new(
+            "Summary".to_string(),
+            &["about.md".to_string(), "chapter1/file1.md".to_string()],
+        );
+        let mut mapping = Titles::new();
+        mapping.insert("chapter1".to_string(), "First Chapter".to_string());
+
+        let mapping = MappingTitle::new(mapping);
+        let titles = resolve(&book, Path::new("."), &[&mapping, &FilenameTitle(Dots::Keep, TitleStyle::Title)]);
+
+        assert_eq!(Some(&"About".to_string()), titles.get("about.md"));
+        assert_eq!(Some(&"First Chapter".to_string()), titles.get("chapter1"));
+        assert_eq!(Some(&"File1".to_string()), titles.get("chapter1/file1.md"));
+    }
+
+    #[test]
+    fn max_scan_bytes_test() {
+        let dir = fixture(&[]);
+        fs::write(dir.join("dump.md"), format!("{}\n# Late title", "log line\n".repeat(100))).unwrap();
+
+        let limited = HeaderTitle(Some(100)).title(&dir, "dump.md");
+        let unlimited = HeaderTitle(None).title(&dir, "dump.md");
+
+        assert_eq!(None, limited);
+        assert_eq!(Some("Late title".to_string()), unlimited);
+    }
+
+    #[test]
+    fn dots_test() {
+        assert_eq!(crate::book::make_title_case("setup.sh"), Dots::Keep.title("setup.sh"));
+        assert_eq!("Setup", Dots::Strip.title("setup.sh"));
+        assert_eq!("Setup: Sh", Dots::Subtitle.title("setup.sh"));
+        assert_eq!("Release 1.2", Dots::Strip.title("release-1.2"));
+        assert_eq!("Notes", Dots::Strip.title("notes"));
+    }
+
+    #[test]
+    fn title_style_test() {
+        let name = "01_getting-started-with-Rust";
+        assert_eq!("Getting Started With Rust", TitleStyle::Title.apply(name));
+        assert_eq!("Getting started with rust", TitleStyle::Sentence.apply(name));
+        assert_eq!("getting started with Rust", TitleStyle::AsIs.apply(name));
+        assert_eq!("Setup: Sh", Dots::Subtitle.styled_title("setup.sh", TitleStyle::Sentence));
+        assert_eq!(Ok(TitleStyle::AsIs), "asis".parse());
+        assert!("upper".parse::<TitleStyle>().is_err());
+    }
+
+    #[test]
+    fn title_source_test() {
+        assert_eq!(Ok(TitleSource::Header), "h1".parse());
+        assert_eq!(Ok(TitleSource::Sentence), "sentence".parse());
+        assert_eq!(
+            Ok(TitleSource::Map(PathBuf::from("titles.toml"))),
+            "map:titles.toml".parse()
+        );
+        assert!("unknown".parse::<TitleSource>().is_err());
+    }
+}
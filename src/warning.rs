@@ -0,0 +1,228 @@
+use crate::book::Chapter;
+use crate::render::{self, RenderContext};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Problem found while generating a summary, which doesn't stop the generation
+/// unless in strict mode
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    /// Entries of the same chapter with the same title
+    DuplicateTitle(String, Vec<String>),
+    /// A directory without any entry for the summary
+    EmptyChapter(String),
+    /// Entries whose paths only differ in case
+    CaseConflict(String, String),
+    /// A file or directory which could not be read
+    Unreadable(PathBuf, String),
+    /// A file or directory name which is not valid UTF-8, left out
+    NonUtf8Name(PathBuf),
+    /// A construct of a file the target of a migration doesn't support
+    Unsupported(String, String),
+    /// A finding of a check of the content of a file at a line, 0 for the whole file
+    Lint(String, usize, String),
+    /// A file or directory with a `#` in its name, which links take for an anchor unless
+    /// escaped
+    HashInName(String),
+    /// A chapter mdBook numbers differently than in the previous summary, with the old
+    /// and the new number
+    Renumbered(String, String, String),
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Warning::DuplicateTitle(title, paths) => {
+                write!(f, "Title `{}` is used by {}", title, paths.join(", "))
+            }
+            Warning::EmptyChapter(path) => write!(f, "Chapter {} has no entries", path),
+            Warning::CaseConflict(a, b) => write!(f, "{} and {} only differ in case", a, b),
+            Warning::Unreadable(path, why) => {
+                write!(f, "Couldn't read {}: {}", path.display(), why)
+            }
+            Warning::NonUtf8Name(path) => {
+                write!(f, "Name of {} is not valid UTF-8", path.display())
+            }
+            Warning::Unsupported(file, construct) => {
+                write!(f, "{} uses {}, which is not supported", file, construct)
+            }
+            Warning::Lint(file, 0, message) => write!(f, "{}: {}", file, message),
+            Warning::Lint(file, line, message) => write!(f, "{}:{}: {}", file, line, message),
+            Warning::HashInName(path) => {
+                write!(f, "{} has a `#` in its name, it is linked as `%23`", path)
+            }
+            Warning::Renumbered(path, old, new) => {
+                write!(f, "{} is renumbered by mdBook from {} to {}", path, old, new)
+            }
+        }
+    }
+}
+
+impl Warning {
+    /// Kind of the warning, e.g. `duplicate-title`
+    pub fn kind(&self) -> &str {
+        match self {
+            Warning::DuplicateTitle(..) => "duplicate-title",
+            Warning::EmptyChapter(_) => "empty-chapter",
+            Warning::CaseConflict(..) => "case-conflict",
+            Warning::Unreadable(..) => "unreadable",
+            Warning::NonUtf8Name(_) => "non-utf8-name",
+            Warning::Unsupported(..) => "unsupported",
+            Warning::Lint(..) => "lint",
+            Warning::HashInName(_) => "hash-in-name",
+            Warning::Renumbered(..) => "renumbered",
+        }
+    }
+
+    /// The entry the warning is about relative to the notes dir, with the line of a lint
+    /// finding (0 for the whole entry)
+    pub fn location(&self) -> (String, usize) {
+        match self {
+            Warning::DuplicateTitle(_, paths) => (paths.first().cloned().unwrap_or_default(), 0),
+            Warning::EmptyChapter(path) | Warning::CaseConflict(_, path) => (path.clone(), 0),
+            Warning::Unreadable(path, _) | Warning::NonUtf8Name(path) => {
+                (path.to_string_lossy().replace('\\', "/"), 0)
+            }
+            Warning::Unsupported(file, _)
+            | Warning::HashInName(file)
+            | Warning::Renumbered(file, ..) => (file.clone(), 0),
+            Warning::Lint(file, line, _) => (file.clone(), *line),
+        }
+    }
+}
+
+/// Case conflicts between the scanned `entries` and their directories
+pub(crate) fn case_conflicts(entries: &[String]) -> Vec<Warning> {
+    let mut paths: Vec<&str> = vec![];
+    for entry in entries {
+        // the directories of an entry conflict as well
+        for (i, _) in entry.match_indices('/') {
+            paths.push(&entry[..i]);
+        }
+        paths.push(entry);
+    }
+    paths.dedup();
+
+    let mut seen: HashMap<String, &str> = HashMap::new();
+    let mut warnings = vec![];
+    for path in paths {
+        match seen.get(&path.to_lowercase()) {
+            Some(other) if *other != path => {
+                let warning = Warning::CaseConflict(other.to_string(), path.to_string());
+                if !warnings.contains(&warning) {
+                    warnings.push(warning);
+                }
+            }
+            Some(_) => {}
+            None => {
+                seen.insert(path.to_lowercase(), path);
+            }
+        }
+    }
+    warnings
+}
+
+/// The scanned `entries` and their directories with a `#` in their name, each once
+pub(crate) fn hash_names(entries: &[String]) -> Vec<Warning> {
+    let mut warnings = vec![];
+    for entry in entries {
+        let ends = entry.match_indices('/').map(|(i, _)| i).chain(std::iter::once(entry.len()));
+        for path in ends.map(|i| &entry[..i]) {
+            let name = path.rsplit('/').next().unwrap();
+            let warning = Warning::HashInName(path.to_string());
+            if name.contains('#') && !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+        }
+    }
+    warnings
+}
+
+/// Chapters of the mdBook `summary` numbered differently than in the `previous` one,
+/// shifted by new or removed drafts, separators or chapters before them
+pub(crate) fn renumbered(previous: &str, summary: &str) -> Vec<Warning> {
+    let numbers = render::mdbook_numbers(previous).into_iter().collect::<HashMap<_, _>>();
+    render::mdbook_numbers(summary)
+        .into_iter()
+        .filter_map(|(path, number)| match numbers.get(&path) {
+            Some(old) if *old != number => Some(Warning::Renumbered(path, old.clone(), number)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Pages of the same chapter with the same title
+pub(crate) fn duplicate_titles(chapter: &Chapter, context: &RenderContext) -> Vec<Warning> {
+    let mut by_title: Vec<(String, Vec<String>)> = vec![];
+    for file in chapter.pages(context.readme_names) {
+        let name = Path::new(file).file_stem().unwrap().to_str().unwrap();
+        let title = context.title(file, name);
+        match by_title.iter_mut().find(|(t, _)| *t == title) {
+            Some((_, files)) => files.push(file.clone()),
+            None => by_title.push((title, vec![file.clone()])),
+        }
+    }
+
+    let mut warnings = by_title
+        .into_iter()
+        .filter(|(_, files)| files.len() > 1)
+        .map(|(title, files)| Warning::DuplicateTitle(title, files))
+        .collect::<Vec<_>>();
+    for c in &chapter.chapter {
+        warnings.extend(duplicate_titles(c, context));
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn case_conflicts_test() {
+        let entries = vec![
+            "Chapter/a.md".to_string(),
+            "chapter/b.md".to_string(),
+            "chapter/B.md".to_string(),
+            "other/file.md".to_string(),
+        ];
+        assert_eq!(
+            vec![
+                Warning::CaseConflict("Chapter".to_string(), "chapter".to_string()),
+                Warning::CaseConflict("chapter/b.md".to_string(), "chapter/B.md".to_string()),
+            ],
+            case_conflicts(&entries)
+        );
+    }
+
+    #[test]
+    fn renumbered_test() {
+        let previous = "# Summary\n\n[Intro](intro.md)\n\n- [Guide](guide/README.md)\n    - [Setup](guide/setup.md)\n\
+                        - [Reference](reference.md)\n";
+        let summary = "# Summary\n\n[Intro](intro.md)\n\n- [Guide](guide/README.md)\n    - [Install]()\n    \
+                       - [Setup](guide/setup.md)\n\n---\n\n- [Reference](reference.md)\n";
+
+        assert_eq!(
+            vec![Warning::Renumbered("guide/setup.md".to_string(), "1.1".to_string(), "1.2".to_string())],
+            renumbered(previous, summary)
+        );
+        assert!(renumbered(summary, summary).is_empty());
+    }
+
+    #[test]
+    fn hash_names_test() {
+        let entries = vec![
+            "C#/basics.md".to_string(),
+            "C#/F# notes.md".to_string(),
+            "csharp.md".to_string(),
+        ];
+        assert_eq!(
+            vec![
+                Warning::HashInName("C#".to_string()),
+                Warning::HashInName("C#/F# notes.md".to_string()),
+            ],
+            hash_names(&entries)
+        );
+    }
+}